@@ -0,0 +1,390 @@
+//! Account parsing and validation of the claim instructions.
+use light_compressed_account::constants::REGISTERED_PROGRAM_PDA;
+use light_token::constants::{
+    ACCOUNT_COMPRESSION_AUTHORITY_PDA, ACCOUNT_COMPRESSION_PROGRAM_ID, CPI_AUTHORITY_PDA,
+    LIGHT_SYSTEM_PROGRAM_ID, NOOP_PROGRAM_ID,
+};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+    pubkey::Pubkey, sysvar,
+};
+
+use crate::error::ClaimError;
+
+/// Where the claimed tokens go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimDestination {
+    /// Decompress to an SPL token account.
+    Decompress,
+    /// Transfer compressed tokens to the claimant.
+    Compressed,
+}
+
+impl ClaimDestination {
+    pub fn num_accounts(self) -> usize {
+        match self {
+            ClaimDestination::Decompress => 16,
+            ClaimDestination::Compressed => 13,
+        }
+    }
+}
+
+/// Accounts only passed by claims that decompress.
+pub struct DecompressAccountInfos<'a, 'info> {
+    pub token_pool_pda: &'a AccountInfo<'info>,
+    pub decompress_destination: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+/// Accounts of the claim, clawback and distributor claim instructions,
+/// see `build_claim_and_decompress_instruction` and `build_claim_compressed_instruction`
+/// for the layouts.
+pub struct ClaimAccountInfos<'a, 'info> {
+    /// The clawback authority for clawbacks.
+    pub claimant: &'a AccountInfo<'info>,
+    pub fee_payer: &'a AccountInfo<'info>,
+    /// The distributor PDA for distributor claims.
+    pub associated_airdrop_pda: &'a AccountInfo<'info>,
+    pub ctoken_cpi_authority_pda: &'a AccountInfo<'info>,
+    pub light_system_program: &'a AccountInfo<'info>,
+    pub registered_program_pda: &'a AccountInfo<'info>,
+    pub noop_program: &'a AccountInfo<'info>,
+    pub account_compression_authority: &'a AccountInfo<'info>,
+    pub account_compression_program: &'a AccountInfo<'info>,
+    pub ctoken_program: &'a AccountInfo<'info>,
+    /// `None` for claims to compressed tokens.
+    pub decompress: Option<DecompressAccountInfos<'a, 'info>>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub state_tree: &'a AccountInfo<'info>,
+    pub queue: &'a AccountInfo<'info>,
+    /// Set for relayed claims, the claimant authorizes them with an ed25519 instruction.
+    pub instructions_sysvar: Option<&'a AccountInfo<'info>>,
+    /// Accounts after the claim accounts,
+    /// e.g. the light system accounts of a claim record or receipt.
+    pub remaining_accounts: &'a [AccountInfo<'info>],
+    /// All accounts, passed on to the compressed token program.
+    pub account_infos: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> ClaimAccountInfos<'a, 'info> {
+    /// Checks the number of accounts, signers, fixed program IDs and writability.
+    ///
+    /// Claims that create or update a compressed PDA (claim record or receipt) pass
+    /// its light system accounts after the claim accounts (`light_system_accounts`),
+    /// all other claims pass exactly the claim accounts.
+    /// The associated airdrop PDA is checked against its seeds by the processor.
+    pub fn parse(
+        accounts: &'a [AccountInfo<'info>],
+        destination: ClaimDestination,
+        light_system_accounts: bool,
+    ) -> Result<Self, ProgramError> {
+        Self::parse_layout(accounts, destination, false, light_system_accounts)
+    }
+
+    /// Parses the accounts of a relayed claim, the claim accounts followed by
+    /// the instructions sysvar. The claimant does not sign.
+    pub fn parse_relayed(
+        accounts: &'a [AccountInfo<'info>],
+        light_system_accounts: bool,
+    ) -> Result<Self, ProgramError> {
+        Self::parse_layout(
+            accounts,
+            ClaimDestination::Decompress,
+            true,
+            light_system_accounts,
+        )
+    }
+
+    fn parse_layout(
+        accounts: &'a [AccountInfo<'info>],
+        destination: ClaimDestination,
+        relayed: bool,
+        light_system_accounts: bool,
+    ) -> Result<Self, ProgramError> {
+        let num_accounts = destination.num_accounts() + relayed as usize;
+        // CHECK:
+        if light_system_accounts && accounts.len() <= num_accounts {
+            msg!(
+                "Expected more than {} accounts, got {}",
+                num_accounts,
+                accounts.len()
+            );
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        // CHECK:
+        if !light_system_accounts && accounts.len() != num_accounts {
+            msg!("Expected {} accounts, got {}", num_accounts, accounts.len());
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let (claim_accounts, remaining_accounts) = accounts.split_at(num_accounts);
+
+        let (decompress, system_program, state_tree, queue) = match destination {
+            ClaimDestination::Decompress => (
+                Some(DecompressAccountInfos {
+                    token_pool_pda: &claim_accounts[10],
+                    decompress_destination: &claim_accounts[11],
+                    token_program: &claim_accounts[12],
+                }),
+                &claim_accounts[13],
+                &claim_accounts[14],
+                &claim_accounts[15],
+            ),
+            ClaimDestination::Compressed => (
+                None,
+                &claim_accounts[10],
+                &claim_accounts[11],
+                &claim_accounts[12],
+            ),
+        };
+        let instructions_sysvar = relayed.then(|| &claim_accounts[num_accounts - 1]);
+        let claim_account_infos = Self {
+            claimant: &claim_accounts[0],
+            fee_payer: &claim_accounts[1],
+            associated_airdrop_pda: &claim_accounts[2],
+            ctoken_cpi_authority_pda: &claim_accounts[3],
+            light_system_program: &claim_accounts[4],
+            registered_program_pda: &claim_accounts[5],
+            noop_program: &claim_accounts[6],
+            account_compression_authority: &claim_accounts[7],
+            account_compression_program: &claim_accounts[8],
+            ctoken_program: &claim_accounts[9],
+            decompress,
+            system_program,
+            state_tree,
+            queue,
+            instructions_sysvar,
+            remaining_accounts,
+            account_infos: accounts,
+        };
+        claim_account_infos.check()?;
+        Ok(claim_account_infos)
+    }
+
+    fn check(&self) -> ProgramResult {
+        match self.instructions_sysvar {
+            // CHECK:
+            Some(instructions_sysvar) => check_key(
+                instructions_sysvar,
+                &sysvar::instructions::ID,
+                ClaimError::InvalidInstructionsSysvar,
+            )?,
+            // CHECK:
+            None => check_signer(self.claimant, "Claimant")?,
+        }
+        // CHECK:
+        check_signer(self.fee_payer, "Fee payer")?;
+        // CHECK:
+        check_key(
+            self.ctoken_cpi_authority_pda,
+            &CPI_AUTHORITY_PDA,
+            ClaimError::InvalidCTokenCpiAuthority,
+        )?;
+        // CHECK:
+        check_key(
+            self.light_system_program,
+            &LIGHT_SYSTEM_PROGRAM_ID,
+            ClaimError::InvalidLightSystemProgram,
+        )?;
+        // CHECK:
+        check_key(
+            self.registered_program_pda,
+            &Pubkey::new_from_array(REGISTERED_PROGRAM_PDA),
+            ClaimError::InvalidRegisteredProgramPda,
+        )?;
+        // CHECK:
+        check_key(
+            self.noop_program,
+            &NOOP_PROGRAM_ID,
+            ClaimError::InvalidNoopProgram,
+        )?;
+        // CHECK:
+        check_key(
+            self.account_compression_authority,
+            &ACCOUNT_COMPRESSION_AUTHORITY_PDA,
+            ClaimError::InvalidAccountCompressionAuthority,
+        )?;
+        // CHECK:
+        check_key(
+            self.account_compression_program,
+            &ACCOUNT_COMPRESSION_PROGRAM_ID,
+            ClaimError::InvalidAccountCompressionProgram,
+        )?;
+        // CHECK:
+        check_key(
+            self.ctoken_program,
+            &light_token::id(),
+            ClaimError::InvalidCompressedTokenProgram,
+        )?;
+        // CHECK:
+        check_key(
+            self.system_program,
+            &solana_program::system_program::ID,
+            ClaimError::InvalidSystemProgram,
+        )?;
+        if let Some(decompress) = self.decompress.as_ref() {
+            // CHECK:
+            check_token_program(decompress.token_program)?;
+            // CHECK:
+            check_writable(decompress.token_pool_pda, "Token pool PDA")?;
+            // CHECK:
+            check_writable(decompress.decompress_destination, "Decompress destination")?;
+        }
+        // CHECK:
+        check_writable(self.fee_payer, "Fee payer")?;
+        // CHECK:
+        check_writable(self.state_tree, "State tree")?;
+        // CHECK:
+        check_writable(self.queue, "Queue")?;
+        Ok(())
+    }
+}
+
+/// Accounts of the claim many instruction, see `build_claim_many_instruction` for the layout.
+pub struct ClaimManyAccountInfos<'a, 'info> {
+    pub claimant: &'a AccountInfo<'info>,
+    pub fee_payer: &'a AccountInfo<'info>,
+    pub ctoken_cpi_authority_pda: &'a AccountInfo<'info>,
+    pub light_system_program: &'a AccountInfo<'info>,
+    pub registered_program_pda: &'a AccountInfo<'info>,
+    pub account_compression_authority: &'a AccountInfo<'info>,
+    pub account_compression_program: &'a AccountInfo<'info>,
+    pub ctoken_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    /// Trees, queues, airdrop PDAs, mints, token pools, destinations and token programs,
+    /// referenced by index in the instruction data.
+    pub packed_accounts: &'a [AccountInfo<'info>],
+    /// All accounts, passed on to the compressed token program.
+    pub account_infos: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> ClaimManyAccountInfos<'a, 'info> {
+    pub const NUM_ACCOUNTS: usize = 9;
+
+    /// Checks the number of accounts, signers, fixed program IDs and writability.
+    ///
+    /// The packed accounts are checked by the processor and the compressed token program.
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        // CHECK:
+        if accounts.len() <= Self::NUM_ACCOUNTS {
+            msg!(
+                "Expected more than {} accounts, got {}",
+                Self::NUM_ACCOUNTS,
+                accounts.len()
+            );
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let (claim_accounts, packed_accounts) = accounts.split_at(Self::NUM_ACCOUNTS);
+        let claim_account_infos = Self {
+            claimant: &claim_accounts[0],
+            fee_payer: &claim_accounts[1],
+            ctoken_cpi_authority_pda: &claim_accounts[2],
+            light_system_program: &claim_accounts[3],
+            registered_program_pda: &claim_accounts[4],
+            account_compression_authority: &claim_accounts[5],
+            account_compression_program: &claim_accounts[6],
+            ctoken_program: &claim_accounts[7],
+            system_program: &claim_accounts[8],
+            packed_accounts,
+            account_infos: accounts,
+        };
+        claim_account_infos.check()?;
+        Ok(claim_account_infos)
+    }
+
+    fn check(&self) -> ProgramResult {
+        // CHECK:
+        check_signer(self.claimant, "Claimant")?;
+        // CHECK:
+        check_signer(self.fee_payer, "Fee payer")?;
+        // CHECK:
+        check_key(
+            self.ctoken_cpi_authority_pda,
+            &CPI_AUTHORITY_PDA,
+            ClaimError::InvalidCTokenCpiAuthority,
+        )?;
+        // CHECK:
+        check_key(
+            self.light_system_program,
+            &LIGHT_SYSTEM_PROGRAM_ID,
+            ClaimError::InvalidLightSystemProgram,
+        )?;
+        // CHECK:
+        check_key(
+            self.registered_program_pda,
+            &Pubkey::new_from_array(REGISTERED_PROGRAM_PDA),
+            ClaimError::InvalidRegisteredProgramPda,
+        )?;
+        // CHECK:
+        check_key(
+            self.account_compression_authority,
+            &ACCOUNT_COMPRESSION_AUTHORITY_PDA,
+            ClaimError::InvalidAccountCompressionAuthority,
+        )?;
+        // CHECK:
+        check_key(
+            self.account_compression_program,
+            &ACCOUNT_COMPRESSION_PROGRAM_ID,
+            ClaimError::InvalidAccountCompressionProgram,
+        )?;
+        // CHECK:
+        check_key(
+            self.ctoken_program,
+            &light_token::id(),
+            ClaimError::InvalidCompressedTokenProgram,
+        )?;
+        // CHECK:
+        check_key(
+            self.system_program,
+            &solana_program::system_program::ID,
+            ClaimError::InvalidSystemProgram,
+        )?;
+        // CHECK:
+        check_writable(self.fee_payer, "Fee payer")?;
+        Ok(())
+    }
+
+    /// Returns the packed account at `index`.
+    pub fn packed_account(&self, index: u8) -> Result<&'a AccountInfo<'info>, ProgramError> {
+        self.packed_accounts.get(index as usize).ok_or_else(|| {
+            msg!("Packed account index {} out of bounds", index);
+            ProgramError::NotEnoughAccountKeys
+        })
+    }
+}
+
+fn check_signer(account_info: &AccountInfo, name: &str) -> ProgramResult {
+    if !account_info.is_signer {
+        msg!("{} must be a signer", name);
+        account_info.key.log();
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+fn check_writable(account_info: &AccountInfo, name: &str) -> ProgramResult {
+    if !account_info.is_writable {
+        msg!("{} must be writable", name);
+        account_info.key.log();
+        return Err(ClaimError::AccountNotWritable.into());
+    }
+    Ok(())
+}
+
+fn check_key(account_info: &AccountInfo, expected: &Pubkey, error: ClaimError) -> ProgramResult {
+    if account_info.key != expected {
+        msg!("{}", error);
+        account_info.key.log();
+        return Err(error.into());
+    }
+    Ok(())
+}
+
+/// Accepts SPL token and Token-2022, the program is passed through to the decompression.
+fn check_token_program(token_program_info: &AccountInfo) -> ProgramResult {
+    if token_program_info.key != &spl_token::ID && token_program_info.key != &spl_token_2022::ID {
+        msg!("{}", ClaimError::InvalidTokenProgram);
+        token_program_info.key.log();
+        return Err(ClaimError::InvalidTokenProgram.into());
+    }
+    Ok(())
+}
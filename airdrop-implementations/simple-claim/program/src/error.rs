@@ -9,6 +9,46 @@ pub enum ClaimError {
     TokensLocked,
     #[error("Invalid airdrop PDA provided.")]
     InvalidPDA,
+    #[error("Invalid vesting schedule.")]
+    InvalidVestingSchedule,
+    #[error("No vested tokens left to claim.")]
+    NothingToClaim,
+    #[error("Invalid claim record.")]
+    InvalidClaimRecord,
+    #[error("Airdrop has expired.")]
+    AirdropExpired,
+    #[error("Airdrop has not expired yet.")]
+    AirdropNotExpired,
+    #[error("Invalid Merkle proof.")]
+    InvalidMerkleProof,
+    #[error("Invalid compressed token CPI authority PDA.")]
+    InvalidCTokenCpiAuthority,
+    #[error("Invalid light system program.")]
+    InvalidLightSystemProgram,
+    #[error("Invalid registered program PDA.")]
+    InvalidRegisteredProgramPda,
+    #[error("Invalid noop program.")]
+    InvalidNoopProgram,
+    #[error("Invalid account compression authority.")]
+    InvalidAccountCompressionAuthority,
+    #[error("Invalid account compression program.")]
+    InvalidAccountCompressionProgram,
+    #[error("Invalid compressed token program.")]
+    InvalidCompressedTokenProgram,
+    #[error("Invalid SPL token program.")]
+    InvalidTokenProgram,
+    #[error("Invalid system program.")]
+    InvalidSystemProgram,
+    #[error("Account must be writable.")]
+    AccountNotWritable,
+    #[error("Invalid instructions sysvar.")]
+    InvalidInstructionsSysvar,
+    #[error("Missing or invalid ed25519 claim authorization.")]
+    InvalidClaimAuthorization,
+    #[error("Claim authorization has expired.")]
+    ClaimAuthorizationExpired,
+    #[error("Relayer fee exceeds the claim amount.")]
+    RelayerFeeTooHigh,
 }
 
 impl From<ClaimError> for ProgramError {
@@ -1,18 +1,30 @@
 use borsh::{BorshDeserialize, BorshSerialize};
+use light_sdk::instruction::{
+    account_meta::CompressedAccountMeta, PackedAddressTreeInfo, PackedStateTreeInfo,
+};
 use light_token::ValidityProof;
-use light_sdk::instruction::PackedStateTreeInfo;
-use solana_program::pubkey::Pubkey;
+use solana_program::{keccak::hashv, pubkey::Pubkey};
 
 #[cfg(not(target_os = "solana"))]
 use solana_program::instruction::{AccountMeta, Instruction};
 
+#[cfg(not(target_os = "solana"))]
+pub use light_compressed_token_sdk::compressed_token::batch_compress::Recipient;
 #[cfg(not(target_os = "solana"))]
 use light_compressed_token_sdk::compressed_token::batch_compress::{
-    create_batch_compress_instruction, BatchCompressInputs, Recipient,
+    create_batch_compress_instruction, BatchCompressInputs,
 };
 #[cfg(not(target_os = "solana"))]
+use light_sdk::address::v2::derive_address;
+#[cfg(not(target_os = "solana"))]
+use light_sdk::instruction::PackedAccounts;
+#[cfg(not(target_os = "solana"))]
 use light_token::spl_interface::derive_spl_interface_pda;
 
+use crate::state::DISTRIBUTOR_SEED;
+#[cfg(not(target_os = "solana"))]
+use crate::state::{CLAIM_RECEIPT_SEED, CLAIM_RECORD_SEED};
+
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct ClaimIxData {
     pub proof: ValidityProof,
@@ -22,11 +34,283 @@ pub struct ClaimIxData {
     pub mint: Pubkey,
     pub unlock_slot: u64,
     pub bump_seed: u8,
+    /// Claims the vested remainder instead of the full amount if set.
+    pub vesting: Option<VestingIxData>,
+    pub clawback: Option<ClawbackConfig>,
+}
+
+/// Claws back the unclaimed compressed tokens of an expired airdrop to a treasury.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ClawbackIxData {
+    pub proof: ValidityProof,
+    pub packed_tree_info: PackedStateTreeInfo,
+    pub amount: u64,
+    pub lamports: Option<u64>,
+    pub mint: Pubkey,
+    pub claimant: Pubkey,
+    pub unlock_slot: u64,
+    pub vesting: Option<VestingSchedule>,
+    pub expiry_slot: u64,
+    pub bump_seed: u8,
+}
+
+/// Clawback configuration of an airdrop, recorded in the seeds of the airdrop PDA.
+///
+/// From `expiry_slot` on the claimant can no longer claim,
+/// and `authority` can claw the unclaimed tokens back to a treasury.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClawbackConfig {
+    pub authority: Pubkey,
+    pub expiry_slot: u64,
+}
+
+/// Claims the `amount` of a distributor leaf from the compressed token account
+/// of the distributor PDA and creates the claim receipt of the leaf.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct DistributorClaimIxData {
+    pub proof: ValidityProof,
+    pub packed_tree_info: PackedStateTreeInfo,
+    /// Balance of the compressed token account of the distributor PDA.
+    pub balance: u64,
+    pub lamports: Option<u64>,
+    pub mint: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub bump_seed: u8,
+    pub leaf_index: u64,
+    pub amount: u64,
+    pub unlock_slot: u64,
+    pub merkle_proof: Vec<[u8; 32]>,
+    /// Validity proof of the new claim receipt address.
+    pub receipt_proof: ValidityProof,
+    pub address_tree_info: PackedAddressTreeInfo,
+    pub output_state_tree_index: u8,
+}
+
+/// Claim submitted by a relayer on behalf of the claimant.
+///
+/// The claimant authorizes it off-chain by signing the [`RelayedClaimMessage`],
+/// the signature is verified by an ed25519 instruction right before the claim.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RelayedClaimIxData {
+    pub claim: ClaimIxData,
+    /// Paid to the fee payer as compressed tokens out of the claimed amount.
+    pub relayer_fee: u64,
+    pub valid_until_slot: u64,
+}
+
+/// Prefix of relayed claim messages, so signatures cannot be reused for other messages.
+pub const RELAYED_CLAIM_DOMAIN: &[u8] = b"light-compressed-claim:relayed-claim";
+
+/// Message signed by the claimant to authorize a relayed claim
+/// of `associated_airdrop_pda` to `decompress_destination`.
+///
+/// The message commits to the compressed token account that is claimed (state tree,
+/// leaf index and balance). The claim spends that account, so an authorization
+/// can be used once and not for later vesting unlocks or tokens sent to the PDA afterwards.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RelayedClaimMessage {
+    pub associated_airdrop_pda: Pubkey,
+    pub decompress_destination: Pubkey,
+    pub relayer_fee: u64,
+    pub valid_until_slot: u64,
+    pub state_tree: Pubkey,
+    pub leaf_index: u32,
+    pub amount: u64,
+}
+
+impl RelayedClaimMessage {
+    /// `RELAYED_CLAIM_DOMAIN || borsh(message)`, the bytes the claimant signs.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = RELAYED_CLAIM_DOMAIN.to_vec();
+        bytes.extend(borsh::to_vec(self).unwrap());
+        bytes
+    }
+}
+
+/// Claims the airdrops of several mints of one claimant with a single validity proof.
+///
+/// Account indices are relative to the packed accounts following the claim many accounts.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ClaimManyIxData {
+    pub proof: ValidityProof,
+    pub claims: Vec<MintClaimIxData>,
+    pub output_queue_index: u8,
+}
+
+/// Claim of the airdrop of one mint in [`ClaimManyIxData`].
+///
+/// Airdrops with vesting and compressed token accounts with lamports are not supported.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct MintClaimIxData {
+    pub packed_tree_info: PackedStateTreeInfo,
+    pub amount: u64,
+    pub unlock_slot: u64,
+    pub bump_seed: u8,
+    pub clawback: Option<ClawbackConfig>,
+    pub associated_airdrop_pda_index: u8,
+    pub mint_index: u8,
+    pub token_pool_index: u8,
+    pub token_pool_bump: u8,
+    pub decompress_destination_index: u8,
+}
+
+/// Linear vesting schedule of an airdrop.
+///
+/// The cliff is `unlock_slot`, nothing can be claimed before it.
+/// The vested amount grows linearly from `start_slot` until all tokens are vested at `end_slot`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VestingSchedule {
+    pub start_slot: u64,
+    pub end_slot: u64,
+}
+
+impl VestingSchedule {
+    /// Returns the part of `total` that is vested at `current_slot`.
+    pub fn vested_amount(&self, total: u64, cliff_slot: u64, current_slot: u64) -> u64 {
+        if current_slot < cliff_slot || current_slot <= self.start_slot {
+            return 0;
+        }
+        if current_slot >= self.end_slot {
+            return total;
+        }
+        let elapsed = (current_slot - self.start_slot) as u128;
+        let duration = (self.end_slot - self.start_slot) as u128;
+        (total as u128 * elapsed / duration) as u64
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct VestingIxData {
+    pub schedule: VestingSchedule,
+    /// Validity proof of the claim record.
+    pub record_proof: ValidityProof,
+    pub record: ClaimRecordIxData,
+}
+
+/// The first vested claim creates the claim record, later claims update it.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub enum ClaimRecordIxData {
+    Create {
+        address_tree_info: PackedAddressTreeInfo,
+        output_state_tree_index: u8,
+    },
+    Update {
+        account_meta: CompressedAccountMeta,
+        withdrawn: u64,
+    },
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub enum ClaimProgramInstruction {
     Claim(ClaimIxData),
+    /// Claims to the claimant as compressed tokens instead of decompressing.
+    ClaimCompressed(ClaimIxData),
+    Clawback(ClawbackIxData),
+    DistributorClaim(DistributorClaimIxData),
+    ClaimMany(ClaimManyIxData),
+    RelayedClaim(RelayedClaimIxData),
+}
+
+/// Returns the seeds of the associated airdrop PDA without the bump seed.
+///
+/// `[claimant, mint, unlock_slot]`, followed by `[start_slot, end_slot]` for vested airdrops
+/// and `[authority, expiry_slot]` for airdrops with clawback.
+pub fn airdrop_pda_seeds(
+    claimant: &Pubkey,
+    mint: &Pubkey,
+    unlock_slot: u64,
+    vesting: Option<&VestingSchedule>,
+    clawback: Option<&ClawbackConfig>,
+) -> Vec<Vec<u8>> {
+    let mut seeds = vec![
+        claimant.to_bytes().to_vec(),
+        mint.to_bytes().to_vec(),
+        unlock_slot.to_le_bytes().to_vec(),
+    ];
+    if let Some(schedule) = vesting {
+        seeds.push(schedule.start_slot.to_le_bytes().to_vec());
+        seeds.push(schedule.end_slot.to_le_bytes().to_vec());
+    }
+    if let Some(clawback) = clawback {
+        seeds.push(clawback.authority.to_bytes().to_vec());
+        seeds.push(clawback.expiry_slot.to_le_bytes().to_vec());
+    }
+    seeds
+}
+
+/// Returns the seeds of the distributor PDA without the bump seed.
+///
+/// A Merkle root is posted by compressing the total of all leaves to this PDA.
+/// The root alone defines who can claim what, so anyone can fund it.
+pub fn distributor_pda_seeds(mint: &Pubkey, merkle_root: &[u8; 32]) -> Vec<Vec<u8>> {
+    vec![
+        DISTRIBUTOR_SEED.to_vec(),
+        mint.to_bytes().to_vec(),
+        merkle_root.to_vec(),
+    ]
+}
+
+/// Leaf of a distributor Merkle tree.
+///
+/// `index` is the position of the leaf in the tree, claim receipts are keyed by it
+/// so a claimant can hold several leaves.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DistributorLeaf {
+    pub index: u64,
+    pub claimant: Pubkey,
+    pub amount: u64,
+    pub unlock_slot: u64,
+}
+
+impl DistributorLeaf {
+    /// `keccak(0 || index || claimant || amount || unlock_slot)`,
+    /// the prefix separates leaves from nodes.
+    pub fn hash(&self) -> [u8; 32] {
+        hashv(&[
+            &[0],
+            &self.index.to_le_bytes(),
+            self.claimant.as_ref(),
+            &self.amount.to_le_bytes(),
+            &self.unlock_slot.to_le_bytes(),
+        ])
+        .to_bytes()
+    }
+}
+
+/// `keccak(1 || min(left, right) || max(left, right))`, sorted so proofs need no path bits.
+pub fn hash_distributor_nodes(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if left <= right {
+        (left, right)
+    } else {
+        (right, left)
+    };
+    hashv(&[&[1], first, second]).to_bytes()
+}
+
+/// Returns whether `proof` proves the inclusion of `leaf` in the tree with `root`.
+pub fn verify_distributor_proof(
+    root: &[u8; 32],
+    leaf: &DistributorLeaf,
+    proof: &[[u8; 32]],
+) -> bool {
+    let computed_root = proof.iter().fold(leaf.hash(), |node, sibling| {
+        hash_distributor_nodes(&node, sibling)
+    });
+    computed_root == *root
+}
+
+/// Finds the associated airdrop PDA compressed tokens are sent to, see [`airdrop_pda_seeds`].
+#[cfg(not(target_os = "solana"))]
+pub fn find_associated_airdrop_pda(
+    claimant: &Pubkey,
+    mint: &Pubkey,
+    unlock_slot: u64,
+    vesting: Option<&VestingSchedule>,
+    clawback: Option<&ClawbackConfig>,
+) -> (Pubkey, u8) {
+    let seeds = airdrop_pda_seeds(claimant, mint, unlock_slot, vesting, clawback);
+    let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+    Pubkey::find_program_address(&seeds, &crate::ID)
 }
 
 #[cfg(not(target_os = "solana"))]
@@ -50,6 +334,27 @@ pub struct ClaimAccounts {
     pub queue: Pubkey,
 }
 
+/// Accounts of [`build_claim_compressed_instruction`].
+///
+/// Same as [`ClaimAccounts`] without the token pool, decompress destination and token program.
+#[cfg(not(target_os = "solana"))]
+#[derive(Debug)]
+pub struct ClaimCompressedAccounts {
+    pub claimant: Pubkey,
+    pub fee_payer: Pubkey,
+    pub associated_airdrop_pda: Pubkey,
+    pub ctoken_cpi_authority_pda: Pubkey,
+    pub light_system_program: Pubkey,
+    pub registered_program_pda: Pubkey,
+    pub noop_program: Pubkey,
+    pub account_compression_authority: Pubkey,
+    pub account_compression_program: Pubkey,
+    pub ctoken_program: Pubkey,
+    pub system_program: Pubkey,
+    pub state_tree: Pubkey,
+    pub queue: Pubkey,
+}
+
 /// Build a claim instruction in the client.
 ///
 /// Accounts expected by this instruction:
@@ -64,9 +369,9 @@ pub struct ClaimAccounts {
 ///   7. `[]` Account compression authority
 ///   8. `[]` Account compression program
 ///   9. `[]` CToken program
-///  10. `[]` Token pool PDA
+///  10. `[writable]` Token pool PDA
 ///  11. `[writable]` Decompress destination
-///  12. `[]` Token program
+///  12. `[]` Token program, SPL token or Token-2022
 ///  13. `[]` System program
 ///  14. `[writable]` State tree
 ///  15. `[writable]` Queue
@@ -81,8 +386,347 @@ pub fn build_claim_and_decompress_instruction(
     mint: Pubkey,
     unlock_slot: u64,
     bump_seed: u8,
+    clawback: Option<ClawbackConfig>,
 ) -> Instruction {
-    let accounts = vec![
+    let instruction_data = ClaimProgramInstruction::Claim(ClaimIxData {
+        proof,
+        packed_tree_info,
+        amount,
+        lamports,
+        mint,
+        unlock_slot,
+        bump_seed,
+        vesting: None,
+        clawback,
+    });
+
+    Instruction {
+        program_id: crate::id(),
+        accounts: claim_account_metas(accounts),
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Build a claim instruction in the client that transfers the tokens
+/// to the claimant as compressed tokens.
+///
+/// Accounts expected by this instruction:
+///
+///   0. `[signer]` Claimant
+///   1. `[signer]` Fee payer
+///   2. `[]` Associated airdrop PDA
+///   3. `[]` CToken CPI authority PDA
+///   4. `[]` Light system program
+///   5. `[]` Registered program PDA
+///   6. `[]` Noop program
+///   7. `[]` Account compression authority
+///   8. `[]` Account compression program
+///   9. `[]` CToken program
+///  10. `[]` System program
+///  11. `[writable]` State tree
+///  12. `[writable]` Queue
+#[cfg(not(target_os = "solana"))]
+#[allow(clippy::too_many_arguments)]
+pub fn build_claim_compressed_instruction(
+    accounts: &ClaimCompressedAccounts,
+    proof: ValidityProof,
+    packed_tree_info: PackedStateTreeInfo,
+    amount: u64,
+    lamports: Option<u64>,
+    mint: Pubkey,
+    unlock_slot: u64,
+    bump_seed: u8,
+    clawback: Option<ClawbackConfig>,
+) -> Instruction {
+    let instruction_data = ClaimProgramInstruction::ClaimCompressed(ClaimIxData {
+        proof,
+        packed_tree_info,
+        amount,
+        lamports,
+        mint,
+        unlock_slot,
+        bump_seed,
+        vesting: None,
+        clawback,
+    });
+
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(accounts.claimant, true),
+            AccountMeta::new(accounts.fee_payer, true),
+            AccountMeta::new_readonly(accounts.associated_airdrop_pda, false),
+            AccountMeta::new_readonly(accounts.ctoken_cpi_authority_pda, false),
+            AccountMeta::new_readonly(accounts.light_system_program, false),
+            AccountMeta::new_readonly(accounts.registered_program_pda, false),
+            AccountMeta::new_readonly(accounts.noop_program, false),
+            AccountMeta::new_readonly(accounts.account_compression_authority, false),
+            AccountMeta::new_readonly(accounts.account_compression_program, false),
+            AccountMeta::new_readonly(accounts.ctoken_program, false),
+            AccountMeta::new_readonly(accounts.system_program, false),
+            AccountMeta::new(accounts.state_tree, false),
+            AccountMeta::new(accounts.queue, false),
+        ],
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Build a claim instruction for a vested airdrop in the client.
+///
+/// `amount` is the current balance of the compressed token account,
+/// the program decompresses only the vested remainder of it.
+///
+/// Expects the accounts of [`build_claim_and_decompress_instruction`],
+/// followed by `light_system_accounts` which are the light system accounts and trees
+/// of the claim record packed with `PackedAccounts`.
+#[cfg(not(target_os = "solana"))]
+#[allow(clippy::too_many_arguments)]
+pub fn build_vested_claim_instruction(
+    accounts: &ClaimAccounts,
+    proof: ValidityProof,
+    packed_tree_info: PackedStateTreeInfo,
+    amount: u64,
+    lamports: Option<u64>,
+    mint: Pubkey,
+    unlock_slot: u64,
+    bump_seed: u8,
+    vesting: VestingIxData,
+    clawback: Option<ClawbackConfig>,
+    light_system_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut account_metas = claim_account_metas(accounts);
+    account_metas.extend(light_system_accounts);
+
+    let instruction_data = ClaimProgramInstruction::Claim(ClaimIxData {
+        proof,
+        packed_tree_info,
+        amount,
+        lamports,
+        mint,
+        unlock_slot,
+        bump_seed,
+        vesting: Some(vesting),
+        clawback,
+    });
+
+    Instruction {
+        program_id: crate::id(),
+        accounts: account_metas,
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Build a clawback instruction in the client.
+///
+/// Expects the accounts of [`build_claim_and_decompress_instruction`]
+/// with the clawback authority as claimant and the treasury token account
+/// as decompress destination.
+#[cfg(not(target_os = "solana"))]
+#[allow(clippy::too_many_arguments)]
+pub fn build_clawback_instruction(
+    accounts: &ClaimAccounts,
+    proof: ValidityProof,
+    packed_tree_info: PackedStateTreeInfo,
+    amount: u64,
+    lamports: Option<u64>,
+    mint: Pubkey,
+    claimant: Pubkey,
+    unlock_slot: u64,
+    vesting: Option<VestingSchedule>,
+    expiry_slot: u64,
+    bump_seed: u8,
+) -> Instruction {
+    let instruction_data = ClaimProgramInstruction::Clawback(ClawbackIxData {
+        proof,
+        packed_tree_info,
+        amount,
+        lamports,
+        mint,
+        claimant,
+        unlock_slot,
+        vesting,
+        expiry_slot,
+        bump_seed,
+    });
+
+    Instruction {
+        program_id: crate::id(),
+        accounts: claim_account_metas(accounts),
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Build a distributor claim instruction in the client.
+///
+/// Expects the accounts of [`build_claim_and_decompress_instruction`]
+/// with the distributor PDA as associated airdrop PDA,
+/// followed by `light_system_accounts` which are the light system accounts and trees
+/// of the claim receipt packed with `PackedAccounts`.
+#[cfg(not(target_os = "solana"))]
+pub fn build_distributor_claim_instruction(
+    accounts: &ClaimAccounts,
+    ix_data: DistributorClaimIxData,
+    light_system_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut account_metas = claim_account_metas(accounts);
+    account_metas.extend(light_system_accounts);
+
+    Instruction {
+        program_id: crate::id(),
+        accounts: account_metas,
+        data: borsh::to_vec(&ClaimProgramInstruction::DistributorClaim(ix_data)).unwrap(),
+    }
+}
+
+/// Build a relayed claim instruction in the client.
+///
+/// Expects the accounts of [`build_claim_and_decompress_instruction`] with the relayer
+/// as fee payer, the claimant does not sign. The instructions sysvar follows the claim accounts,
+/// then `light_system_accounts` of the claim record for vested claims.
+///
+/// Must directly follow an ed25519 instruction with the claimant's signature of
+/// the [`RelayedClaimMessage`] of this claim.
+#[cfg(not(target_os = "solana"))]
+pub fn build_relayed_claim_instruction(
+    accounts: &ClaimAccounts,
+    ix_data: RelayedClaimIxData,
+    light_system_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut account_metas = claim_account_metas(accounts);
+    account_metas[0] = AccountMeta::new_readonly(accounts.claimant, false);
+    account_metas.push(AccountMeta::new_readonly(
+        solana_program::sysvar::instructions::ID,
+        false,
+    ));
+    account_metas.extend(light_system_accounts);
+
+    Instruction {
+        program_id: crate::id(),
+        accounts: account_metas,
+        data: borsh::to_vec(&ClaimProgramInstruction::RelayedClaim(ix_data)).unwrap(),
+    }
+}
+
+#[cfg(not(target_os = "solana"))]
+#[derive(Debug)]
+pub struct ClaimManyAccounts {
+    pub claimant: Pubkey,
+    pub fee_payer: Pubkey,
+    pub ctoken_cpi_authority_pda: Pubkey,
+    pub light_system_program: Pubkey,
+    pub registered_program_pda: Pubkey,
+    pub account_compression_authority: Pubkey,
+    pub account_compression_program: Pubkey,
+    pub ctoken_program: Pubkey,
+    pub system_program: Pubkey,
+    /// Receives the compressed remainder of the claimed accounts, if any.
+    pub output_queue: Pubkey,
+}
+
+/// Airdrop of one mint claimed with [`build_claim_many_instruction`].
+#[cfg(not(target_os = "solana"))]
+#[derive(Debug, Clone)]
+pub struct MintClaim {
+    pub mint: Pubkey,
+    /// SPL token or Token-2022, the owner of the mint.
+    pub token_program: Pubkey,
+    pub decompress_destination: Pubkey,
+    pub associated_airdrop_pda: Pubkey,
+    pub bump_seed: u8,
+    pub unlock_slot: u64,
+    pub clawback: Option<ClawbackConfig>,
+    /// Balance of the compressed token account of the airdrop PDA, claimed in full.
+    pub amount: u64,
+    pub state_tree: Pubkey,
+    pub queue: Pubkey,
+    pub leaf_index: u32,
+    pub root_index: u16,
+    pub prove_by_index: bool,
+}
+
+/// Build a claim many instruction in the client.
+///
+/// `proof` is one validity proof of the compressed token accounts of all `claims`.
+///
+/// Accounts expected by this instruction:
+///
+///   0. `[signer]` Claimant
+///   1. `[signer, writable]` Fee payer
+///   2. `[]` CToken CPI authority PDA
+///   3. `[]` Light system program
+///   4. `[]` Registered program PDA
+///   5. `[]` Account compression authority
+///   6. `[]` Account compression program
+///   7. `[]` CToken program
+///   8. `[]` System program
+///   9. ..  Packed accounts: trees, queues, airdrop PDAs, mints, token pools,
+///      decompress destinations and token programs.
+///      Accounts shared by several mints are passed once.
+#[cfg(not(target_os = "solana"))]
+pub fn build_claim_many_instruction(
+    accounts: &ClaimManyAccounts,
+    proof: ValidityProof,
+    claims: &[MintClaim],
+) -> Instruction {
+    let mut packed_accounts = PackedAccounts::default();
+    let output_queue_index = packed_accounts.insert_or_get(accounts.output_queue);
+
+    let mut mint_claims = Vec::with_capacity(claims.len());
+    for claim in claims {
+        let spl_interface_pda = derive_spl_interface_pda(&claim.mint, 0, false);
+        packed_accounts.insert_or_get_read_only(claim.token_program);
+        mint_claims.push(MintClaimIxData {
+            packed_tree_info: PackedStateTreeInfo {
+                root_index: claim.root_index,
+                prove_by_index: claim.prove_by_index,
+                merkle_tree_pubkey_index: packed_accounts.insert_or_get(claim.state_tree),
+                queue_pubkey_index: packed_accounts.insert_or_get(claim.queue),
+                leaf_index: claim.leaf_index,
+            },
+            amount: claim.amount,
+            unlock_slot: claim.unlock_slot,
+            bump_seed: claim.bump_seed,
+            clawback: claim.clawback,
+            associated_airdrop_pda_index: packed_accounts
+                .insert_or_get_read_only(claim.associated_airdrop_pda),
+            mint_index: packed_accounts.insert_or_get_read_only(claim.mint),
+            token_pool_index: packed_accounts.insert_or_get(spl_interface_pda.pubkey),
+            token_pool_bump: spl_interface_pda.bump,
+            decompress_destination_index: packed_accounts
+                .insert_or_get(claim.decompress_destination),
+        });
+    }
+    let (packed_account_metas, _, _) = packed_accounts.to_account_metas();
+
+    let mut account_metas = vec![
+        AccountMeta::new_readonly(accounts.claimant, true),
+        AccountMeta::new(accounts.fee_payer, true),
+        AccountMeta::new_readonly(accounts.ctoken_cpi_authority_pda, false),
+        AccountMeta::new_readonly(accounts.light_system_program, false),
+        AccountMeta::new_readonly(accounts.registered_program_pda, false),
+        AccountMeta::new_readonly(accounts.account_compression_authority, false),
+        AccountMeta::new_readonly(accounts.account_compression_program, false),
+        AccountMeta::new_readonly(accounts.ctoken_program, false),
+        AccountMeta::new_readonly(accounts.system_program, false),
+    ];
+    account_metas.extend(packed_account_metas);
+
+    let instruction_data = ClaimProgramInstruction::ClaimMany(ClaimManyIxData {
+        proof,
+        claims: mint_claims,
+        output_queue_index,
+    });
+
+    Instruction {
+        program_id: crate::id(),
+        accounts: account_metas,
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+#[cfg(not(target_os = "solana"))]
+fn claim_account_metas(accounts: &ClaimAccounts) -> Vec<AccountMeta> {
+    vec![
         AccountMeta::new(accounts.claimant, true),
         AccountMeta::new(accounts.fee_payer, true),
         AccountMeta::new_readonly(accounts.associated_airdrop_pda, false),
@@ -99,23 +743,48 @@ pub fn build_claim_and_decompress_instruction(
         AccountMeta::new_readonly(accounts.system_program, false),
         AccountMeta::new(accounts.state_tree, false),
         AccountMeta::new(accounts.queue, false),
-    ];
+    ]
+}
 
-    let instruction_data = ClaimProgramInstruction::Claim(ClaimIxData {
-        proof,
-        packed_tree_info,
-        amount,
-        lamports,
-        mint,
-        unlock_slot,
-        bump_seed,
-    });
+/// Derives the address of the claim record of a vested airdrop PDA.
+#[cfg(not(target_os = "solana"))]
+pub fn derive_claim_record_address(
+    associated_airdrop_pda: &Pubkey,
+    address_tree: &Pubkey,
+) -> [u8; 32] {
+    derive_address(
+        &[CLAIM_RECORD_SEED, associated_airdrop_pda.as_ref()],
+        address_tree,
+        &crate::ID,
+    )
+    .0
+}
 
-    Instruction {
-        program_id: crate::id(),
-        accounts,
-        data: borsh::to_vec(&instruction_data).unwrap(),
-    }
+/// Finds the distributor PDA of a Merkle root, see [`distributor_pda_seeds`].
+#[cfg(not(target_os = "solana"))]
+pub fn find_distributor_pda(mint: &Pubkey, merkle_root: &[u8; 32]) -> (Pubkey, u8) {
+    let seeds = distributor_pda_seeds(mint, merkle_root);
+    let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+    Pubkey::find_program_address(&seeds, &crate::ID)
+}
+
+/// Derives the address of the claim receipt of the leaf at `leaf_index` in a distributor.
+#[cfg(not(target_os = "solana"))]
+pub fn derive_claim_receipt_address(
+    distributor_pda: &Pubkey,
+    leaf_index: u64,
+    address_tree: &Pubkey,
+) -> [u8; 32] {
+    derive_address(
+        &[
+            CLAIM_RECEIPT_SEED,
+            distributor_pda.as_ref(),
+            &leaf_index.to_le_bytes(),
+        ],
+        address_tree,
+        &crate::ID,
+    )
+    .0
 }
 
 /// Creates a compress instruction that compresses SPL tokens to a compressed token account.
@@ -129,6 +798,7 @@ pub fn build_claim_and_decompress_instruction(
 /// * `recipient` - Recipient of the compressed tokens (can be a PDA)
 /// * `merkle_tree` - State tree to store the compressed account
 #[cfg(not(target_os = "solana"))]
+#[allow(clippy::too_many_arguments)]
 pub fn compress(
     fee_payer: Pubkey,
     authority: Pubkey,
@@ -138,6 +808,40 @@ pub fn compress(
     recipient: Pubkey,
     merkle_tree: Pubkey,
     token_program_id: Pubkey,
+) -> Result<Instruction, light_token::error::TokenSdkError> {
+    batch_compress(
+        fee_payer,
+        authority,
+        sender_token_account,
+        mint,
+        vec![Recipient {
+            pubkey: recipient,
+            amount,
+        }],
+        merkle_tree,
+        token_program_id,
+    )
+}
+
+/// Creates a compress instruction that compresses SPL tokens to one compressed token account
+/// per recipient.
+///
+/// # Arguments
+/// * `fee_payer` - Account paying for the transaction
+/// * `authority` - Owner of the sender token account (must be signer)
+/// * `sender_token_account` - SPL token account to compress tokens from
+/// * `mint` - Token mint address
+/// * `recipients` - Recipients and amounts of the compressed tokens (can be PDAs)
+/// * `merkle_tree` - State tree to store the compressed accounts
+#[cfg(not(target_os = "solana"))]
+pub fn batch_compress(
+    fee_payer: Pubkey,
+    authority: Pubkey,
+    sender_token_account: Pubkey,
+    mint: Pubkey,
+    recipients: Vec<Recipient>,
+    merkle_tree: Pubkey,
+    token_program_id: Pubkey,
 ) -> Result<Instruction, light_token::error::TokenSdkError> {
     let spl_interface_info = derive_spl_interface_pda(&mint, 0, false);
 
@@ -148,10 +852,7 @@ pub fn compress(
         sender_token_account,
         token_program: token_program_id,
         merkle_tree,
-        recipients: vec![Recipient {
-            pubkey: recipient,
-            amount,
-        }],
+        recipients,
         lamports: None,
         token_pool_index: spl_interface_info.index,
         token_pool_bump: spl_interface_info.bump,
@@ -160,3 +861,113 @@ pub fn compress(
 
     create_batch_compress_instruction(inputs)
 }
+
+/// Splits `total` into `num_accounts` compressed token accounts of the distributor PDA,
+/// to be compressed with [`batch_compress`].
+///
+/// Every claim spends one compressed token account of the distributor PDA,
+/// claims spending different accounts do not conflict with each other.
+#[cfg(not(target_os = "solana"))]
+pub fn distributor_funding(
+    distributor_pda: &Pubkey,
+    total: u64,
+    num_accounts: u64,
+) -> Vec<Recipient> {
+    let num_accounts = num_accounts.clamp(1, total.max(1));
+    (0..num_accounts)
+        .map(|i| Recipient {
+            pubkey: *distributor_pda,
+            amount: total / num_accounts + u64::from(i < total % num_accounts),
+        })
+        .collect()
+}
+
+/// Returns the position in `balances` of the compressed token account of the distributor PDA
+/// the leaf at `leaf_index` claims `amount` from.
+///
+/// Starts at `leaf_index % balances.len()` so concurrent claimants spread over the accounts,
+/// and moves on to the next account that still holds `amount`.
+#[cfg(not(target_os = "solana"))]
+pub fn select_distributor_account(leaf_index: u64, balances: &[u64], amount: u64) -> Option<usize> {
+    if balances.is_empty() {
+        return None;
+    }
+    let start = (leaf_index % balances.len() as u64) as usize;
+    (0..balances.len())
+        .map(|offset| (start + offset) % balances.len())
+        .find(|position| balances[*position] >= amount)
+}
+
+/// Merkle tree of distributor leaves.
+///
+/// Odd nodes are carried up to the next layer unhashed.
+#[cfg(not(target_os = "solana"))]
+#[derive(Debug, Clone)]
+pub struct DistributorTree {
+    leaves: Vec<DistributorLeaf>,
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+#[cfg(not(target_os = "solana"))]
+impl DistributorTree {
+    /// Panics if the index of a leaf is not its position.
+    pub fn new(leaves: Vec<DistributorLeaf>) -> Self {
+        for (position, leaf) in leaves.iter().enumerate() {
+            assert_eq!(
+                leaf.index, position as u64,
+                "leaf index must be its position"
+            );
+        }
+        let mut layers = vec![leaves.iter().map(DistributorLeaf::hash).collect::<Vec<_>>()];
+        while layers.last().unwrap().len() > 1 {
+            let next = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_distributor_nodes(left, right),
+                    [node] => *node,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+        Self { leaves, layers }
+    }
+
+    /// Returns the root, or zero bytes for an empty tree.
+    pub fn root(&self) -> [u8; 32] {
+        self.layers
+            .last()
+            .and_then(|layer| layer.first())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn leaves(&self) -> &[DistributorLeaf] {
+        &self.leaves
+    }
+
+    /// Returns the index of the first leaf of `claimant`.
+    pub fn position(&self, claimant: &Pubkey) -> Option<usize> {
+        self.leaves
+            .iter()
+            .position(|leaf| leaf.claimant == *claimant)
+    }
+
+    /// Returns the inclusion proof of the leaf at `index`.
+    pub fn proof(&self, index: usize) -> Option<Vec<[u8; 32]>> {
+        if index >= self.leaves.len() {
+            return None;
+        }
+        let mut proof = Vec::with_capacity(self.layers.len());
+        let mut index = index;
+        for layer in &self.layers[..self.layers.len() - 1] {
+            if let Some(sibling) = layer.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
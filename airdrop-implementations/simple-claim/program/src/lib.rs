@@ -1,4 +1,5 @@
 //! Program entrypoint
+use light_sdk::{cpi::CpiSigner, derive_light_cpi_signer};
 use solana_program::{
     account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, program_error::ProgramError,
     pubkey::Pubkey,
@@ -18,9 +19,18 @@ pub fn process_instruction(
     crate::processor::process_instruction(accounts, instruction_data)
 }
 
+pub mod accounts;
 mod error;
 pub mod instruction;
+#[cfg(not(target_os = "solana"))]
+pub mod planner;
 pub mod processor;
+pub mod state;
+#[cfg(not(target_os = "solana"))]
+pub mod status;
 pub use solana_program;
 
 solana_program::declare_id!("7UHB3CfWv7SugNhfdyP7aeZJPMjnpd9zJ7xYkHozB3Na");
+
+pub const LIGHT_CPI_SIGNER: CpiSigner =
+    derive_light_cpi_signer!("7UHB3CfWv7SugNhfdyP7aeZJPMjnpd9zJ7xYkHozB3Na");
@@ -0,0 +1,201 @@
+//! Client side planner that batches compress instructions for an airdrop.
+//!
+//! Reads a list of `(claimant, amount, unlock_slot)` entries from CSV or JSON,
+//! derives the associated airdrop PDA of every entry with the seeds checked by `process_claim`,
+//! and packs as many recipients into each compress instruction as fit into a transaction.
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+use solana_program::{instruction::Instruction, message::Message, pubkey::Pubkey};
+use thiserror::Error;
+
+use crate::instruction::{batch_compress, find_associated_airdrop_pda, Recipient};
+
+/// Maximum size of a serialized transaction.
+pub const MAX_TRANSACTION_SIZE: usize = 1232;
+
+#[derive(Error, Debug)]
+pub enum PlannerError {
+    #[error("Csv error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("Json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Token sdk error: {0:?}")]
+    TokenSdk(light_token::error::TokenSdkError),
+    #[error("A single recipient does not fit into a transaction.")]
+    RecipientTooLarge,
+}
+
+/// Airdrop of `amount` tokens to `claimant`, locked until `unlock_slot`.
+///
+/// CSV files have the header `claimant,amount,unlock_slot`,
+/// JSON files are an array of objects with the same fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AirdropEntry {
+    #[serde(with = "pubkey_string")]
+    pub claimant: Pubkey,
+    pub amount: u64,
+    pub unlock_slot: u64,
+}
+
+impl AirdropEntry {
+    pub fn from_csv<R: Read>(reader: R) -> Result<Vec<Self>, PlannerError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+        let entries = reader.deserialize().collect::<Result<Vec<Self>, _>>()?;
+        Ok(entries)
+    }
+
+    pub fn from_json<R: Read>(reader: R) -> Result<Vec<Self>, PlannerError> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+/// Associated airdrop PDA and bump of an airdrop entry, needed to claim it later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    #[serde(with = "pubkey_string")]
+    pub claimant: Pubkey,
+    pub amount: u64,
+    pub unlock_slot: u64,
+    #[serde(with = "pubkey_string")]
+    pub airdrop_pda: Pubkey,
+    pub bump_seed: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AirdropManifest {
+    #[serde(with = "pubkey_string")]
+    pub mint: Pubkey,
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl AirdropManifest {
+    pub fn new(mint: Pubkey, entries: &[AirdropEntry]) -> Self {
+        let entries = entries
+            .iter()
+            .map(|entry| {
+                let (airdrop_pda, bump_seed) = find_associated_airdrop_pda(
+                    &entry.claimant,
+                    &mint,
+                    entry.unlock_slot,
+                    None,
+                    None,
+                );
+                ManifestEntry {
+                    claimant: entry.claimant,
+                    amount: entry.amount,
+                    unlock_slot: entry.unlock_slot,
+                    airdrop_pda,
+                    bump_seed,
+                }
+            })
+            .collect();
+        Self { mint, entries }
+    }
+
+    pub fn write_json<W: Write>(&self, writer: W) -> Result<(), PlannerError> {
+        Ok(serde_json::to_writer_pretty(writer, self)?)
+    }
+
+    pub fn from_json<R: Read>(reader: R) -> Result<Self, PlannerError> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+#[derive(Debug)]
+pub struct AirdropPlan {
+    /// Compress instructions, each fits into its own transaction.
+    pub instructions: Vec<Instruction>,
+    pub manifest: AirdropManifest,
+}
+
+/// Compresses the tokens of an airdrop from `sender_token_account` to the airdrop PDAs.
+#[derive(Debug, Clone)]
+pub struct AirdropPlanner {
+    pub fee_payer: Pubkey,
+    pub authority: Pubkey,
+    pub sender_token_account: Pubkey,
+    pub mint: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub token_program_id: Pubkey,
+    /// Lower it to leave room for other instructions, e.g. compute budget instructions.
+    pub max_transaction_size: usize,
+}
+
+impl AirdropPlanner {
+    pub fn plan(&self, entries: &[AirdropEntry]) -> Result<AirdropPlan, PlannerError> {
+        let manifest = AirdropManifest::new(self.mint, entries);
+        let entries = &manifest.entries;
+
+        let mut instructions = Vec::new();
+        let mut start = 0;
+        while start < entries.len() {
+            let mut end = start + 1;
+            if self.transaction_size(&self.compress_instruction(&entries[start..end])?)
+                > self.max_transaction_size
+            {
+                return Err(PlannerError::RecipientTooLarge);
+            }
+            while end < entries.len()
+                && self.transaction_size(&self.compress_instruction(&entries[start..end + 1])?)
+                    <= self.max_transaction_size
+            {
+                end += 1;
+            }
+            instructions.push(self.compress_instruction(&entries[start..end])?);
+            start = end;
+        }
+
+        Ok(AirdropPlan {
+            instructions,
+            manifest,
+        })
+    }
+
+    fn compress_instruction(&self, entries: &[ManifestEntry]) -> Result<Instruction, PlannerError> {
+        let recipients = entries
+            .iter()
+            .map(|entry| Recipient {
+                pubkey: entry.airdrop_pda,
+                amount: entry.amount,
+            })
+            .collect();
+        batch_compress(
+            self.fee_payer,
+            self.authority,
+            self.sender_token_account,
+            self.mint,
+            recipients,
+            self.merkle_tree,
+            self.token_program_id,
+        )
+        .map_err(PlannerError::TokenSdk)
+    }
+
+    /// Size of a legacy transaction with `instruction` as its only instruction.
+    pub fn transaction_size(&self, instruction: &Instruction) -> usize {
+        let message = Message::new(std::slice::from_ref(instruction), Some(&self.fee_payer));
+        let num_signatures = message.header.num_required_signatures as usize;
+        // compact-u16 signature count, signatures and message.
+        1 + num_signatures * 64 + message.serialize().len()
+    }
+}
+
+/// Serializes pubkeys as base58 strings.
+mod pubkey_string {
+    use std::str::FromStr;
+
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use solana_program::pubkey::Pubkey;
+
+    pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&pubkey.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let pubkey = String::deserialize(deserializer)?;
+        Pubkey::from_str(&pubkey).map_err(D::Error::custom)
+    }
+}
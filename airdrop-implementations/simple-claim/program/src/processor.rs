@@ -1,27 +1,156 @@
 use crate::{
+    accounts::{ClaimAccountInfos, ClaimDestination, ClaimManyAccountInfos},
     error::ClaimError,
-    instruction::{ClaimIxData, ClaimProgramInstruction},
+    instruction::{
+        airdrop_pda_seeds, distributor_pda_seeds, verify_distributor_proof, ClaimIxData,
+        ClaimManyIxData, ClaimProgramInstruction, ClaimRecordIxData, ClawbackConfig,
+        ClawbackIxData, DistributorClaimIxData, DistributorLeaf, RelayedClaimIxData,
+        RelayedClaimMessage, VestingIxData,
+    },
+    state::{ClaimReceipt, ClaimRecord, CLAIM_RECEIPT_SEED, CLAIM_RECORD_SEED},
+    LIGHT_CPI_SIGNER,
 };
 use borsh::BorshDeserialize;
 
+use light_compressed_account::compressed_account::PackedMerkleContext;
 use light_compressed_token_sdk::compressed_token::{
-    transfer::instruction::DecompressInputs, CTokenAccount, TokenAccountMeta,
+    transfer::{
+        account_metas::TokenAccountsMetaConfig,
+        instruction::{
+            create_transfer_instruction_raw, DecompressInputs, TransferConfig, TransferInputs,
+        },
+    },
+    transfer2::{create_transfer2_instruction, Transfer2AccountsMetaConfig, Transfer2Inputs},
+    CTokenAccount, CTokenAccount2, TokenAccountMeta,
+};
+use light_sdk::instruction::{PackedAddressTreeInfo, PackedStateTreeInfo};
+use light_sdk::{
+    account::LightAccount,
+    address::v2::derive_address,
+    constants::ADDRESS_TREE_V2,
+    cpi::{
+        v2::{CpiAccounts, LightSystemProgramCpi},
+        InvokeLightSystemProgram, LightCpiInstruction,
+    },
+    PackedAddressTreeInfoExt,
+};
+use light_token::ValidityProof;
+use light_token_interface::{
+    instructions::transfer2::MultiInputTokenDataWithContext, state::TokenDataVersion,
 };
 use solana_program::{
-    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, msg,
-    program::invoke_signed, program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
+    account_info::AccountInfo,
+    clock::Clock,
+    ed25519_program,
+    entrypoint::ProgramResult,
+    instruction::AccountMeta,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{
+        instructions::{load_current_index_checked, load_instruction_at_checked},
+        Sysvar,
+    },
 };
+use spl_token_2022::{extension::StateWithExtensions, state::Mint};
+
+/// Offsets of the first `Ed25519SignatureOffsets` in ed25519 instruction data.
+const ED25519_OFFSETS_START: usize = 2;
+const ED25519_OFFSETS_SIZE: usize = 14;
 
 pub fn process_instruction(accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
     let instruction = ClaimProgramInstruction::try_from_slice(instruction_data)
         .map_err(|_| ProgramError::InvalidInstructionData)?;
     match instruction {
-        ClaimProgramInstruction::Claim(ix_data) => process_claim(accounts, ix_data),
+        ClaimProgramInstruction::Claim(ix_data) => {
+            process_claim(accounts, ix_data, ClaimDestination::Decompress)
+        }
+        ClaimProgramInstruction::ClaimCompressed(ix_data) => {
+            process_claim(accounts, ix_data, ClaimDestination::Compressed)
+        }
+        ClaimProgramInstruction::Clawback(ix_data) => process_clawback(accounts, ix_data),
+        ClaimProgramInstruction::DistributorClaim(ix_data) => {
+            process_distributor_claim(accounts, ix_data)
+        }
+        ClaimProgramInstruction::ClaimMany(ix_data) => process_claim_many(accounts, ix_data),
+        ClaimProgramInstruction::RelayedClaim(ix_data) => process_relayed_claim(accounts, ix_data),
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-fn process_claim(accounts: &[AccountInfo], ix_data: ClaimIxData) -> ProgramResult {
+fn process_claim(
+    accounts: &[AccountInfo],
+    ix_data: ClaimIxData,
+    destination: ClaimDestination,
+) -> ProgramResult {
+    // Vested claims pass the light system accounts of the claim record after the claim accounts.
+    let claim_accounts =
+        ClaimAccountInfos::parse(accounts, destination, ix_data.vesting.is_some())?;
+    execute_claim(&claim_accounts, ix_data, destination, 0)
+}
+
+/// Claims on behalf of the claimant, authorized by the claimant's ed25519 signature
+/// of the [`RelayedClaimMessage`] instead of a transaction signature.
+fn process_relayed_claim(accounts: &[AccountInfo], ix_data: RelayedClaimIxData) -> ProgramResult {
+    let RelayedClaimIxData {
+        claim,
+        relayer_fee,
+        valid_until_slot,
+    } = ix_data;
+
+    let claim_accounts = ClaimAccountInfos::parse_relayed(accounts, claim.vesting.is_some())?;
+    let decompress_accounts = claim_accounts
+        .decompress
+        .as_ref()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let instructions_sysvar_info = claim_accounts
+        .instructions_sysvar
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    // CHECK:
+    let current_slot = Clock::get()?.slot;
+    if current_slot > valid_until_slot {
+        msg!(
+            "Claim authorization has expired: current slot ({}) is greater than valid until slot ({}).",
+            current_slot,
+            valid_until_slot
+        );
+        return Err(ClaimError::ClaimAuthorizationExpired.into());
+    }
+    // CHECK:
+    // The airdrop PDA commits to the claimant, mint and schedule,
+    // the relayer cannot change the destination or the fee.
+    // The input compressed token account is spent by the claim, the signature cannot be replayed.
+    let message = RelayedClaimMessage {
+        associated_airdrop_pda: *claim_accounts.associated_airdrop_pda.key,
+        decompress_destination: *decompress_accounts.decompress_destination.key,
+        relayer_fee,
+        valid_until_slot,
+        state_tree: *claim_accounts.state_tree.key,
+        leaf_index: claim.packed_tree_info.leaf_index,
+        amount: claim.amount,
+    };
+    check_ed25519_authorization(
+        instructions_sysvar_info,
+        claim_accounts.claimant.key,
+        &message.to_bytes(),
+    )?;
+
+    execute_claim(
+        &claim_accounts,
+        claim,
+        ClaimDestination::Decompress,
+        relayer_fee,
+    )
+}
+
+/// Claims from the associated airdrop PDA to `destination`,
+/// `relayer_fee` of the claimed amount is paid to the fee payer.
+fn execute_claim(
+    claim_accounts: &ClaimAccountInfos,
+    ix_data: ClaimIxData,
+    destination: ClaimDestination,
+    relayer_fee: u64,
+) -> ProgramResult {
     let ClaimIxData {
         proof,
         packed_tree_info,
@@ -30,56 +159,438 @@ fn process_claim(accounts: &[AccountInfo], ix_data: ClaimIxData) -> ProgramResul
         mint,
         unlock_slot,
         bump_seed,
+        vesting,
+        clawback,
     } = ix_data;
-    let claimant_info = &accounts[0];
-    let fee_payer_info = &accounts[1];
-    let associated_airdrop_pda_info = &accounts[2];
-    let _ctoken_cpi_authority_pda_info = &accounts[3];
-    let _light_system_program_info = &accounts[4];
-    let _registered_program_pda_info = &accounts[5];
-    let _noop_program_info = &accounts[6];
-    let _account_compression_authority_info = &accounts[7];
-    let _account_compression_program_info = &accounts[8];
-    let ctoken_program_info = &accounts[9];
-    let spl_interface_pda_info = &accounts[10];
-    let decompress_destination_info = &accounts[11];
-    let token_program_info = &accounts[12];
-    let _system_program_info = &accounts[13];
-    let state_tree_info = &accounts[14];
-    let queue_info = &accounts[15];
-
-    if accounts.len() != 16 {
-        msg!("Expected 16 accounts, got {}", accounts.len());
-        return Err(ProgramError::NotEnoughAccountKeys);
+
+    let claimant_info = claim_accounts.claimant;
+    let associated_airdrop_pda_info = claim_accounts.associated_airdrop_pda;
+    // CHECK:
+    let current_slot = Clock::get()?.slot;
+    if current_slot < unlock_slot {
+        msg!(
+            "Tokens are still locked: current slot ({}) is less than unlock slot ({}).",
+            current_slot,
+            unlock_slot
+        );
+        return Err(ClaimError::TokensLocked.into());
     }
 
     // CHECK:
-    if !claimant_info.is_signer {
-        msg!("Claimant must be a signer");
-        claimant_info.key.log();
-        return Err(ProgramError::MissingRequiredSignature);
+    if let Some(clawback) = clawback.as_ref() {
+        if current_slot >= clawback.expiry_slot {
+            msg!(
+                "Airdrop has expired: current slot ({}) is not less than expiry slot ({}).",
+                current_slot,
+                clawback.expiry_slot
+            );
+            return Err(ClaimError::AirdropExpired.into());
+        }
+    }
+
+    // The vesting schedule and clawback configuration are part of the seeds,
+    // so they cannot be changed by the claimant.
+    let seeds = airdrop_pda_seeds(
+        claimant_info.key,
+        &mint,
+        unlock_slot,
+        vesting.as_ref().map(|vesting| &vesting.schedule),
+        clawback.as_ref(),
+    );
+    let bump_bytes = [bump_seed];
+    let mut seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+    seeds.push(&bump_bytes[..]);
+
+    check_claim_pda(&seeds, &crate::ID, associated_airdrop_pda_info.key)?;
+
+    let claim_amount = match vesting {
+        Some(vesting) => update_claim_record(
+            claim_accounts.fee_payer,
+            associated_airdrop_pda_info.key,
+            claim_accounts.remaining_accounts,
+            vesting,
+            amount,
+            unlock_slot,
+            current_slot,
+        )?,
+        None => amount,
+    };
+    // CHECK:
+    if relayer_fee > claim_amount {
+        msg!(
+            "Relayer fee ({}) exceeds the claim amount ({}).",
+            relayer_fee,
+            claim_amount
+        );
+        return Err(ClaimError::RelayerFeeTooHigh.into());
     }
+
+    match destination {
+        ClaimDestination::Decompress if relayer_fee > 0 => decompress_with_relayer_fee(
+            claim_accounts,
+            &seeds,
+            proof,
+            packed_tree_info,
+            mint,
+            amount,
+            lamports,
+            claim_amount,
+            relayer_fee,
+        ),
+        ClaimDestination::Decompress => decompress_from_airdrop_pda(
+            claim_accounts,
+            &seeds,
+            proof,
+            packed_tree_info,
+            mint,
+            amount,
+            lamports,
+            claim_amount,
+        ),
+        ClaimDestination::Compressed => transfer_from_airdrop_pda(
+            claim_accounts,
+            &seeds,
+            proof,
+            packed_tree_info,
+            mint,
+            amount,
+            lamports,
+            claim_amount,
+        ),
+    }
+}
+
+/// Decompresses the airdrops of several mints in one compressed token transfer,
+/// every airdrop PDA signs for its own compressed token account.
+fn process_claim_many(accounts: &[AccountInfo], ix_data: ClaimManyIxData) -> ProgramResult {
+    let ClaimManyIxData {
+        proof,
+        claims,
+        output_queue_index,
+    } = ix_data;
+
+    let claim_accounts = ClaimManyAccountInfos::parse(accounts)?;
+    let claimant_info = claim_accounts.claimant;
+    // CHECK:
+    if claims.is_empty() {
+        msg!("No claims provided.");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let current_slot = Clock::get()?.slot;
+
+    let mut packed_account_metas: Vec<AccountMeta> = claim_accounts
+        .packed_accounts
+        .iter()
+        .map(|account_info| AccountMeta {
+            pubkey: *account_info.key,
+            is_signer: false,
+            is_writable: account_info.is_writable,
+        })
+        .collect();
+    let mut signer_seeds = Vec::with_capacity(claims.len());
+    let mut token_accounts = Vec::with_capacity(claims.len());
+    for claim in claims {
+        let associated_airdrop_pda_info =
+            claim_accounts.packed_account(claim.associated_airdrop_pda_index)?;
+        let mint_info = claim_accounts.packed_account(claim.mint_index)?;
+        // CHECK:
+        if current_slot < claim.unlock_slot {
+            msg!(
+                "Tokens are still locked: current slot ({}) is less than unlock slot ({}).",
+                current_slot,
+                claim.unlock_slot
+            );
+            return Err(ClaimError::TokensLocked.into());
+        }
+        // CHECK:
+        if let Some(clawback) = claim.clawback.as_ref() {
+            if current_slot >= clawback.expiry_slot {
+                msg!(
+                    "Airdrop has expired: current slot ({}) is not less than expiry slot ({}).",
+                    current_slot,
+                    clawback.expiry_slot
+                );
+                return Err(ClaimError::AirdropExpired.into());
+            }
+        }
+
+        let mut seeds = airdrop_pda_seeds(
+            claimant_info.key,
+            mint_info.key,
+            claim.unlock_slot,
+            None,
+            claim.clawback.as_ref(),
+        );
+        seeds.push(vec![claim.bump_seed]);
+        let seed_slices: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        check_claim_pda(&seed_slices, &crate::ID, associated_airdrop_pda_info.key)?;
+        packed_account_metas[claim.associated_airdrop_pda_index as usize].is_signer = true;
+
+        let decimals = StateWithExtensions::<Mint>::unpack(&mint_info.try_borrow_data()?)?
+            .base
+            .decimals;
+        // Airdrops are compressed with the v1 compressed token program.
+        let mut token_account = CTokenAccount2::new(vec![MultiInputTokenDataWithContext {
+            owner: claim.associated_airdrop_pda_index,
+            amount: claim.amount,
+            has_delegate: false,
+            delegate: 0,
+            mint: claim.mint_index,
+            version: TokenDataVersion::V1 as u8,
+            merkle_context: PackedMerkleContext {
+                merkle_tree_pubkey_index: claim.packed_tree_info.merkle_tree_pubkey_index,
+                queue_pubkey_index: claim.packed_tree_info.queue_pubkey_index,
+                leaf_index: claim.packed_tree_info.leaf_index,
+                prove_by_index: claim.packed_tree_info.prove_by_index,
+            },
+            root_index: claim.packed_tree_info.root_index,
+        }])?;
+        token_account.decompress_spl(
+            claim.amount,
+            claim.decompress_destination_index,
+            claim.token_pool_index,
+            0,
+            claim.token_pool_bump,
+            decimals,
+        )?;
+        token_accounts.push(token_account);
+        signer_seeds.push(seeds);
+    }
+
+    let instruction = create_transfer2_instruction(Transfer2Inputs {
+        token_accounts,
+        validity_proof: proof,
+        meta_config: Transfer2AccountsMetaConfig::new(
+            *claim_accounts.fee_payer.key,
+            packed_account_metas,
+        ),
+        output_queue: output_queue_index,
+        ..Default::default()
+    })?;
+
+    let signer_seeds: Vec<Vec<&[u8]>> = signer_seeds
+        .iter()
+        .map(|seeds| seeds.iter().map(Vec::as_slice).collect())
+        .collect();
+    let signers_seeds: Vec<&[&[u8]]> = signer_seeds.iter().map(Vec::as_slice).collect();
+    invoke_signed(&instruction, claim_accounts.account_infos, &signers_seeds)?;
+    Ok(())
+}
+
+fn process_clawback(accounts: &[AccountInfo], ix_data: ClawbackIxData) -> ProgramResult {
+    let ClawbackIxData {
+        proof,
+        packed_tree_info,
+        amount,
+        lamports,
+        mint,
+        claimant,
+        unlock_slot,
+        vesting,
+        expiry_slot,
+        bump_seed,
+    } = ix_data;
+
+    // The clawback authority signs in place of the claimant.
+    let claim_accounts = ClaimAccountInfos::parse(accounts, ClaimDestination::Decompress, false)?;
+    let authority_info = claim_accounts.claimant;
+    let associated_airdrop_pda_info = claim_accounts.associated_airdrop_pda;
+    // CHECK:
+    let current_slot = Clock::get()?.slot;
+    if current_slot < expiry_slot {
+        msg!(
+            "Airdrop has not expired: current slot ({}) is less than expiry slot ({}).",
+            current_slot,
+            expiry_slot
+        );
+        return Err(ClaimError::AirdropNotExpired.into());
+    }
+
+    // Only the authority recorded in the seeds at compress time derives the airdrop PDA.
+    let clawback = ClawbackConfig {
+        authority: *authority_info.key,
+        expiry_slot,
+    };
+    let seeds = airdrop_pda_seeds(
+        &claimant,
+        &mint,
+        unlock_slot,
+        vesting.as_ref(),
+        Some(&clawback),
+    );
+    let bump_bytes = [bump_seed];
+    let mut seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+    seeds.push(&bump_bytes[..]);
+
+    check_claim_pda(&seeds, &crate::ID, associated_airdrop_pda_info.key)?;
+
+    decompress_from_airdrop_pda(
+        &claim_accounts,
+        &seeds,
+        proof,
+        packed_tree_info,
+        mint,
+        amount,
+        lamports,
+        amount,
+    )
+}
+
+fn process_distributor_claim(
+    accounts: &[AccountInfo],
+    ix_data: DistributorClaimIxData,
+) -> ProgramResult {
+    let DistributorClaimIxData {
+        proof,
+        packed_tree_info,
+        balance,
+        lamports,
+        mint,
+        merkle_root,
+        bump_seed,
+        leaf_index,
+        amount,
+        unlock_slot,
+        merkle_proof,
+        receipt_proof,
+        address_tree_info,
+        output_state_tree_index,
+    } = ix_data;
+
+    // The light system accounts of the claim receipt follow the claim accounts.
+    let claim_accounts = ClaimAccountInfos::parse(accounts, ClaimDestination::Decompress, true)?;
+    let claimant_info = claim_accounts.claimant;
+    let distributor_pda_info = claim_accounts.associated_airdrop_pda;
     // CHECK:
-    if !fee_payer_info.is_signer {
-        msg!("Fee payer must be a signer");
-        fee_payer_info.key.log();
-        return Err(ProgramError::MissingRequiredSignature);
+    let current_slot = Clock::get()?.slot;
+    if current_slot < unlock_slot {
+        msg!(
+            "Tokens are still locked: current slot ({}) is less than unlock slot ({}).",
+            current_slot,
+            unlock_slot
+        );
+        return Err(ClaimError::TokensLocked.into());
     }
     // CHECK:
-    if ctoken_program_info.key != &light_token::id() {
-        msg!("Invalid compressed token program.",);
-        ctoken_program_info.key.log();
-        return Err(ProgramError::InvalidArgument);
+    let leaf = DistributorLeaf {
+        index: leaf_index,
+        claimant: *claimant_info.key,
+        amount,
+        unlock_slot,
+    };
+    if !verify_distributor_proof(&merkle_root, &leaf, &merkle_proof) {
+        msg!("Invalid Merkle proof.");
+        return Err(ClaimError::InvalidMerkleProof.into());
     }
+
+    let seeds = distributor_pda_seeds(&mint, &merkle_root);
+    let bump_bytes = [bump_seed];
+    let mut seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+    seeds.push(&bump_bytes[..]);
+
+    check_claim_pda(&seeds, &crate::ID, distributor_pda_info.key)?;
+
+    create_claim_receipt(
+        claim_accounts.fee_payer,
+        claim_accounts.remaining_accounts,
+        distributor_pda_info.key,
+        &leaf,
+        receipt_proof,
+        address_tree_info,
+        output_state_tree_index,
+    )?;
+
+    decompress_from_airdrop_pda(
+        &claim_accounts,
+        &seeds,
+        proof,
+        packed_tree_info,
+        mint,
+        balance,
+        lamports,
+        amount,
+    )
+}
+
+/// Creates the claim receipt of a distributor leaf.
+///
+/// The address is unique per distributor and leaf index, claiming a leaf twice fails.
+fn create_claim_receipt<'info>(
+    fee_payer_info: &AccountInfo<'info>,
+    light_system_accounts: &[AccountInfo<'info>],
+    distributor_pda: &Pubkey,
+    leaf: &DistributorLeaf,
+    receipt_proof: ValidityProof,
+    address_tree_info: PackedAddressTreeInfo,
+    output_state_tree_index: u8,
+) -> ProgramResult {
+    let light_cpi_accounts =
+        CpiAccounts::new(fee_payer_info, light_system_accounts, LIGHT_CPI_SIGNER);
+
+    let address_tree_pubkey = address_tree_info
+        .get_tree_pubkey(&light_cpi_accounts)
+        .map_err(|_| ProgramError::NotEnoughAccountKeys)?;
     // CHECK:
-    if token_program_info.key != &spl_token::ID {
-        msg!("Invalid SPL token program.");
-        token_program_info.key.log();
-        return Err(ProgramError::InvalidArgument);
+    if address_tree_pubkey.to_bytes() != ADDRESS_TREE_V2 {
+        msg!("Invalid address tree");
+        return Err(ProgramError::InvalidAccountData);
     }
+    let (address, address_seed) = derive_address(
+        &[
+            CLAIM_RECEIPT_SEED,
+            distributor_pda.as_ref(),
+            &leaf.index.to_le_bytes(),
+        ],
+        &address_tree_pubkey,
+        &crate::ID,
+    );
+
+    let mut claim_receipt =
+        LightAccount::<ClaimReceipt>::new_init(&crate::ID, Some(address), output_state_tree_index);
+    claim_receipt.leaf_index = leaf.index;
+    claim_receipt.claimant = leaf.claimant;
+    claim_receipt.amount = leaf.amount;
+
+    LightSystemProgramCpi::new_cpi(LIGHT_CPI_SIGNER, receipt_proof)
+        .with_light_account(claim_receipt)?
+        .with_new_addresses(&[
+            address_tree_info.into_new_address_params_assigned_packed(address_seed, Some(0))
+        ])
+        .invoke(light_cpi_accounts)?;
+
+    Ok(())
+}
+
+/// Decompresses `decompress_amount` of the compressed token account owned by the airdrop PDA.
+///
+/// `amount` is the balance of the compressed token account,
+/// the remainder stays compressed in the airdrop PDA.
+#[allow(clippy::too_many_arguments)]
+fn decompress_from_airdrop_pda(
+    claim_accounts: &ClaimAccountInfos,
+    seeds: &[&[u8]],
+    proof: ValidityProof,
+    packed_tree_info: PackedStateTreeInfo,
+    mint: Pubkey,
+    amount: u64,
+    lamports: Option<u64>,
+    decompress_amount: u64,
+) -> ProgramResult {
+    let decompress_accounts = claim_accounts
+        .decompress
+        .as_ref()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let fee_payer_info = claim_accounts.fee_payer;
+    let associated_airdrop_pda_info = claim_accounts.associated_airdrop_pda;
+    let spl_interface_pda_info = decompress_accounts.token_pool_pda;
+    let decompress_destination_info = decompress_accounts.decompress_destination;
+    let token_program_info = decompress_accounts.token_program;
+    let state_tree_info = claim_accounts.state_tree;
+    let queue_info = claim_accounts.queue;
+
     let compressed_token_account = CTokenAccount::new(
         mint,
-        associated_airdrop_pda_info.key.clone(),
+        *associated_airdrop_pda_info.key,
         vec![TokenAccountMeta {
             amount,
             delegate_index: None,
@@ -90,46 +601,315 @@ fn process_claim(accounts: &[AccountInfo], ix_data: ClaimIxData) -> ProgramResul
         1,
     );
     let decompress_inputs = DecompressInputs {
-        fee_payer: fee_payer_info.key.clone(),
+        fee_payer: *fee_payer_info.key,
         validity_proof: proof,
         sender_account: compressed_token_account,
-        amount,
-        tree_pubkeys: vec![state_tree_info.key.clone(), queue_info.key.clone()],
+        amount: decompress_amount,
+        tree_pubkeys: vec![*state_tree_info.key, *queue_info.key],
         config: None,
-        spl_interface_pda: spl_interface_pda_info.key.clone(),
-        recipient_token_account: decompress_destination_info.key.clone(),
-        spl_token_program: token_program_info.key.clone(),
+        spl_interface_pda: *spl_interface_pda_info.key,
+        recipient_token_account: *decompress_destination_info.key,
+        spl_token_program: *token_program_info.key,
     };
 
     let instruction =
-        light_compressed_token_sdk::compressed_token::transfer::instruction::decompress(decompress_inputs)?;
+        light_compressed_token_sdk::compressed_token::transfer::instruction::decompress(
+            decompress_inputs,
+        )?;
 
+    let signers_seeds: &[&[&[u8]]] = &[seeds];
+    invoke_signed(&instruction, claim_accounts.account_infos, signers_seeds)?;
+    Ok(())
+}
+
+/// Decompresses `claim_amount - relayer_fee` of the compressed token account owned by
+/// the airdrop PDA and transfers `relayer_fee` to the fee payer as compressed tokens,
+/// both in one compressed token transfer.
+#[allow(clippy::too_many_arguments)]
+fn decompress_with_relayer_fee(
+    claim_accounts: &ClaimAccountInfos,
+    seeds: &[&[u8]],
+    proof: ValidityProof,
+    packed_tree_info: PackedStateTreeInfo,
+    mint: Pubkey,
+    amount: u64,
+    lamports: Option<u64>,
+    claim_amount: u64,
+    relayer_fee: u64,
+) -> ProgramResult {
+    let decompress_accounts = claim_accounts
+        .decompress
+        .as_ref()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let fee_payer_info = claim_accounts.fee_payer;
+    let associated_airdrop_pda_info = claim_accounts.associated_airdrop_pda;
+
+    let mut compressed_token_account = CTokenAccount::new(
+        mint,
+        *associated_airdrop_pda_info.key,
+        vec![TokenAccountMeta {
+            amount,
+            delegate_index: None,
+            packed_tree_info,
+            lamports,
+            tlv: None,
+        }],
+        1,
+    );
+    let relayer_token_account =
+        compressed_token_account.transfer(fee_payer_info.key, relayer_fee, None)?;
+    compressed_token_account.decompress(claim_amount - relayer_fee)?;
+
+    let meta_config = TokenAccountsMetaConfig::decompress(
+        *fee_payer_info.key,
+        *associated_airdrop_pda_info.key,
+        *decompress_accounts.token_pool_pda.key,
+        *decompress_accounts.decompress_destination.key,
+        *decompress_accounts.token_program.key,
+    );
+    let instruction = create_transfer_instruction_raw(
+        mint,
+        vec![relayer_token_account, compressed_token_account],
+        proof,
+        TransferConfig::default(),
+        meta_config,
+        vec![*claim_accounts.state_tree.key, *claim_accounts.queue.key],
+    )?;
+
+    let signers_seeds: &[&[&[u8]]] = &[seeds];
+    invoke_signed(&instruction, claim_accounts.account_infos, signers_seeds)?;
+    Ok(())
+}
+
+/// Creates or updates the claim record of a vested airdrop and returns the amount to decompress.
+///
+/// The total allocation is the remaining balance of the compressed token account (`amount`)
+/// plus what has already been withdrawn.
+fn update_claim_record<'info>(
+    fee_payer_info: &AccountInfo<'info>,
+    associated_airdrop_pda: &Pubkey,
+    light_system_accounts: &[AccountInfo<'info>],
+    vesting: VestingIxData,
+    amount: u64,
+    unlock_slot: u64,
+    current_slot: u64,
+) -> Result<u64, ProgramError> {
+    let VestingIxData {
+        schedule,
+        record_proof,
+        record,
+    } = vesting;
     // CHECK:
-    let current_slot = Clock::get()?.slot;
-    if current_slot < unlock_slot {
+    if schedule.start_slot >= schedule.end_slot {
         msg!(
-            "Tokens are still locked: current slot ({}) is less than unlock slot ({}).",
-            current_slot,
-            unlock_slot
+            "Invalid vesting schedule: start slot ({}) must be less than end slot ({}).",
+            schedule.start_slot,
+            schedule.end_slot
         );
-        return Err(ClaimError::TokensLocked.into());
+        return Err(ClaimError::InvalidVestingSchedule.into());
     }
 
-    let claimant_bytes = claimant_info.key.to_bytes();
-    let slot_bytes = unlock_slot.to_le_bytes();
-    let mint_bytes = mint.to_bytes();
+    let light_cpi_accounts =
+        CpiAccounts::new(fee_payer_info, light_system_accounts, LIGHT_CPI_SIGNER);
 
-    let seeds = &[
-        &claimant_bytes[..32],
-        &mint_bytes[..32],
-        &slot_bytes[..8],
-        &[bump_seed],
-    ];
+    let (mut claim_record, new_address_params) = match record {
+        ClaimRecordIxData::Create {
+            address_tree_info,
+            output_state_tree_index,
+        } => {
+            let address_tree_pubkey = address_tree_info
+                .get_tree_pubkey(&light_cpi_accounts)
+                .map_err(|_| ProgramError::NotEnoughAccountKeys)?;
+            // CHECK:
+            if address_tree_pubkey.to_bytes() != ADDRESS_TREE_V2 {
+                msg!("Invalid address tree");
+                return Err(ProgramError::InvalidAccountData);
+            }
+            // The address is unique per airdrop PDA, creating a second record fails.
+            let (address, address_seed) = derive_address(
+                &[CLAIM_RECORD_SEED, associated_airdrop_pda.as_ref()],
+                &address_tree_pubkey,
+                &crate::ID,
+            );
+            let mut claim_record = LightAccount::<ClaimRecord>::new_init(
+                &crate::ID,
+                Some(address),
+                output_state_tree_index,
+            );
+            claim_record.associated_airdrop_pda = *associated_airdrop_pda;
+            let new_address_params =
+                address_tree_info.into_new_address_params_assigned_packed(address_seed, Some(0));
+            (claim_record, Some(new_address_params))
+        }
+        ClaimRecordIxData::Update {
+            account_meta,
+            withdrawn,
+        } => {
+            let (address, _) = derive_address(
+                &[CLAIM_RECORD_SEED, associated_airdrop_pda.as_ref()],
+                &Pubkey::new_from_array(ADDRESS_TREE_V2),
+                &crate::ID,
+            );
+            // CHECK:
+            if account_meta.address != address {
+                msg!("Invalid claim record address.");
+                return Err(ClaimError::InvalidClaimRecord.into());
+            }
+            // The input account hash commits to the airdrop PDA,
+            // a record of another airdrop fails the validity proof.
+            let claim_record = LightAccount::<ClaimRecord>::new_mut(
+                &crate::ID,
+                &account_meta,
+                ClaimRecord {
+                    associated_airdrop_pda: *associated_airdrop_pda,
+                    withdrawn,
+                },
+            )?;
+            (claim_record, None)
+        }
+    };
 
-    check_claim_pda(seeds, &crate::ID, associated_airdrop_pda_info.key)?;
+    let total = amount
+        .checked_add(claim_record.withdrawn)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let vested = schedule.vested_amount(total, unlock_slot, current_slot);
+    let claim_amount = vested.saturating_sub(claim_record.withdrawn);
+    // CHECK:
+    if claim_amount == 0 {
+        msg!(
+            "Nothing to claim: vested ({}), withdrawn ({}).",
+            vested,
+            claim_record.withdrawn
+        );
+        return Err(ClaimError::NothingToClaim.into());
+    }
+    claim_record.withdrawn = vested;
+
+    let cpi = LightSystemProgramCpi::new_cpi(LIGHT_CPI_SIGNER, record_proof)
+        .with_light_account(claim_record)?;
+    match new_address_params {
+        Some(new_address_params) => cpi
+            .with_new_addresses(&[new_address_params])
+            .invoke(light_cpi_accounts)?,
+        None => cpi.invoke(light_cpi_accounts)?,
+    }
+
+    Ok(claim_amount)
+}
 
-    let signers_seeds: &[&[&[u8]]] = &[&seeds[..]];
-    invoke_signed(&instruction, &accounts, signers_seeds)?;
+/// Transfers `transfer_amount` of the compressed token account owned by the airdrop PDA
+/// to the claimant as compressed tokens.
+///
+/// `amount` is the balance of the compressed token account,
+/// the remainder stays compressed in the airdrop PDA.
+#[allow(clippy::too_many_arguments)]
+fn transfer_from_airdrop_pda(
+    claim_accounts: &ClaimAccountInfos,
+    seeds: &[&[u8]],
+    proof: ValidityProof,
+    packed_tree_info: PackedStateTreeInfo,
+    mint: Pubkey,
+    amount: u64,
+    lamports: Option<u64>,
+    transfer_amount: u64,
+) -> ProgramResult {
+    let claimant_info = claim_accounts.claimant;
+    let fee_payer_info = claim_accounts.fee_payer;
+    let associated_airdrop_pda_info = claim_accounts.associated_airdrop_pda;
+    let state_tree_info = claim_accounts.state_tree;
+    let queue_info = claim_accounts.queue;
+
+    let compressed_token_account = CTokenAccount::new(
+        mint,
+        *associated_airdrop_pda_info.key,
+        vec![TokenAccountMeta {
+            amount,
+            delegate_index: None,
+            packed_tree_info,
+            lamports,
+            tlv: None,
+        }],
+        1,
+    );
+    let transfer_inputs = TransferInputs {
+        fee_payer: *fee_payer_info.key,
+        validity_proof: proof,
+        sender_account: compressed_token_account,
+        amount: transfer_amount,
+        recipient: *claimant_info.key,
+        tree_pubkeys: vec![*state_tree_info.key, *queue_info.key],
+        config: None,
+    };
+
+    let instruction =
+        light_compressed_token_sdk::compressed_token::transfer::instruction::transfer(
+            transfer_inputs,
+        )?;
+
+    let signers_seeds: &[&[&[u8]]] = &[seeds];
+    invoke_signed(&instruction, claim_accounts.account_infos, signers_seeds)?;
+    Ok(())
+}
+
+/// Checks that the instruction before the current one is an ed25519 instruction
+/// verifying one signature of `signer` over `message`.
+///
+/// The ed25519 program fails the transaction if the signature is invalid,
+/// so only the signed public key and message are compared here.
+fn check_ed25519_authorization(
+    instructions_sysvar_info: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> ProgramResult {
+    let current_index = load_current_index_checked(instructions_sysvar_info)?;
+    let ed25519_instruction = match current_index.checked_sub(1) {
+        Some(index) => load_instruction_at_checked(index as usize, instructions_sysvar_info)?,
+        None => {
+            msg!("Missing ed25519 instruction before the relayed claim.");
+            return Err(ClaimError::InvalidClaimAuthorization.into());
+        }
+    };
+    if ed25519_instruction.program_id != ed25519_program::ID {
+        msg!("Instruction before the relayed claim is not an ed25519 instruction.");
+        return Err(ClaimError::InvalidClaimAuthorization.into());
+    }
+
+    // [num_signatures: u8, padding: u8, offsets: 7 x u16], see `Ed25519SignatureOffsets`.
+    let data = &ed25519_instruction.data;
+    if data.len() < ED25519_OFFSETS_START + ED25519_OFFSETS_SIZE || data[0] != 1 {
+        msg!("Expected exactly one ed25519 signature.");
+        return Err(ClaimError::InvalidClaimAuthorization.into());
+    }
+    let offset = |index: usize| {
+        let start = ED25519_OFFSETS_START + index * 2;
+        u16::from_le_bytes([data[start], data[start + 1]])
+    };
+    let signature_instruction_index = offset(1);
+    let public_key_offset = offset(2) as usize;
+    let public_key_instruction_index = offset(3);
+    let message_offset = offset(4) as usize;
+    let message_size = offset(5) as usize;
+    let message_instruction_index = offset(6);
+    // The signature, public key and message must be stored in the ed25519 instruction itself.
+    if [
+        signature_instruction_index,
+        public_key_instruction_index,
+        message_instruction_index,
+    ]
+    .iter()
+    .any(|index| *index != u16::MAX)
+    {
+        msg!("Ed25519 signature data must be part of the ed25519 instruction.");
+        return Err(ClaimError::InvalidClaimAuthorization.into());
+    }
+
+    let signed_public_key = data.get(public_key_offset..public_key_offset + 32);
+    let signed_message = data.get(message_offset..message_offset + message_size);
+    if signed_public_key != Some(signer.as_ref()) || signed_message != Some(message) {
+        msg!("Ed25519 signature does not authorize this claim.");
+        signer.log();
+        return Err(ClaimError::InvalidClaimAuthorization.into());
+    }
     Ok(())
 }
 
@@ -0,0 +1,39 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use light_sdk::{LightDiscriminator, LightHasher};
+use solana_program::pubkey::Pubkey;
+
+/// Address seed of the compressed claim record of a vested airdrop.
+pub const CLAIM_RECORD_SEED: &[u8] = b"claim_record";
+
+/// Compressed PDA tracking how much a claimant has already withdrawn from a vested airdrop.
+///
+/// The address is derived from `[CLAIM_RECORD_SEED, associated_airdrop_pda]`,
+/// so every airdrop PDA has at most one claim record.
+#[derive(
+    Debug, Default, Clone, BorshSerialize, BorshDeserialize, LightDiscriminator, LightHasher,
+)]
+pub struct ClaimRecord {
+    #[hash]
+    pub associated_airdrop_pda: Pubkey,
+    pub withdrawn: u64,
+}
+
+/// Seed of the distributor PDA that owns the compressed tokens of a Merkle distributor.
+pub const DISTRIBUTOR_SEED: &[u8] = b"distributor";
+
+/// Address seed of the claim receipt of a distributor claim.
+pub const CLAIM_RECEIPT_SEED: &[u8] = b"claim_receipt";
+
+/// Compressed PDA created when a claimant claims from a Merkle distributor.
+///
+/// The address is derived from `[CLAIM_RECEIPT_SEED, distributor_pda, leaf_index]`,
+/// so every leaf can be claimed at most once.
+#[derive(
+    Debug, Default, Clone, BorshSerialize, BorshDeserialize, LightDiscriminator, LightHasher,
+)]
+pub struct ClaimReceipt {
+    pub leaf_index: u64,
+    #[hash]
+    pub claimant: Pubkey,
+    pub amount: u64,
+}
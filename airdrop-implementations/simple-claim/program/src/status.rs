@@ -0,0 +1,234 @@
+//! Client side status queries of airdrops.
+//!
+//! Derives the associated airdrop PDA of a claimant and reads its compressed token accounts
+//! through an indexer, e.g. `LightProgramTest` or a `PhotonIndexer`.
+//!
+//! Spent compressed token accounts are not returned by the indexer, so an airdrop PDA without
+//! balance is either not funded yet or claimed. Vested airdrops are told apart by their claim record.
+//! Non-vested claims and clawbacks leave no state to tell them apart,
+//! the status of such an airdrop PDA is [`StatusError::UnknownClaimState`].
+use borsh::BorshDeserialize;
+use light_client::indexer::{
+    GetCompressedTokenAccountsByOwnerOrDelegateOptions, Indexer, IndexerError,
+};
+use light_sdk::constants::ADDRESS_TREE_V2;
+use solana_program::pubkey::Pubkey;
+use thiserror::Error;
+
+use crate::{
+    instruction::{
+        derive_claim_record_address, find_associated_airdrop_pda, ClawbackConfig, VestingSchedule,
+    },
+    planner::AirdropManifest,
+    state::ClaimRecord,
+};
+
+#[derive(Error, Debug)]
+pub enum StatusError {
+    #[error(transparent)]
+    Indexer(#[from] IndexerError),
+    #[error("Airdrop PDA {0} holds no tokens and has no claim record, it is either claimed or not funded.")]
+    UnknownClaimState(Pubkey),
+    #[error("Invalid claim record of airdrop PDA {0}.")]
+    InvalidClaimRecord(Pubkey),
+}
+
+/// Status of the airdrop of one mint and unlock slot to a claimant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AirdropStatus {
+    /// No compressed tokens have been sent to the airdrop PDA.
+    NotFunded,
+    /// The airdrop PDA holds `amount`, it can be claimed from `unlock_slot` on.
+    Locked { amount: u64, unlock_slot: u64 },
+    /// `amount` can be claimed now, for vested airdrops the vested part of the balance not withdrawn yet.
+    Claimable { amount: u64 },
+    /// The airdrop PDA has been emptied by claims, or by a clawback.
+    Claimed,
+}
+
+impl AirdropStatus {
+    /// Derives the status from the balance of the airdrop PDA, the part of it that is `claimable`
+    /// and whether the airdrop PDA has been claimed from.
+    pub fn new(
+        balance: u64,
+        claimable: u64,
+        claimed: bool,
+        unlock_slot: u64,
+        current_slot: u64,
+    ) -> Self {
+        if balance == 0 {
+            if claimed {
+                AirdropStatus::Claimed
+            } else {
+                AirdropStatus::NotFunded
+            }
+        } else if current_slot < unlock_slot {
+            AirdropStatus::Locked {
+                amount: balance,
+                unlock_slot,
+            }
+        } else {
+            AirdropStatus::Claimable { amount: claimable }
+        }
+    }
+}
+
+/// Status of a manifest entry of a claimant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClaimantAirdropStatus {
+    pub mint: Pubkey,
+    pub unlock_slot: u64,
+    pub airdrop_pda: Pubkey,
+    pub status: AirdropStatus,
+}
+
+/// Returns the status of the airdrop of `mint` unlocking at `unlock_slot` to `claimant`,
+/// `vesting` and `clawback` are the ones the airdrop PDA was derived with.
+///
+/// `current_slot` decides whether a funded airdrop is locked or claimable
+/// and how much of a vested airdrop is claimable.
+pub async fn get_airdrop_status<I: Indexer>(
+    indexer: &I,
+    claimant: &Pubkey,
+    mint: &Pubkey,
+    unlock_slot: u64,
+    vesting: Option<&VestingSchedule>,
+    clawback: Option<&ClawbackConfig>,
+    current_slot: u64,
+) -> Result<AirdropStatus, StatusError> {
+    let (airdrop_pda, _) =
+        find_associated_airdrop_pda(claimant, mint, unlock_slot, vesting, clawback);
+    get_airdrop_pda_status(
+        indexer,
+        &airdrop_pda,
+        mint,
+        unlock_slot,
+        vesting,
+        clawback.is_some(),
+        current_slot,
+    )
+    .await
+}
+
+/// Returns the status of every airdrop of `claimant` in `manifest`.
+///
+/// Manifest airdrops are not vested, an entry without balance fails with [`StatusError::UnknownClaimState`].
+pub async fn get_claimant_statuses<I: Indexer>(
+    indexer: &I,
+    manifest: &AirdropManifest,
+    claimant: &Pubkey,
+    current_slot: u64,
+) -> Result<Vec<ClaimantAirdropStatus>, StatusError> {
+    let mut statuses = Vec::new();
+    for entry in manifest
+        .entries
+        .iter()
+        .filter(|entry| entry.claimant == *claimant)
+    {
+        let status = get_airdrop_pda_status(
+            indexer,
+            &entry.airdrop_pda,
+            &manifest.mint,
+            entry.unlock_slot,
+            None,
+            false,
+            current_slot,
+        )
+        .await?;
+        statuses.push(ClaimantAirdropStatus {
+            mint: manifest.mint,
+            unlock_slot: entry.unlock_slot,
+            airdrop_pda: entry.airdrop_pda,
+            status,
+        });
+    }
+    Ok(statuses)
+}
+
+async fn get_airdrop_pda_status<I: Indexer>(
+    indexer: &I,
+    airdrop_pda: &Pubkey,
+    mint: &Pubkey,
+    unlock_slot: u64,
+    vesting: Option<&VestingSchedule>,
+    clawback: bool,
+    current_slot: u64,
+) -> Result<AirdropStatus, StatusError> {
+    let balance = get_balance(indexer, airdrop_pda, mint).await?;
+    let Some(schedule) = vesting else {
+        if balance == 0 {
+            return Err(StatusError::UnknownClaimState(*airdrop_pda));
+        }
+        return Ok(AirdropStatus::new(
+            balance,
+            balance,
+            false,
+            unlock_slot,
+            current_slot,
+        ));
+    };
+
+    // Vested claims create the claim record of the airdrop PDA,
+    // only a clawback empties the airdrop PDA without one.
+    let withdrawn = get_withdrawn(indexer, airdrop_pda).await?;
+    if balance == 0 && withdrawn.is_none() && clawback {
+        return Err(StatusError::UnknownClaimState(*airdrop_pda));
+    }
+    let withdrawn = withdrawn.unwrap_or_default();
+    let total = balance.saturating_add(withdrawn);
+    let claimable = schedule
+        .vested_amount(total, unlock_slot, current_slot)
+        .saturating_sub(withdrawn);
+    Ok(AirdropStatus::new(
+        balance,
+        claimable,
+        withdrawn > 0,
+        unlock_slot,
+        current_slot,
+    ))
+}
+
+/// Sums the compressed token accounts of `mint` owned by the airdrop PDA, over all pages.
+async fn get_balance<I: Indexer>(
+    indexer: &I,
+    airdrop_pda: &Pubkey,
+    mint: &Pubkey,
+) -> Result<u64, IndexerError> {
+    let mut balance = 0u64;
+    let mut cursor = None;
+    loop {
+        let options = Some(GetCompressedTokenAccountsByOwnerOrDelegateOptions {
+            mint: Some(*mint),
+            cursor,
+            limit: None,
+        });
+        let page = indexer
+            .get_compressed_token_accounts_by_owner(airdrop_pda, options, None)
+            .await?
+            .value;
+        balance = page.items.iter().fold(balance, |balance, token_account| {
+            balance.saturating_add(token_account.token.amount)
+        });
+        match page.cursor {
+            Some(next) if !page.items.is_empty() => cursor = Some(next),
+            _ => return Ok(balance),
+        }
+    }
+}
+
+/// Returns the amount withdrawn from a vested airdrop PDA, `None` without claim record.
+async fn get_withdrawn<I: Indexer>(
+    indexer: &I,
+    airdrop_pda: &Pubkey,
+) -> Result<Option<u64>, StatusError> {
+    let address =
+        derive_claim_record_address(airdrop_pda, &Pubkey::new_from_array(ADDRESS_TREE_V2));
+    let Some(account) = indexer.get_compressed_account(address, None).await?.value else {
+        return Ok(None);
+    };
+    let claim_record = account
+        .data
+        .and_then(|data| ClaimRecord::try_from_slice(&data.data).ok())
+        .ok_or(StatusError::InvalidClaimRecord(*airdrop_pda))?;
+    Ok(Some(claim_record.withdrawn))
+}
@@ -1,20 +1,46 @@
 #![cfg(feature = "test-sbf")]
 
-use light_client::indexer::GetCompressedTokenAccountsByOwnerOrDelegateOptions;
+use borsh::BorshDeserialize;
+use light_client::indexer::{AddressWithTree, GetCompressedTokenAccountsByOwnerOrDelegateOptions};
 use light_compressed_claim::instruction::{
-    build_claim_and_decompress_instruction, compress, ClaimAccounts,
+    batch_compress, build_claim_and_decompress_instruction, build_claim_compressed_instruction,
+    build_claim_many_instruction, build_clawback_instruction, build_distributor_claim_instruction,
+    build_relayed_claim_instruction, build_vested_claim_instruction, compress,
+    derive_claim_receipt_address, derive_claim_record_address, distributor_funding,
+    find_associated_airdrop_pda, find_distributor_pda, select_distributor_account, ClaimAccounts,
+    ClaimCompressedAccounts, ClaimIxData, ClaimManyAccounts, ClaimRecordIxData, ClawbackConfig,
+    DistributorClaimIxData, DistributorLeaf, DistributorTree, MintClaim, RelayedClaimIxData,
+    RelayedClaimMessage, VestingIxData, VestingSchedule,
+};
+use light_compressed_claim::planner::{
+    AirdropEntry, AirdropManifest, AirdropPlanner, MAX_TRANSACTION_SIZE,
+};
+use light_compressed_claim::state::ClaimRecord;
+use light_compressed_claim::status::{
+    get_airdrop_status, get_claimant_statuses, AirdropStatus, StatusError,
 };
-use light_token::constants::{ACCOUNT_COMPRESSION_PROGRAM_ID, LIGHT_SYSTEM_PROGRAM_ID};
-use light_token::spl_interface::{get_spl_interface_pda, CreateSplInterfacePda};
 use light_program_test::accounts::test_accounts::NOOP_PROGRAM_ID;
 use light_program_test::program_test::TestRpc;
-use light_program_test::{program_test::LightProgramTest, Indexer, ProgramTestConfig, Rpc};
-use light_sdk::instruction::PackedStateTreeInfo;
+use light_program_test::{
+    program_test::LightProgramTest, Indexer, ProgramTestConfig, Rpc, RpcError,
+};
+use light_sdk::instruction::{
+    account_meta::CompressedAccountMeta, PackedAccounts, PackedStateTreeInfo,
+    SystemAccountMetaConfig,
+};
+use light_token::constants::{ACCOUNT_COMPRESSION_PROGRAM_ID, LIGHT_SYSTEM_PROGRAM_ID};
+use light_token::spl_interface::{get_spl_interface_pda, CreateSplInterfacePda};
+use solana_sdk::ed25519_instruction::new_ed25519_instruction_with_signature;
+use solana_sdk::instruction::{AccountMeta, Instruction};
 use solana_sdk::program_pack::Pack;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signature::{Keypair, Signer};
-use spl_token::{
-    id, instruction,
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::instruction::initialize_transfer_fee_config, ExtensionType,
+        StateWithExtensions,
+    },
+    instruction,
     state::{Account, Mint},
 };
 
@@ -82,33 +108,15 @@ async fn test_claim_and_decompress() {
         .await
         .unwrap();
 
-    let spl_interface_pda = get_spl_interface_pda(&mint.pubkey(), false);
-    let accounts = ClaimAccounts {
-        claimant: claimant.pubkey(),
-        fee_payer: payer.pubkey(),
-        associated_airdrop_pda: claimant_pda,
-        ctoken_cpi_authority_pda: Pubkey::from_str_const(
-            "GXtd2izAiMJPwMEjfgTRH3d7k9mjn4Jq3JrWFv9gySYy",
-        ),
-        light_system_program: LIGHT_SYSTEM_PROGRAM_ID,
-        registered_program_pda: Pubkey::from_str_const(
-            "35hkDgaAKwMCaxRz2ocSZ6NaUrtKkyNqU6c4RV3tYJRh",
-        ),
-        noop_program: NOOP_PROGRAM_ID,
-        account_compression_authority: Pubkey::find_program_address(
-            &[b"cpi_authority"],
-            &LIGHT_SYSTEM_PROGRAM_ID,
-        )
-        .0,
-        account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID.into(),
-        ctoken_program: Pubkey::from_str_const("cTokenmWW8bLPjZEBAUgYy3zKxQZW6VKi7bqNFEVv3m"),
-        token_pool_pda: spl_interface_pda,
-        decompress_destination: token_account.pubkey(),
-        token_program: spl_token::ID,
-        system_program: solana_sdk::system_program::ID,
+    let accounts = claim_accounts(
+        claimant.pubkey(),
+        payer.pubkey(),
+        claimant_pda,
+        mint.pubkey(),
+        token_account.pubkey(),
         state_tree,
         queue,
-    };
+    );
 
     let packed_merkle_context = PackedStateTreeInfo {
         root_index: proof.value.accounts[0]
@@ -130,6 +138,7 @@ async fn test_claim_and_decompress() {
         mint.pubkey(),
         unlock_slot,
         bump_seed,
+        None,
     );
     let instruction_clone = instruction.clone();
 
@@ -159,32 +168,2194 @@ async fn test_claim_and_decompress() {
     assert_eq!(account_data.amount, 10);
 }
 
-pub fn find_claimant_pda(claimant: Pubkey, mint: Pubkey, slot: u64) -> (Pubkey, u8) {
-    let claimant_bytes = claimant.to_bytes();
-    let mint_bytes = mint.to_bytes();
-    let slot_bytes = slot.to_le_bytes();
-    let seeds = &[&claimant_bytes[..32], &mint_bytes[..32], &slot_bytes[..8]];
+#[tokio::test]
+async fn test_claim_and_decompress_token_2022() {
+    let config = ProgramTestConfig::new_v2(
+        true,
+        Some(vec![(
+            "light_compressed_claim",
+            light_compressed_claim::id(),
+        )]),
+    );
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = Keypair::new();
+    rpc.airdrop_lamports(&payer.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+    let state_tree = rpc.test_accounts.v2_state_trees[0].merkle_tree;
+    let queue = rpc.test_accounts.v2_state_trees[0].output_queue;
 
-    Pubkey::find_program_address(seeds, &light_compressed_claim::id())
+    let (mint, token_account, owner) =
+        setup_token_account_with_program(&mut rpc, &spl_token_2022::ID).await;
+    setup_token_pool_with_program(&mut rpc, &mint, &payer, &spl_token_2022::ID).await;
+    let claimant = Keypair::new();
+    let unlock_slot = 1_000;
+    let amount = 2;
+
+    let (claimant_pda, bump_seed) =
+        find_claimant_pda(claimant.pubkey(), mint.pubkey(), unlock_slot);
+
+    let compress_ix = compress(
+        payer.pubkey(),
+        owner.pubkey(),
+        token_account.pubkey(),
+        mint.pubkey(),
+        amount,
+        claimant_pda,
+        queue,
+        spl_token_2022::ID,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(&[compress_ix], &payer.pubkey(), &[&payer, &owner])
+        .await
+        .unwrap();
+
+    let options = Some(GetCompressedTokenAccountsByOwnerOrDelegateOptions {
+        mint: Some(mint.pubkey()),
+        cursor: None,
+        limit: None,
+    });
+    let compressed_token_account = rpc
+        .get_compressed_token_accounts_by_owner(&claimant_pda, options, None)
+        .await
+        .unwrap()
+        .value
+        .items[0]
+        .clone();
+    let proof = rpc
+        .get_validity_proof(vec![compressed_token_account.account.hash], vec![], None)
+        .await
+        .unwrap();
+    let packed_tree_info = PackedStateTreeInfo {
+        root_index: proof.value.accounts[0]
+            .root_index
+            .root_index()
+            .unwrap_or_default(),
+        merkle_tree_pubkey_index: 0,
+        queue_pubkey_index: 1,
+        leaf_index: compressed_token_account.account.leaf_index,
+        prove_by_index: proof.value.accounts[0].root_index.proof_by_index(),
+    };
+
+    let mut accounts = claim_accounts(
+        claimant.pubkey(),
+        payer.pubkey(),
+        claimant_pda,
+        mint.pubkey(),
+        token_account.pubkey(),
+        state_tree,
+        queue,
+    );
+    accounts.token_program = spl_token_2022::ID;
+    let instruction = build_claim_and_decompress_instruction(
+        &accounts,
+        proof.value.proof.clone().into(),
+        packed_tree_info,
+        amount,
+        None,
+        mint.pubkey(),
+        unlock_slot,
+        bump_seed,
+        None,
+    );
+
+    rpc.warp_to_slot(1000).unwrap();
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+
+    let account_info = rpc.get_account(token_account.pubkey()).await.unwrap();
+    let account_data = Account::unpack(&account_info.unwrap().data).unwrap();
+    assert_eq!(account_data.amount, 10);
 }
 
-pub async fn setup_token_pool(rpc: &mut LightProgramTest, mint: &Keypair, payer: &Keypair) {
-    let create_spl_interface_pda_ix =
-        CreateSplInterfacePda::new(payer.pubkey(), mint.pubkey(), spl_token::ID, false).instruction();
-    rpc.create_and_send_transaction(&[create_spl_interface_pda_ix], &payer.pubkey(), &[&payer])
+/// Transfer fee mints need a restricted token pool in compression only mode,
+/// the compress and decompress instructions used for airdrops can't use it.
+#[tokio::test]
+async fn test_token_2022_transfer_fee_mint_unsupported() {
+    let config = ProgramTestConfig::new_v2(
+        true,
+        Some(vec![(
+            "light_compressed_claim",
+            light_compressed_claim::id(),
+        )]),
+    );
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = Keypair::new();
+    rpc.airdrop_lamports(&payer.pubkey(), 10_000_000_000)
         .await
         .unwrap();
+    let queue = rpc.test_accounts.v2_state_trees[0].output_queue;
+
+    let (mint, token_account, owner) = setup_transfer_fee_token_account(&mut rpc).await;
+    let create_spl_interface_pda_ix =
+        CreateSplInterfacePda::new(payer.pubkey(), mint.pubkey(), spl_token_2022::ID, false)
+            .instruction();
+    let result = rpc
+        .create_and_send_transaction(&[create_spl_interface_pda_ix], &payer.pubkey(), &[&payer])
+        .await;
+    assert!(result.is_err());
+
+    let (claimant_pda, _) = find_claimant_pda(Pubkey::new_unique(), mint.pubkey(), 1_000);
+    let compress_ix = compress(
+        payer.pubkey(),
+        owner.pubkey(),
+        token_account.pubkey(),
+        mint.pubkey(),
+        2,
+        claimant_pda,
+        queue,
+        spl_token_2022::ID,
+    )
+    .unwrap();
+    let result = rpc
+        .create_and_send_transaction(&[compress_ix], &payer.pubkey(), &[&payer, &owner])
+        .await;
+    assert!(result.is_err());
+
+    // The tokens stay in the token account.
+    let account_info = rpc.get_account(token_account.pubkey()).await.unwrap();
+    let account_data = StateWithExtensions::<Account>::unpack(&account_info.unwrap().data)
+        .unwrap()
+        .base;
+    assert_eq!(account_data.amount, 10);
 }
 
-/// Creates a new SPL mint and a token account, and funds it with tokens.
-///
-/// Returns (mint_account, token_account, owner)
-pub async fn setup_spl_token_account(rpc: &mut LightProgramTest) -> (Keypair, Keypair, Keypair) {
+#[tokio::test]
+async fn test_invalid_claim_accounts() {
+    let config = ProgramTestConfig::new_v2(
+        true,
+        Some(vec![(
+            "light_compressed_claim",
+            light_compressed_claim::id(),
+        )]),
+    );
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = Keypair::new();
+    rpc.airdrop_lamports(&payer.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+    let state_tree = rpc.test_accounts.v2_state_trees[0].merkle_tree;
+    let queue = rpc.test_accounts.v2_state_trees[0].output_queue;
+
+    let (mint, token_account, owner) = setup_spl_token_account(&mut rpc).await;
+    setup_token_pool(&mut rpc, &mint, &payer).await;
+    let claimant = Keypair::new();
+    let unlock_slot = 1_000;
+    let amount = 2;
+
+    let (claimant_pda, bump_seed) =
+        find_claimant_pda(claimant.pubkey(), mint.pubkey(), unlock_slot);
+    let compress_ix = compress(
+        payer.pubkey(),
+        owner.pubkey(),
+        token_account.pubkey(),
+        mint.pubkey(),
+        amount,
+        claimant_pda,
+        queue,
+        spl_token::ID,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(&[compress_ix], &payer.pubkey(), &[&payer, &owner])
+        .await
+        .unwrap();
+
+    let options = Some(GetCompressedTokenAccountsByOwnerOrDelegateOptions {
+        mint: Some(mint.pubkey()),
+        cursor: None,
+        limit: None,
+    });
+    let compressed_token_account = rpc
+        .get_compressed_token_accounts_by_owner(&claimant_pda, options, None)
+        .await
+        .unwrap()
+        .value
+        .items[0]
+        .clone();
+    let proof = rpc
+        .indexer()
+        .unwrap()
+        .get_validity_proof(vec![compressed_token_account.account.hash], vec![], None)
+        .await
+        .unwrap();
+    let packed_tree_info = PackedStateTreeInfo {
+        root_index: proof.value.accounts[0]
+            .root_index
+            .root_index()
+            .unwrap_or_default(),
+        merkle_tree_pubkey_index: 0,
+        queue_pubkey_index: 1,
+        leaf_index: compressed_token_account.account.leaf_index,
+        prove_by_index: proof.value.accounts[0].root_index.proof_by_index(),
+    };
+    let accounts = claim_accounts(
+        claimant.pubkey(),
+        payer.pubkey(),
+        claimant_pda,
+        mint.pubkey(),
+        token_account.pubkey(),
+        state_tree,
+        queue,
+    );
+    let instruction = build_claim_and_decompress_instruction(
+        &accounts,
+        proof.value.proof.clone().into(),
+        packed_tree_info,
+        amount,
+        None,
+        mint.pubkey(),
+        unlock_slot,
+        bump_seed,
+        None,
+    );
+    rpc.warp_to_slot(1000).unwrap();
+
+    let custom_error = |error_code: u32| {
+        format!(
+            "TransactionError: Error processing Instruction 0: custom program error: {:#x}",
+            error_code
+        )
+    };
+    let missing_signature =
+        "TransactionError: Error processing Instruction 0: missing required signature for instruction"
+            .to_string();
+    // (account index, replacement account, expected error)
+    let invalid_accounts = [
+        (
+            0,
+            AccountMeta::new(claimant.pubkey(), false),
+            missing_signature.clone(),
+        ),
+        (
+            1,
+            AccountMeta::new(Pubkey::new_unique(), false),
+            missing_signature.clone(),
+        ),
+        (
+            3,
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            custom_error(0x9),
+        ),
+        (
+            4,
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            custom_error(0xa),
+        ),
+        (
+            5,
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            custom_error(0xb),
+        ),
+        (
+            6,
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            custom_error(0xc),
+        ),
+        (
+            7,
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            custom_error(0xd),
+        ),
+        (
+            8,
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            custom_error(0xe),
+        ),
+        (
+            9,
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            custom_error(0xf),
+        ),
+        (
+            10,
+            AccountMeta::new_readonly(accounts.token_pool_pda, false),
+            custom_error(0x12),
+        ),
+        (
+            11,
+            AccountMeta::new_readonly(accounts.decompress_destination, false),
+            custom_error(0x12),
+        ),
+        (
+            12,
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            custom_error(0x10),
+        ),
+        (
+            13,
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            custom_error(0x11),
+        ),
+        (
+            14,
+            AccountMeta::new_readonly(state_tree, false),
+            custom_error(0x12),
+        ),
+        (
+            15,
+            AccountMeta::new_readonly(queue, false),
+            custom_error(0x12),
+        ),
+    ];
+    for (index, account_meta, expected_error) in invalid_accounts {
+        let mut invalid_instruction = instruction.clone();
+        invalid_instruction.accounts[index] = account_meta;
+        // The claimant cannot sign if it is not a signer of the instruction.
+        let signers: Vec<&Keypair> = if index == 0 {
+            vec![&payer]
+        } else {
+            vec![&payer, &claimant]
+        };
+        let result = rpc
+            .create_and_send_transaction(&[invalid_instruction], &payer.pubkey(), &signers)
+            .await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            expected_error,
+            "account index {}",
+            index
+        );
+    }
+
+    // Missing accounts fail before any account is read.
+    let mut short_instruction = instruction.clone();
+    short_instruction.accounts.truncate(15);
+    let result = rpc
+        .create_and_send_transaction(&[short_instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: insufficient account keys for instruction"
+    );
+    // Claims without a claim record take exactly the claim accounts.
+    let mut long_instruction = instruction.clone();
+    long_instruction
+        .accounts
+        .push(AccountMeta::new_readonly(Pubkey::new_unique(), false));
+    let result = rpc
+        .create_and_send_transaction(&[long_instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: insufficient account keys for instruction"
+    );
+
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_claim_compressed() {
+    let config = ProgramTestConfig::new_v2(
+        true,
+        Some(vec![(
+            "light_compressed_claim",
+            light_compressed_claim::id(),
+        )]),
+    );
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = Keypair::new();
+    rpc.airdrop_lamports(&payer.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+    let state_tree = rpc.test_accounts.v2_state_trees[0].merkle_tree;
+    let queue = rpc.test_accounts.v2_state_trees[0].output_queue;
+
+    let (mint, token_account, owner) = setup_spl_token_account(&mut rpc).await;
+    setup_token_pool(&mut rpc, &mint, &payer).await;
+    let claimant = Keypair::new();
+    let unlock_slot = 1_000;
+    let amount = 2;
+
+    let (claimant_pda, bump_seed) =
+        find_claimant_pda(claimant.pubkey(), mint.pubkey(), unlock_slot);
+
+    let compress_ix = compress(
+        payer.pubkey(),
+        owner.pubkey(),
+        token_account.pubkey(),
+        mint.pubkey(),
+        amount,
+        claimant_pda,
+        queue,
+        spl_token::ID,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(&[compress_ix], &payer.pubkey(), &[&payer, &owner])
+        .await
+        .unwrap();
+
+    let options = Some(GetCompressedTokenAccountsByOwnerOrDelegateOptions {
+        mint: Some(mint.pubkey()),
+        cursor: None,
+        limit: None,
+    });
+    let compressed_token_account = rpc
+        .get_compressed_token_accounts_by_owner(&claimant_pda, options.clone(), None)
+        .await
+        .unwrap()
+        .value
+        .items[0]
+        .clone();
+    let proof = rpc
+        .get_validity_proof(vec![compressed_token_account.account.hash], vec![], None)
+        .await
+        .unwrap();
+    let packed_tree_info = PackedStateTreeInfo {
+        root_index: proof.value.accounts[0]
+            .root_index
+            .root_index()
+            .unwrap_or_default(),
+        merkle_tree_pubkey_index: 0,
+        queue_pubkey_index: 1,
+        leaf_index: compressed_token_account.account.leaf_index,
+        prove_by_index: proof.value.accounts[0].root_index.proof_by_index(),
+    };
+
+    let accounts = ClaimCompressedAccounts {
+        claimant: claimant.pubkey(),
+        fee_payer: payer.pubkey(),
+        associated_airdrop_pda: claimant_pda,
+        ctoken_cpi_authority_pda: Pubkey::from_str_const(
+            "GXtd2izAiMJPwMEjfgTRH3d7k9mjn4Jq3JrWFv9gySYy",
+        ),
+        light_system_program: LIGHT_SYSTEM_PROGRAM_ID,
+        registered_program_pda: Pubkey::from_str_const(
+            "35hkDgaAKwMCaxRz2ocSZ6NaUrtKkyNqU6c4RV3tYJRh",
+        ),
+        noop_program: NOOP_PROGRAM_ID,
+        account_compression_authority: Pubkey::find_program_address(
+            &[b"cpi_authority"],
+            &LIGHT_SYSTEM_PROGRAM_ID,
+        )
+        .0,
+        account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID.into(),
+        ctoken_program: Pubkey::from_str_const("cTokenmWW8bLPjZEBAUgYy3zKxQZW6VKi7bqNFEVv3m"),
+        system_program: solana_sdk::system_program::ID,
+        state_tree,
+        queue,
+    };
+    let instruction = build_claim_compressed_instruction(
+        &accounts,
+        proof.value.proof.clone().into(),
+        packed_tree_info,
+        amount,
+        None,
+        mint.pubkey(),
+        unlock_slot,
+        bump_seed,
+        None,
+    );
+
+    rpc.warp_to_slot(1000).unwrap();
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+
+    // The claimant owns the compressed tokens, the SPL token account is unchanged.
+    let claimant_token_accounts = rpc
+        .get_compressed_token_accounts_by_owner(&claimant.pubkey(), options.clone(), None)
+        .await
+        .unwrap()
+        .value
+        .items;
+    assert_eq!(claimant_token_accounts.len(), 1);
+    assert_eq!(claimant_token_accounts[0].token.amount, amount);
+    let pda_token_accounts = rpc
+        .get_compressed_token_accounts_by_owner(&claimant_pda, options, None)
+        .await
+        .unwrap()
+        .value
+        .items;
+    assert!(pda_token_accounts.is_empty());
+
+    let account_info = rpc.get_account(token_account.pubkey()).await.unwrap();
+    let account_data = Account::unpack(&account_info.unwrap().data).unwrap();
+    assert_eq!(account_data.amount, 10 - amount);
+}
+
+#[tokio::test]
+async fn test_claim_many() {
+    let config = ProgramTestConfig::new_v2(
+        true,
+        Some(vec![(
+            "light_compressed_claim",
+            light_compressed_claim::id(),
+        )]),
+    );
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = Keypair::new();
+    rpc.airdrop_lamports(&payer.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+    let state_tree = rpc.test_accounts.v2_state_trees[0].merkle_tree;
+    let queue = rpc.test_accounts.v2_state_trees[0].output_queue;
+    let claimant = Keypair::new();
+    let unlock_slot = 1_000;
+
+    // Airdrop 2 tokens of an SPL token mint and 3 tokens of a Token-2022 mint.
+    let mut claims = Vec::new();
+    let mut hashes = Vec::new();
+    for (token_program, amount) in [(spl_token::ID, 2), (spl_token_2022::ID, 3)] {
+        let (mint, token_account, owner) =
+            setup_token_account_with_program(&mut rpc, &token_program).await;
+        setup_token_pool_with_program(&mut rpc, &mint, &payer, &token_program).await;
+        let (claimant_pda, bump_seed) =
+            find_claimant_pda(claimant.pubkey(), mint.pubkey(), unlock_slot);
+        let compress_ix = compress(
+            payer.pubkey(),
+            owner.pubkey(),
+            token_account.pubkey(),
+            mint.pubkey(),
+            amount,
+            claimant_pda,
+            queue,
+            token_program,
+        )
+        .unwrap();
+        rpc.create_and_send_transaction(&[compress_ix], &payer.pubkey(), &[&payer, &owner])
+            .await
+            .unwrap();
+
+        let options = Some(GetCompressedTokenAccountsByOwnerOrDelegateOptions {
+            mint: Some(mint.pubkey()),
+            cursor: None,
+            limit: None,
+        });
+        let compressed_token_account = rpc
+            .get_compressed_token_accounts_by_owner(&claimant_pda, options, None)
+            .await
+            .unwrap()
+            .value
+            .items[0]
+            .clone();
+        hashes.push(compressed_token_account.account.hash);
+        claims.push(MintClaim {
+            mint: mint.pubkey(),
+            token_program,
+            decompress_destination: token_account.pubkey(),
+            associated_airdrop_pda: claimant_pda,
+            bump_seed,
+            unlock_slot,
+            clawback: None,
+            amount,
+            state_tree,
+            queue,
+            leaf_index: compressed_token_account.account.leaf_index,
+            root_index: 0,
+            prove_by_index: false,
+        });
+    }
+
+    // One validity proof for the compressed token accounts of both mints.
+    let proof = rpc
+        .indexer()
+        .unwrap()
+        .get_validity_proof(hashes, vec![], None)
+        .await
+        .unwrap();
+    for (claim, account) in claims.iter_mut().zip(proof.value.accounts.iter()) {
+        claim.root_index = account.root_index.root_index().unwrap_or_default();
+        claim.prove_by_index = account.root_index.proof_by_index();
+    }
+
+    let accounts = ClaimManyAccounts {
+        claimant: claimant.pubkey(),
+        fee_payer: payer.pubkey(),
+        ctoken_cpi_authority_pda: Pubkey::from_str_const(
+            "GXtd2izAiMJPwMEjfgTRH3d7k9mjn4Jq3JrWFv9gySYy",
+        ),
+        light_system_program: LIGHT_SYSTEM_PROGRAM_ID,
+        registered_program_pda: Pubkey::from_str_const(
+            "35hkDgaAKwMCaxRz2ocSZ6NaUrtKkyNqU6c4RV3tYJRh",
+        ),
+        account_compression_authority: Pubkey::find_program_address(
+            &[b"cpi_authority"],
+            &LIGHT_SYSTEM_PROGRAM_ID,
+        )
+        .0,
+        account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID.into(),
+        ctoken_program: Pubkey::from_str_const("cTokenmWW8bLPjZEBAUgYy3zKxQZW6VKi7bqNFEVv3m"),
+        system_program: solana_sdk::system_program::ID,
+        output_queue: queue,
+    };
+    let instruction =
+        build_claim_many_instruction(&accounts, proof.value.proof.clone().into(), &claims);
+
+    // not yet unlocked.
+    rpc.warp_to_slot(999).unwrap();
+    let result = rpc
+        .create_and_send_transaction(
+            &[instruction.clone()],
+            &payer.pubkey(),
+            &[&payer, &claimant],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x1"
+    );
+
+    // now unlocked, both mints are claimed in one instruction.
+    rpc.warp_to_slot(1000).unwrap();
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+
+    for claim in claims.iter() {
+        let account_info = rpc.get_account(claim.decompress_destination).await.unwrap();
+        let account_data = Account::unpack(&account_info.unwrap().data).unwrap();
+        assert_eq!(account_data.amount, 10);
+        let remaining = rpc
+            .get_compressed_token_accounts_by_owner(&claim.associated_airdrop_pda, None, None)
+            .await
+            .unwrap()
+            .value
+            .items;
+        assert!(remaining.iter().all(|account| account.token.amount == 0));
+    }
+}
+
+#[tokio::test]
+async fn test_relayed_claim() {
+    let config = ProgramTestConfig::new_v2(
+        true,
+        Some(vec![(
+            "light_compressed_claim",
+            light_compressed_claim::id(),
+        )]),
+    );
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    // The relayer pays the fees, the claimant holds no SOL.
+    let relayer = Keypair::new();
+    rpc.airdrop_lamports(&relayer.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+    let state_tree = rpc.test_accounts.v2_state_trees[0].merkle_tree;
+    let queue = rpc.test_accounts.v2_state_trees[0].output_queue;
+
+    let (mint, token_account, owner) = setup_spl_token_account(&mut rpc).await;
+    setup_token_pool(&mut rpc, &mint, &relayer).await;
+    let claimant = Keypair::new();
+    let unlock_slot = 1_000;
+    let amount = 5;
+    let relayer_fee = 1;
+
+    let (claimant_pda, bump_seed) =
+        find_claimant_pda(claimant.pubkey(), mint.pubkey(), unlock_slot);
+    let compress_ix = compress(
+        relayer.pubkey(),
+        owner.pubkey(),
+        token_account.pubkey(),
+        mint.pubkey(),
+        amount,
+        claimant_pda,
+        queue,
+        spl_token::ID,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(&[compress_ix], &relayer.pubkey(), &[&relayer, &owner])
+        .await
+        .unwrap();
+
+    let options = Some(GetCompressedTokenAccountsByOwnerOrDelegateOptions {
+        mint: Some(mint.pubkey()),
+        cursor: None,
+        limit: None,
+    });
+    let compressed_token_account = rpc
+        .get_compressed_token_accounts_by_owner(&claimant_pda, options.clone(), None)
+        .await
+        .unwrap()
+        .value
+        .items[0]
+        .clone();
+    let proof = rpc
+        .indexer()
+        .unwrap()
+        .get_validity_proof(vec![compressed_token_account.account.hash], vec![], None)
+        .await
+        .unwrap();
+    let packed_tree_info = PackedStateTreeInfo {
+        root_index: proof.value.accounts[0]
+            .root_index
+            .root_index()
+            .unwrap_or_default(),
+        merkle_tree_pubkey_index: 0,
+        queue_pubkey_index: 1,
+        leaf_index: compressed_token_account.account.leaf_index,
+        prove_by_index: proof.value.accounts[0].root_index.proof_by_index(),
+    };
+    let accounts = claim_accounts(
+        claimant.pubkey(),
+        relayer.pubkey(),
+        claimant_pda,
+        mint.pubkey(),
+        token_account.pubkey(),
+        state_tree,
+        queue,
+    );
+    let valid_until_slot = 1_100;
+    let relayed_claim_ix = |relayer_fee: u64| {
+        build_relayed_claim_instruction(
+            &accounts,
+            RelayedClaimIxData {
+                claim: ClaimIxData {
+                    proof: proof.value.proof.clone().into(),
+                    packed_tree_info,
+                    amount,
+                    lamports: None,
+                    mint: mint.pubkey(),
+                    unlock_slot,
+                    bump_seed,
+                    vesting: None,
+                    clawback: None,
+                },
+                relayer_fee,
+                valid_until_slot,
+            },
+            vec![],
+        )
+    };
+
+    // The claimant signs the claim to the token account off-chain.
+    let message = RelayedClaimMessage {
+        associated_airdrop_pda: claimant_pda,
+        decompress_destination: token_account.pubkey(),
+        relayer_fee,
+        valid_until_slot,
+        state_tree,
+        leaf_index: compressed_token_account.account.leaf_index,
+        amount,
+    }
+    .to_bytes();
+    let signature = claimant.sign_message(&message);
+    let ed25519_ix = new_ed25519_instruction_with_signature(
+        &message,
+        signature.as_ref().try_into().unwrap(),
+        &claimant.pubkey().to_bytes(),
+    );
+    rpc.warp_to_slot(1000).unwrap();
+
+    // without the claimant's signature.
+    let result = rpc
+        .create_and_send_transaction(
+            &[relayed_claim_ix(relayer_fee)],
+            &relayer.pubkey(),
+            &[&relayer],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x14"
+    );
+
+    // the relayer cannot raise the fee.
+    let result = rpc
+        .create_and_send_transaction(
+            &[ed25519_ix.clone(), relayed_claim_ix(relayer_fee + 1)],
+            &relayer.pubkey(),
+            &[&relayer],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 1: custom program error: 0x14"
+    );
+
+    rpc.create_and_send_transaction(
+        &[ed25519_ix.clone(), relayed_claim_ix(relayer_fee)],
+        &relayer.pubkey(),
+        &[&relayer],
+    )
+    .await
+    .unwrap();
+
+    // The token account receives the claim minus the relayer fee.
+    let account_info = rpc.get_account(token_account.pubkey()).await.unwrap();
+    let account_data = Account::unpack(&account_info.unwrap().data).unwrap();
+    assert_eq!(account_data.amount, 10 - relayer_fee);
+    // The relayer receives the fee as compressed tokens.
+    let relayer_token_accounts = rpc
+        .get_compressed_token_accounts_by_owner(&relayer.pubkey(), options.clone(), None)
+        .await
+        .unwrap()
+        .value
+        .items;
+    assert_eq!(relayer_token_accounts.len(), 1);
+    assert_eq!(relayer_token_accounts[0].token.amount, relayer_fee);
+
+    // the signature cannot be replayed for tokens sent to the airdrop PDA later.
+    let compress_ix = compress(
+        relayer.pubkey(),
+        owner.pubkey(),
+        token_account.pubkey(),
+        mint.pubkey(),
+        amount,
+        claimant_pda,
+        queue,
+        spl_token::ID,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(&[compress_ix], &relayer.pubkey(), &[&relayer, &owner])
+        .await
+        .unwrap();
+    let compressed_token_account = rpc
+        .get_compressed_token_accounts_by_owner(&claimant_pda, options, None)
+        .await
+        .unwrap()
+        .value
+        .items[0]
+        .clone();
+    let proof = rpc
+        .get_validity_proof(vec![compressed_token_account.account.hash], vec![], None)
+        .await
+        .unwrap();
+    let replayed_claim_ix = build_relayed_claim_instruction(
+        &accounts,
+        RelayedClaimIxData {
+            claim: ClaimIxData {
+                proof: proof.value.proof,
+                packed_tree_info: PackedStateTreeInfo {
+                    root_index: proof.value.accounts[0]
+                        .root_index
+                        .root_index()
+                        .unwrap_or_default(),
+                    merkle_tree_pubkey_index: 0,
+                    queue_pubkey_index: 1,
+                    leaf_index: compressed_token_account.account.leaf_index,
+                    prove_by_index: proof.value.accounts[0].root_index.proof_by_index(),
+                },
+                amount,
+                lamports: None,
+                mint: mint.pubkey(),
+                unlock_slot,
+                bump_seed,
+                vesting: None,
+                clawback: None,
+            },
+            relayer_fee,
+            valid_until_slot,
+        },
+        vec![],
+    );
+    let result = rpc
+        .create_and_send_transaction(
+            &[ed25519_ix, replayed_claim_ix],
+            &relayer.pubkey(),
+            &[&relayer],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 1: custom program error: 0x14"
+    );
+}
+
+#[tokio::test]
+async fn test_vested_claim() {
+    let config = ProgramTestConfig::new_v2(
+        true,
+        Some(vec![(
+            "light_compressed_claim",
+            light_compressed_claim::id(),
+        )]),
+    );
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = Keypair::new();
+    rpc.airdrop_lamports(&payer.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+    let state_tree = rpc.test_accounts.v2_state_trees[0].merkle_tree;
+    let queue = rpc.test_accounts.v2_state_trees[0].output_queue;
+
+    let (mint, token_account, owner) = setup_spl_token_account(&mut rpc).await;
+    setup_token_pool(&mut rpc, &mint, &payer).await;
+    let claimant = Keypair::new();
+    let unlock_slot = 1_000;
+    let schedule = VestingSchedule {
+        start_slot: 1_000,
+        end_slot: 1_004,
+    };
+    let amount = 8;
+
+    let (claimant_pda, bump_seed) = find_associated_airdrop_pda(
+        &claimant.pubkey(),
+        &mint.pubkey(),
+        unlock_slot,
+        Some(&schedule),
+        None,
+    );
+
+    let compress_ix = compress(
+        payer.pubkey(),
+        owner.pubkey(),
+        token_account.pubkey(),
+        mint.pubkey(),
+        amount,
+        claimant_pda,
+        queue,
+        spl_token::ID,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(&[compress_ix], &payer.pubkey(), &[&payer, &owner])
+        .await
+        .unwrap();
+
+    let accounts = claim_accounts(
+        claimant.pubkey(),
+        payer.pubkey(),
+        claimant_pda,
+        mint.pubkey(),
+        token_account.pubkey(),
+        state_tree,
+        queue,
+    );
+    let airdrop = VestedAirdrop {
+        mint: mint.pubkey(),
+        unlock_slot,
+        bump_seed,
+        schedule,
+    };
+
+    // cliff not yet reached.
+    rpc.warp_to_slot(999).unwrap();
+    let result = vested_claim(&mut rpc, &payer, &claimant, &accounts, &airdrop).await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x1"
+    );
+
+    // half vested, creates the claim record.
+    rpc.warp_to_slot(1002).unwrap();
+    vested_claim(&mut rpc, &payer, &claimant, &accounts, &airdrop)
+        .await
+        .unwrap();
+    let account_info = rpc.get_account(token_account.pubkey()).await.unwrap();
+    let account_data = Account::unpack(&account_info.unwrap().data).unwrap();
+    assert_eq!(account_data.amount, 10 - amount + 4);
+
+    // nothing new vested in the same slot.
+    let result = vested_claim(&mut rpc, &payer, &claimant, &accounts, &airdrop).await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x4"
+    );
+
+    // fully vested, updates the claim record.
+    rpc.warp_to_slot(1004).unwrap();
+    vested_claim(&mut rpc, &payer, &claimant, &accounts, &airdrop)
+        .await
+        .unwrap();
+    let account_info = rpc.get_account(token_account.pubkey()).await.unwrap();
+    let account_data = Account::unpack(&account_info.unwrap().data).unwrap();
+    assert_eq!(account_data.amount, 10);
+
+    // a second airdrop of the claimant cannot use the claim record of the first one.
+    let other_schedule = VestingSchedule {
+        start_slot: 1_000,
+        end_slot: 1_008,
+    };
+    let (other_pda, other_bump_seed) = find_associated_airdrop_pda(
+        &claimant.pubkey(),
+        &mint.pubkey(),
+        unlock_slot,
+        Some(&other_schedule),
+        None,
+    );
+    let compress_ix = compress(
+        payer.pubkey(),
+        owner.pubkey(),
+        token_account.pubkey(),
+        mint.pubkey(),
+        amount,
+        other_pda,
+        queue,
+        spl_token::ID,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(&[compress_ix], &payer.pubkey(), &[&payer, &owner])
+        .await
+        .unwrap();
+    let other_accounts = claim_accounts(
+        claimant.pubkey(),
+        payer.pubkey(),
+        other_pda,
+        mint.pubkey(),
+        token_account.pubkey(),
+        state_tree,
+        queue,
+    );
+    let other_airdrop = VestedAirdrop {
+        mint: mint.pubkey(),
+        unlock_slot,
+        bump_seed: other_bump_seed,
+        schedule: other_schedule,
+    };
+    let instruction = build_vested_claim(&mut rpc, &other_accounts, &other_airdrop, &claimant_pda)
+        .await
+        .unwrap();
+    let result = rpc
+        .create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x5"
+    );
+}
+
+#[tokio::test]
+async fn test_clawback() {
+    let config = ProgramTestConfig::new_v2(
+        true,
+        Some(vec![(
+            "light_compressed_claim",
+            light_compressed_claim::id(),
+        )]),
+    );
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = Keypair::new();
+    rpc.airdrop_lamports(&payer.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+    let state_tree = rpc.test_accounts.v2_state_trees[0].merkle_tree;
+    let queue = rpc.test_accounts.v2_state_trees[0].output_queue;
+
+    let (mint, token_account, owner) = setup_spl_token_account(&mut rpc).await;
+    setup_token_pool(&mut rpc, &mint, &payer).await;
+    let claimant = Keypair::new();
+    let authority = Keypair::new();
+    let unlock_slot = 1_000;
+    let clawback = ClawbackConfig {
+        authority: authority.pubkey(),
+        expiry_slot: 2_000,
+    };
+    let amount = 2;
+
+    let (claimant_pda, bump_seed) = find_associated_airdrop_pda(
+        &claimant.pubkey(),
+        &mint.pubkey(),
+        unlock_slot,
+        None,
+        Some(&clawback),
+    );
+
+    let compress_ix = compress(
+        payer.pubkey(),
+        owner.pubkey(),
+        token_account.pubkey(),
+        mint.pubkey(),
+        amount,
+        claimant_pda,
+        queue,
+        spl_token::ID,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(&[compress_ix], &payer.pubkey(), &[&payer, &owner])
+        .await
+        .unwrap();
+
+    let options = Some(GetCompressedTokenAccountsByOwnerOrDelegateOptions {
+        mint: Some(mint.pubkey()),
+        cursor: None,
+        limit: None,
+    });
+    let compressed_token_account = rpc
+        .get_compressed_token_accounts_by_owner(&claimant_pda, options, None)
+        .await
+        .unwrap()
+        .value
+        .items[0]
+        .clone();
+    let proof = rpc
+        .get_validity_proof(vec![compressed_token_account.account.hash], vec![], None)
+        .await
+        .unwrap();
+    let packed_tree_info = PackedStateTreeInfo {
+        root_index: proof.value.accounts[0]
+            .root_index
+            .root_index()
+            .unwrap_or_default(),
+        merkle_tree_pubkey_index: 0,
+        queue_pubkey_index: 1,
+        leaf_index: compressed_token_account.account.leaf_index,
+        prove_by_index: proof.value.accounts[0].root_index.proof_by_index(),
+    };
+
+    // The treasury is the token account the tokens were compressed from.
+    let accounts = claim_accounts(
+        authority.pubkey(),
+        payer.pubkey(),
+        claimant_pda,
+        mint.pubkey(),
+        token_account.pubkey(),
+        state_tree,
+        queue,
+    );
+    let clawback_ix = build_clawback_instruction(
+        &accounts,
+        proof.value.proof.clone().into(),
+        packed_tree_info,
+        amount,
+        None,
+        mint.pubkey(),
+        claimant.pubkey(),
+        unlock_slot,
+        None,
+        clawback.expiry_slot,
+        bump_seed,
+    );
+
+    // not yet expired.
+    rpc.warp_to_slot(1999).unwrap();
+    let result = rpc
+        .create_and_send_transaction(
+            &[clawback_ix.clone()],
+            &payer.pubkey(),
+            &[&payer, &authority],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x7"
+    );
+
+    // expired, the claimant can no longer claim.
+    rpc.warp_to_slot(2000).unwrap();
+    let claimant_accounts = claim_accounts(
+        claimant.pubkey(),
+        payer.pubkey(),
+        claimant_pda,
+        mint.pubkey(),
+        token_account.pubkey(),
+        state_tree,
+        queue,
+    );
+    let claim_ix = build_claim_and_decompress_instruction(
+        &claimant_accounts,
+        proof.value.proof.clone().into(),
+        packed_tree_info,
+        amount,
+        None,
+        mint.pubkey(),
+        unlock_slot,
+        bump_seed,
+        Some(clawback),
+    );
+    let result = rpc
+        .create_and_send_transaction(&[claim_ix], &payer.pubkey(), &[&payer, &claimant])
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x6"
+    );
+
+    // only the recorded authority can claw back.
+    let other_authority = Keypair::new();
+    let mut invalid_clawback_ix = clawback_ix.clone();
+    invalid_clawback_ix.accounts[0].pubkey = other_authority.pubkey();
+    let result = rpc
+        .create_and_send_transaction(
+            &[invalid_clawback_ix],
+            &payer.pubkey(),
+            &[&payer, &other_authority],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x2"
+    );
+
+    rpc.create_and_send_transaction(&[clawback_ix], &payer.pubkey(), &[&payer, &authority])
+        .await
+        .unwrap();
+
+    let account_info = rpc.get_account(token_account.pubkey()).await.unwrap();
+    let account_data = Account::unpack(&account_info.unwrap().data).unwrap();
+    assert_eq!(account_data.amount, 10);
+}
+
+#[tokio::test]
+async fn test_distributor_claim() {
+    let config = ProgramTestConfig::new_v2(
+        true,
+        Some(vec![(
+            "light_compressed_claim",
+            light_compressed_claim::id(),
+        )]),
+    );
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = Keypair::new();
+    rpc.airdrop_lamports(&payer.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+    let state_tree = rpc.test_accounts.v2_state_trees[0].merkle_tree;
+    let queue = rpc.test_accounts.v2_state_trees[0].output_queue;
+
+    let (mint, token_account, owner) = setup_spl_token_account(&mut rpc).await;
+    setup_token_pool(&mut rpc, &mint, &payer).await;
+    let claimant = Keypair::new();
+    // The claimant holds two leaves.
+    let tree = DistributorTree::new(vec![
+        DistributorLeaf {
+            index: 0,
+            claimant: Pubkey::new_unique(),
+            amount: 1,
+            unlock_slot: 0,
+        },
+        DistributorLeaf {
+            index: 1,
+            claimant: claimant.pubkey(),
+            amount: 2,
+            unlock_slot: 1_000,
+        },
+        DistributorLeaf {
+            index: 2,
+            claimant: Pubkey::new_unique(),
+            amount: 3,
+            unlock_slot: 0,
+        },
+        DistributorLeaf {
+            index: 3,
+            claimant: claimant.pubkey(),
+            amount: 4,
+            unlock_slot: 0,
+        },
+    ]);
+    let total = tree.leaves().iter().map(|leaf| leaf.amount).sum();
+    let (distributor_pda, bump_seed) = find_distributor_pda(&mint.pubkey(), &tree.root());
+
+    // The root is posted by compressing the total to two compressed token accounts
+    // of the distributor PDA.
+    let compress_ix = batch_compress(
+        payer.pubkey(),
+        owner.pubkey(),
+        token_account.pubkey(),
+        mint.pubkey(),
+        distributor_funding(&distributor_pda, total, 2),
+        queue,
+        spl_token::ID,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(&[compress_ix], &payer.pubkey(), &[&payer, &owner])
+        .await
+        .unwrap();
+
+    let accounts = claim_accounts(
+        claimant.pubkey(),
+        payer.pubkey(),
+        distributor_pda,
+        mint.pubkey(),
+        token_account.pubkey(),
+        state_tree,
+        queue,
+    );
+    let distributor = Distributor {
+        mint: mint.pubkey(),
+        tree,
+        bump_seed,
+    };
+    let leaf = distributor.tree.leaves()[1];
+
+    // not yet unlocked.
+    rpc.warp_to_slot(999).unwrap();
+    let result =
+        distributor_claim(&mut rpc, &payer, &claimant, &accounts, &distributor, leaf).await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x1"
+    );
+
+    // leaf is not in the tree.
+    rpc.warp_to_slot(1000).unwrap();
+    let invalid_leaf = DistributorLeaf { amount: 3, ..leaf };
+    let result = distributor_claim(
+        &mut rpc,
+        &payer,
+        &claimant,
+        &accounts,
+        &distributor,
+        invalid_leaf,
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x8"
+    );
+
+    // leaf is claimed under the index of another leaf of the claimant.
+    let invalid_leaf = DistributorLeaf { index: 3, ..leaf };
+    let result = distributor_claim(
+        &mut rpc,
+        &payer,
+        &claimant,
+        &accounts,
+        &distributor,
+        invalid_leaf,
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x8"
+    );
+
+    // both leaves of the claimant are claimable.
+    let second_leaf = distributor.tree.leaves()[3];
+    for leaf in [leaf, second_leaf] {
+        distributor_claim(&mut rpc, &payer, &claimant, &accounts, &distributor, leaf)
+            .await
+            .unwrap();
+    }
+    let account_info = rpc.get_account(token_account.pubkey()).await.unwrap();
+    let account_data = Account::unpack(&account_info.unwrap().data).unwrap();
+    assert_eq!(
+        account_data.amount,
+        10 - total + leaf.amount + second_leaf.amount
+    );
+
+    // the claim receipt already exists.
+    let result =
+        distributor_claim(&mut rpc, &payer, &claimant, &accounts, &distributor, leaf).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_airdrop_planner() {
+    let config = ProgramTestConfig::new_v2(
+        true,
+        Some(vec![(
+            "light_compressed_claim",
+            light_compressed_claim::id(),
+        )]),
+    );
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = Keypair::new();
+    rpc.airdrop_lamports(&payer.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+    let state_tree = rpc.test_accounts.v2_state_trees[0].merkle_tree;
+    let queue = rpc.test_accounts.v2_state_trees[0].output_queue;
+
+    let (mint, token_account, owner) = setup_spl_token_account(&mut rpc).await;
+    setup_token_pool(&mut rpc, &mint, &payer).await;
+    let mint_to_ix = instruction::mint_to(
+        &spl_token::ID,
+        &mint.pubkey(),
+        &token_account.pubkey(),
+        &owner.pubkey(),
+        &[],
+        30,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(&[mint_to_ix], &payer.pubkey(), &[&payer, &owner])
+        .await
+        .unwrap();
+
+    // 40 recipients do not fit into a single transaction.
+    let claimant = Keypair::new();
+    let mut csv = String::from("claimant,amount,unlock_slot\n");
+    csv.push_str(&format!("{},1,1000\n", claimant.pubkey()));
+    for i in 1..40 {
+        csv.push_str(&format!("{},1,{}\n", Pubkey::new_unique(), i % 3));
+    }
+    let entries = AirdropEntry::from_csv(csv.as_bytes()).unwrap();
+    assert_eq!(entries.len(), 40);
+
+    let planner = AirdropPlanner {
+        fee_payer: payer.pubkey(),
+        authority: owner.pubkey(),
+        sender_token_account: token_account.pubkey(),
+        mint: mint.pubkey(),
+        merkle_tree: queue,
+        token_program_id: spl_token::ID,
+        max_transaction_size: MAX_TRANSACTION_SIZE,
+    };
+    let plan = planner.plan(&entries).unwrap();
+    assert!(plan.instructions.len() > 1);
+    for instruction in plan.instructions {
+        assert!(planner.transaction_size(&instruction) <= MAX_TRANSACTION_SIZE);
+        rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &owner])
+            .await
+            .unwrap();
+    }
+
+    let mut manifest_json = Vec::new();
+    plan.manifest.write_json(&mut manifest_json).unwrap();
+    let manifest = AirdropManifest::from_json(manifest_json.as_slice()).unwrap();
+    assert_eq!(manifest, plan.manifest);
+
+    for entry in manifest.entries.iter() {
+        let (airdrop_pda, bump_seed) =
+            find_claimant_pda(entry.claimant, mint.pubkey(), entry.unlock_slot);
+        assert_eq!(entry.airdrop_pda, airdrop_pda);
+        assert_eq!(entry.bump_seed, bump_seed);
+
+        let options = Some(GetCompressedTokenAccountsByOwnerOrDelegateOptions {
+            mint: Some(mint.pubkey()),
+            cursor: None,
+            limit: None,
+        });
+        let compressed_token_accounts = rpc
+            .get_compressed_token_accounts_by_owner(&entry.airdrop_pda, options, None)
+            .await
+            .unwrap()
+            .value
+            .items;
+        assert_eq!(compressed_token_accounts.len(), 1);
+        assert_eq!(compressed_token_accounts[0].token.amount, entry.amount);
+    }
+
+    // claim with the PDA and bump of the manifest.
+    let entry = manifest.entries[0];
+    let options = Some(GetCompressedTokenAccountsByOwnerOrDelegateOptions {
+        mint: Some(mint.pubkey()),
+        cursor: None,
+        limit: None,
+    });
+    let compressed_token_account = rpc
+        .get_compressed_token_accounts_by_owner(&entry.airdrop_pda, options, None)
+        .await
+        .unwrap()
+        .value
+        .items[0]
+        .clone();
+    let proof = rpc
+        .get_validity_proof(vec![compressed_token_account.account.hash], vec![], None)
+        .await
+        .unwrap();
+    let packed_tree_info = PackedStateTreeInfo {
+        root_index: proof.value.accounts[0]
+            .root_index
+            .root_index()
+            .unwrap_or_default(),
+        merkle_tree_pubkey_index: 0,
+        queue_pubkey_index: 1,
+        leaf_index: compressed_token_account.account.leaf_index,
+        prove_by_index: proof.value.accounts[0].root_index.proof_by_index(),
+    };
+    let accounts = claim_accounts(
+        claimant.pubkey(),
+        payer.pubkey(),
+        entry.airdrop_pda,
+        mint.pubkey(),
+        token_account.pubkey(),
+        state_tree,
+        queue,
+    );
+    let instruction = build_claim_and_decompress_instruction(
+        &accounts,
+        proof.value.proof.clone().into(),
+        packed_tree_info,
+        entry.amount,
+        None,
+        mint.pubkey(),
+        entry.unlock_slot,
+        entry.bump_seed,
+        None,
+    );
+    rpc.warp_to_slot(entry.unlock_slot).unwrap();
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+
+    let account_info = rpc.get_account(token_account.pubkey()).await.unwrap();
+    let account_data = Account::unpack(&account_info.unwrap().data).unwrap();
+    // all 40 tokens were compressed before.
+    assert_eq!(account_data.amount, entry.amount);
+}
+
+#[tokio::test]
+async fn test_airdrop_status() {
+    let config = ProgramTestConfig::new_v2(
+        true,
+        Some(vec![(
+            "light_compressed_claim",
+            light_compressed_claim::id(),
+        )]),
+    );
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = Keypair::new();
+    rpc.airdrop_lamports(&payer.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+    let state_tree = rpc.test_accounts.v2_state_trees[0].merkle_tree;
+    let queue = rpc.test_accounts.v2_state_trees[0].output_queue;
+
+    let (mint, token_account, owner) = setup_spl_token_account(&mut rpc).await;
+    setup_token_pool(&mut rpc, &mint, &payer).await;
+    let claimant = Keypair::new();
+    let manifest = AirdropManifest::new(
+        mint.pubkey(),
+        &[
+            AirdropEntry {
+                claimant: claimant.pubkey(),
+                amount: 2,
+                unlock_slot: 1_000,
+            },
+            AirdropEntry {
+                claimant: claimant.pubkey(),
+                amount: 3,
+                unlock_slot: 2_000,
+            },
+        ],
+    );
+    // The claim record tells a claimed vested airdrop from one that is not funded.
+    let unlock_slot = 1_000;
+    let schedule = VestingSchedule {
+        start_slot: 1_000,
+        end_slot: 1_004,
+    };
+    let vested_amount = 4;
+    let (vested_pda, vested_bump_seed) = find_associated_airdrop_pda(
+        &claimant.pubkey(),
+        &mint.pubkey(),
+        unlock_slot,
+        Some(&schedule),
+        None,
+    );
+
+    let slot = rpc.get_slot().await.unwrap();
+    let status = get_airdrop_status(
+        &rpc,
+        &claimant.pubkey(),
+        &mint.pubkey(),
+        unlock_slot,
+        Some(&schedule),
+        None,
+        slot,
+    )
+    .await
+    .unwrap();
+    assert_eq!(status, AirdropStatus::NotFunded);
+    // Without claim record an unfunded non-vested airdrop looks like a claimed one.
+    let result = get_claimant_statuses(&rpc, &manifest, &claimant.pubkey(), slot).await;
+    assert!(matches!(
+        result,
+        Err(StatusError::UnknownClaimState(pda)) if pda == manifest.entries[0].airdrop_pda
+    ));
+
+    let recipients = manifest
+        .entries
+        .iter()
+        .map(|entry| (entry.airdrop_pda, entry.amount))
+        .chain([(vested_pda, vested_amount)]);
+    for (airdrop_pda, amount) in recipients {
+        let compress_ix = compress(
+            payer.pubkey(),
+            owner.pubkey(),
+            token_account.pubkey(),
+            mint.pubkey(),
+            amount,
+            airdrop_pda,
+            queue,
+            spl_token::ID,
+        )
+        .unwrap();
+        rpc.create_and_send_transaction(&[compress_ix], &payer.pubkey(), &[&payer, &owner])
+            .await
+            .unwrap();
+    }
+
+    let slot = rpc.get_slot().await.unwrap();
+    let statuses = get_claimant_statuses(&rpc, &manifest, &claimant.pubkey(), slot)
+        .await
+        .unwrap();
+    assert_eq!(
+        statuses[0].status,
+        AirdropStatus::Locked {
+            amount: 2,
+            unlock_slot: 1_000
+        }
+    );
+    assert_eq!(statuses[0].airdrop_pda, manifest.entries[0].airdrop_pda);
+    assert_eq!(
+        statuses[1].status,
+        AirdropStatus::Locked {
+            amount: 3,
+            unlock_slot: 2_000
+        }
+    );
+
+    rpc.warp_to_slot(1_004).unwrap();
+    let statuses = get_claimant_statuses(&rpc, &manifest, &claimant.pubkey(), 1_004)
+        .await
+        .unwrap();
+    assert_eq!(statuses[0].status, AirdropStatus::Claimable { amount: 2 });
+    assert_eq!(
+        statuses[1].status,
+        AirdropStatus::Locked {
+            amount: 3,
+            unlock_slot: 2_000
+        }
+    );
+    let status = get_airdrop_status(
+        &rpc,
+        &claimant.pubkey(),
+        &mint.pubkey(),
+        unlock_slot,
+        Some(&schedule),
+        None,
+        1_004,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        status,
+        AirdropStatus::Claimable {
+            amount: vested_amount
+        }
+    );
+    // Half of the vesting schedule has elapsed at slot 1_002.
+    let status = get_airdrop_status(
+        &rpc,
+        &claimant.pubkey(),
+        &mint.pubkey(),
+        unlock_slot,
+        Some(&schedule),
+        None,
+        1_002,
+    )
+    .await
+    .unwrap();
+    assert_eq!(status, AirdropStatus::Claimable { amount: 2 });
+
+    let accounts = claim_accounts(
+        claimant.pubkey(),
+        payer.pubkey(),
+        vested_pda,
+        mint.pubkey(),
+        token_account.pubkey(),
+        state_tree,
+        queue,
+    );
+    let airdrop = VestedAirdrop {
+        mint: mint.pubkey(),
+        unlock_slot,
+        bump_seed: vested_bump_seed,
+        schedule,
+    };
+    vested_claim(&mut rpc, &payer, &claimant, &accounts, &airdrop)
+        .await
+        .unwrap();
+    let status = get_airdrop_status(
+        &rpc,
+        &claimant.pubkey(),
+        &mint.pubkey(),
+        unlock_slot,
+        Some(&schedule),
+        None,
+        1_004,
+    )
+    .await
+    .unwrap();
+    assert_eq!(status, AirdropStatus::Claimed);
+
+    // A claimed non-vested airdrop leaves no claim record, its status is unknown as well.
+    let entry = &manifest.entries[0];
+    let options = Some(GetCompressedTokenAccountsByOwnerOrDelegateOptions {
+        mint: Some(mint.pubkey()),
+        cursor: None,
+        limit: None,
+    });
+    let compressed_token_account = rpc
+        .get_compressed_token_accounts_by_owner(&entry.airdrop_pda, options, None)
+        .await
+        .unwrap()
+        .value
+        .items[0]
+        .clone();
+    let proof = rpc
+        .get_validity_proof(vec![compressed_token_account.account.hash], vec![], None)
+        .await
+        .unwrap();
+    let accounts = claim_accounts(
+        claimant.pubkey(),
+        payer.pubkey(),
+        entry.airdrop_pda,
+        mint.pubkey(),
+        token_account.pubkey(),
+        state_tree,
+        queue,
+    );
+    let packed_merkle_context = PackedStateTreeInfo {
+        root_index: proof.value.accounts[0]
+            .root_index
+            .root_index()
+            .unwrap_or_default(),
+        merkle_tree_pubkey_index: 0,
+        queue_pubkey_index: 1,
+        leaf_index: compressed_token_account.account.leaf_index,
+        prove_by_index: proof.value.accounts[0].root_index.proof_by_index(),
+    };
+    let instruction = build_claim_and_decompress_instruction(
+        &accounts,
+        proof.value.proof,
+        packed_merkle_context,
+        entry.amount,
+        None,
+        mint.pubkey(),
+        entry.unlock_slot,
+        entry.bump_seed,
+        None,
+    );
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+    let result = get_airdrop_status(
+        &rpc,
+        &claimant.pubkey(),
+        &mint.pubkey(),
+        entry.unlock_slot,
+        None,
+        None,
+        1_004,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(StatusError::UnknownClaimState(pda)) if pda == entry.airdrop_pda
+    ));
+    let result = get_claimant_statuses(&rpc, &manifest, &claimant.pubkey(), 1_004).await;
+    assert!(matches!(
+        result,
+        Err(StatusError::UnknownClaimState(pda)) if pda == entry.airdrop_pda
+    ));
+
+    assert_eq!(
+        AirdropStatus::new(0, 0, false, unlock_slot, 1_004),
+        AirdropStatus::NotFunded
+    );
+    assert_eq!(
+        AirdropStatus::new(0, 0, true, unlock_slot, 1_004),
+        AirdropStatus::Claimed
+    );
+}
+
+struct Distributor {
+    mint: Pubkey,
+    tree: DistributorTree,
+    bump_seed: u8,
+}
+
+/// Claims `leaf` from the compressed token account of the distributor PDA
+/// selected by its leaf index.
+async fn distributor_claim(
+    rpc: &mut LightProgramTest,
+    payer: &Keypair,
+    claimant: &Keypair,
+    accounts: &ClaimAccounts,
+    distributor: &Distributor,
+    leaf: DistributorLeaf,
+) -> Result<Signature, RpcError> {
+    let options = Some(GetCompressedTokenAccountsByOwnerOrDelegateOptions {
+        mint: Some(distributor.mint),
+        cursor: None,
+        limit: None,
+    });
+    let compressed_token_accounts = rpc
+        .get_compressed_token_accounts_by_owner(&accounts.associated_airdrop_pda, options, None)
+        .await
+        .unwrap()
+        .value
+        .items;
+    let balances: Vec<u64> = compressed_token_accounts
+        .iter()
+        .map(|token_account| token_account.token.amount)
+        .collect();
+    let position = select_distributor_account(leaf.index, &balances, leaf.amount).unwrap();
+    let compressed_token_account = compressed_token_accounts[position].clone();
+    let proof = rpc
+        .get_validity_proof(vec![compressed_token_account.account.hash], vec![], None)
+        .await
+        .unwrap();
+    let packed_tree_info = PackedStateTreeInfo {
+        root_index: proof.value.accounts[0]
+            .root_index
+            .root_index()
+            .unwrap_or_default(),
+        merkle_tree_pubkey_index: 0,
+        queue_pubkey_index: 1,
+        leaf_index: compressed_token_account.account.leaf_index,
+        prove_by_index: proof.value.accounts[0].root_index.proof_by_index(),
+    };
+
+    let mut remaining_accounts = PackedAccounts::default();
+    remaining_accounts
+        .add_system_accounts_v2(SystemAccountMetaConfig::new(light_compressed_claim::id()))
+        .unwrap();
+    let address_tree = rpc.get_address_tree_v2().tree;
+    let address =
+        derive_claim_receipt_address(&accounts.associated_airdrop_pda, leaf.index, &address_tree);
+    let receipt_proof = rpc
+        .get_validity_proof(
+            vec![],
+            vec![AddressWithTree {
+                address,
+                tree: address_tree,
+            }],
+            None,
+        )
+        .await?
+        .value;
+    let output_state_tree_index = rpc
+        .get_random_state_tree_info()?
+        .pack_output_tree_index(&mut remaining_accounts)?;
+    let address_tree_info = receipt_proof
+        .pack_tree_infos(&mut remaining_accounts)
+        .address_trees[0];
+    let (light_system_accounts, _, _) = remaining_accounts.to_account_metas();
+
+    let instruction = build_distributor_claim_instruction(
+        accounts,
+        DistributorClaimIxData {
+            proof: proof.value.proof.clone().into(),
+            packed_tree_info,
+            balance: compressed_token_account.token.amount,
+            lamports: None,
+            mint: distributor.mint,
+            merkle_root: distributor.tree.root(),
+            bump_seed: distributor.bump_seed,
+            leaf_index: leaf.index,
+            amount: leaf.amount,
+            unlock_slot: leaf.unlock_slot,
+            merkle_proof: distributor.tree.proof(leaf.index as usize).unwrap(),
+            receipt_proof: receipt_proof.proof,
+            address_tree_info,
+            output_state_tree_index,
+        },
+        light_system_accounts,
+    );
+
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[payer, claimant])
+        .await
+}
+
+struct VestedAirdrop {
+    mint: Pubkey,
+    unlock_slot: u64,
+    bump_seed: u8,
+    schedule: VestingSchedule,
+}
+
+/// Claims the vested remainder of the compressed token account of the airdrop PDA.
+async fn vested_claim(
+    rpc: &mut LightProgramTest,
+    payer: &Keypair,
+    claimant: &Keypair,
+    accounts: &ClaimAccounts,
+    airdrop: &VestedAirdrop,
+) -> Result<Signature, RpcError> {
+    let instruction =
+        build_vested_claim(rpc, accounts, airdrop, &accounts.associated_airdrop_pda).await?;
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[payer, claimant])
+        .await
+}
+
+/// Builds a vested claim with the claim record of `claim_record_airdrop_pda`,
+/// which is the claimed airdrop PDA for valid claims.
+async fn build_vested_claim(
+    rpc: &mut LightProgramTest,
+    accounts: &ClaimAccounts,
+    airdrop: &VestedAirdrop,
+    claim_record_airdrop_pda: &Pubkey,
+) -> Result<Instruction, RpcError> {
+    let options = Some(GetCompressedTokenAccountsByOwnerOrDelegateOptions {
+        mint: Some(airdrop.mint),
+        cursor: None,
+        limit: None,
+    });
+    let compressed_token_account = rpc
+        .get_compressed_token_accounts_by_owner(&accounts.associated_airdrop_pda, options, None)
+        .await
+        .unwrap()
+        .value
+        .items[0]
+        .clone();
+    let proof = rpc
+        .get_validity_proof(vec![compressed_token_account.account.hash], vec![], None)
+        .await
+        .unwrap();
+    let packed_tree_info = PackedStateTreeInfo {
+        root_index: proof.value.accounts[0]
+            .root_index
+            .root_index()
+            .unwrap_or_default(),
+        merkle_tree_pubkey_index: 0,
+        queue_pubkey_index: 1,
+        leaf_index: compressed_token_account.account.leaf_index,
+        prove_by_index: proof.value.accounts[0].root_index.proof_by_index(),
+    };
+
+    let mut remaining_accounts = PackedAccounts::default();
+    remaining_accounts
+        .add_system_accounts_v2(SystemAccountMetaConfig::new(light_compressed_claim::id()))
+        .unwrap();
+
+    let address_tree = rpc.get_address_tree_v2().tree;
+    let address = derive_claim_record_address(claim_record_airdrop_pda, &address_tree);
+    let claim_record = rpc
+        .get_compressed_account(address, None)
+        .await
+        .ok()
+        .and_then(|response| response.value);
+
+    let (record_proof, record) = match claim_record {
+        None => {
+            let record_proof = rpc
+                .get_validity_proof(
+                    vec![],
+                    vec![AddressWithTree {
+                        address,
+                        tree: address_tree,
+                    }],
+                    None,
+                )
+                .await?
+                .value;
+            let output_state_tree_index = rpc
+                .get_random_state_tree_info()?
+                .pack_output_tree_index(&mut remaining_accounts)?;
+            let address_tree_info = record_proof
+                .pack_tree_infos(&mut remaining_accounts)
+                .address_trees[0];
+            (
+                record_proof.proof,
+                ClaimRecordIxData::Create {
+                    address_tree_info,
+                    output_state_tree_index,
+                },
+            )
+        }
+        Some(claim_record) => {
+            let record_proof = rpc
+                .get_validity_proof(vec![claim_record.hash], vec![], None)
+                .await?
+                .value;
+            let packed_state_trees = record_proof
+                .pack_tree_infos(&mut remaining_accounts)
+                .state_trees
+                .unwrap();
+            let withdrawn =
+                ClaimRecord::deserialize(&mut claim_record.data.unwrap().data.as_slice())
+                    .unwrap()
+                    .withdrawn;
+            (
+                record_proof.proof,
+                ClaimRecordIxData::Update {
+                    account_meta: CompressedAccountMeta {
+                        tree_info: packed_state_trees.packed_tree_infos[0],
+                        address,
+                        output_state_tree_index: packed_state_trees.output_tree_index,
+                    },
+                    withdrawn,
+                },
+            )
+        }
+    };
+    let (light_system_accounts, _, _) = remaining_accounts.to_account_metas();
+
+    Ok(build_vested_claim_instruction(
+        accounts,
+        proof.value.proof.clone().into(),
+        packed_tree_info,
+        compressed_token_account.token.amount,
+        None,
+        airdrop.mint,
+        airdrop.unlock_slot,
+        airdrop.bump_seed,
+        VestingIxData {
+            schedule: airdrop.schedule,
+            record_proof,
+            record,
+        },
+        None,
+        light_system_accounts,
+    ))
+}
+
+pub fn claim_accounts(
+    claimant: Pubkey,
+    fee_payer: Pubkey,
+    associated_airdrop_pda: Pubkey,
+    mint: Pubkey,
+    decompress_destination: Pubkey,
+    state_tree: Pubkey,
+    queue: Pubkey,
+) -> ClaimAccounts {
+    ClaimAccounts {
+        claimant,
+        fee_payer,
+        associated_airdrop_pda,
+        ctoken_cpi_authority_pda: Pubkey::from_str_const(
+            "GXtd2izAiMJPwMEjfgTRH3d7k9mjn4Jq3JrWFv9gySYy",
+        ),
+        light_system_program: LIGHT_SYSTEM_PROGRAM_ID,
+        registered_program_pda: Pubkey::from_str_const(
+            "35hkDgaAKwMCaxRz2ocSZ6NaUrtKkyNqU6c4RV3tYJRh",
+        ),
+        noop_program: NOOP_PROGRAM_ID,
+        account_compression_authority: Pubkey::find_program_address(
+            &[b"cpi_authority"],
+            &LIGHT_SYSTEM_PROGRAM_ID,
+        )
+        .0,
+        account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID.into(),
+        ctoken_program: Pubkey::from_str_const("cTokenmWW8bLPjZEBAUgYy3zKxQZW6VKi7bqNFEVv3m"),
+        token_pool_pda: get_spl_interface_pda(&mint, false),
+        decompress_destination,
+        token_program: spl_token::ID,
+        system_program: solana_sdk::system_program::ID,
+        state_tree,
+        queue,
+    }
+}
+
+pub fn find_claimant_pda(claimant: Pubkey, mint: Pubkey, slot: u64) -> (Pubkey, u8) {
+    let claimant_bytes = claimant.to_bytes();
+    let mint_bytes = mint.to_bytes();
+    let slot_bytes = slot.to_le_bytes();
+    let seeds = &[&claimant_bytes[..32], &mint_bytes[..32], &slot_bytes[..8]];
+
+    Pubkey::find_program_address(seeds, &light_compressed_claim::id())
+}
+
+pub async fn setup_token_pool(rpc: &mut LightProgramTest, mint: &Keypair, payer: &Keypair) {
+    setup_token_pool_with_program(rpc, mint, payer, &spl_token::ID).await
+}
+
+pub async fn setup_token_pool_with_program(
+    rpc: &mut LightProgramTest,
+    mint: &Keypair,
+    payer: &Keypair,
+    token_program: &Pubkey,
+) {
+    let create_spl_interface_pda_ix =
+        CreateSplInterfacePda::new(payer.pubkey(), mint.pubkey(), *token_program, false)
+            .instruction();
+    rpc.create_and_send_transaction(&[create_spl_interface_pda_ix], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+}
+
+/// Creates a new SPL mint and a token account, and funds it with tokens.
+///
+/// Returns (mint_account, token_account, owner)
+pub async fn setup_spl_token_account(rpc: &mut LightProgramTest) -> (Keypair, Keypair, Keypair) {
+    setup_token_account_with_program(rpc, &spl_token::ID).await
+}
+
+/// Creates a new Token-2022 mint with a 1% transfer fee and a token account,
+/// and funds it with tokens.
+///
+/// Returns (mint_account, token_account, owner)
+pub async fn setup_transfer_fee_token_account(
+    rpc: &mut LightProgramTest,
+) -> (Keypair, Keypair, Keypair) {
+    let payer = rpc.get_payer().insecure_clone();
+    let token_program = &spl_token_2022::ID;
+
+    let mint_account = Keypair::new();
+    let owner = payer.insecure_clone();
+    let mint_len =
+        ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])
+            .unwrap();
+    let mint_rent = rpc
+        .get_minimum_balance_for_rent_exemption(mint_len)
+        .await
+        .unwrap();
+    let create_mint_account_ix = solana_program::system_instruction::create_account(
+        &payer.pubkey(),
+        &mint_account.pubkey(),
+        mint_rent,
+        mint_len as u64,
+        token_program,
+    );
+    let initialize_transfer_fee_config_ix = initialize_transfer_fee_config(
+        token_program,
+        &mint_account.pubkey(),
+        Some(&owner.pubkey()),
+        Some(&owner.pubkey()),
+        100,
+        u64::MAX,
+    )
+    .unwrap();
+    let initialize_mint_ix = instruction::initialize_mint(
+        token_program,
+        &mint_account.pubkey(),
+        &owner.pubkey(),
+        None,
+        9,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(
+        &[
+            create_mint_account_ix,
+            initialize_transfer_fee_config_ix,
+            initialize_mint_ix,
+        ],
+        &payer.pubkey(),
+        &[&payer, &mint_account],
+    )
+    .await
+    .unwrap();
+
+    // Token accounts of transfer fee mints hold the withheld fees.
+    let account_len =
+        ExtensionType::try_calculate_account_len::<Account>(&[ExtensionType::TransferFeeAmount])
+            .unwrap();
+    let account_rent = rpc
+        .get_minimum_balance_for_rent_exemption(account_len)
+        .await
+        .unwrap();
+    let token_account = Keypair::new();
+    let create_token_account_ix = solana_sdk::system_instruction::create_account(
+        &payer.pubkey(),
+        &token_account.pubkey(),
+        account_rent,
+        account_len as u64,
+        token_program,
+    );
+    let initialize_account_ix = instruction::initialize_account(
+        token_program,
+        &token_account.pubkey(),
+        &mint_account.pubkey(),
+        &owner.pubkey(),
+    )
+    .unwrap();
+    let mint_to_ix = instruction::mint_to(
+        token_program,
+        &mint_account.pubkey(),
+        &token_account.pubkey(),
+        &owner.pubkey(),
+        &[],
+        10,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(
+        &[create_token_account_ix, initialize_account_ix, mint_to_ix],
+        &payer.pubkey(),
+        &[&payer, &token_account, &owner],
+    )
+    .await
+    .unwrap();
+
+    (mint_account, token_account, owner)
+}
+
+/// Creates a new mint of `token_program` (SPL token or Token-2022) and a token account,
+/// and funds it with tokens.
+///
+/// Returns (mint_account, token_account, owner)
+pub async fn setup_token_account_with_program(
+    rpc: &mut LightProgramTest,
+    token_program: &Pubkey,
+) -> (Keypair, Keypair, Keypair) {
     let payer = rpc.get_payer().insecure_clone();
 
     let mint_account = Keypair::new();
     let owner = payer.insecure_clone();
-    let token_program = &id();
     let mint_rent = rpc
         .get_minimum_balance_for_rent_exemption(Mint::LEN)
         .await
@@ -0,0 +1,84 @@
+//! Client side helpers to look nullifiers up through the indexer
+//! and to build the instruction data of nullifier instructions.
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use light_client::{
+    indexer::{AddressWithTree, Indexer, IndexerError},
+    rpc::RpcError,
+};
+use light_sdk::instruction::{PackedAccounts, SystemAccountMetaConfig};
+
+use crate::{derive_nullifier_address, NullifierCheckInstructionData};
+
+/// Returns for each nullifier whether its nullifier account exists, i.e. the nullifier is spent.
+/// All nullifiers are looked up with one indexer request.
+///
+/// # Arguments
+/// * `domain` - Namespace of the nullifiers
+/// * `nullifiers` - Nullifier values to look up
+/// * `address_tree` - Address tree the nullifier accounts are created in
+/// * `program_id` - Program that owns the nullifier accounts
+pub async fn get_spent_nullifiers<R>(
+    indexer: &mut R,
+    domain: &[u8; 32],
+    nullifiers: &[[u8; 32]],
+    address_tree: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<Vec<bool>, IndexerError>
+where
+    R: Indexer,
+{
+    let addresses = nullifiers
+        .iter()
+        .map(|nullifier| derive_nullifier_address(domain, nullifier, address_tree, program_id).0)
+        .collect();
+    let accounts = indexer
+        .get_multiple_compressed_accounts(Some(addresses), None, None)
+        .await?
+        .value
+        .items;
+    Ok(accounts.iter().map(Option::is_some).collect())
+}
+
+/// Fetches the non-inclusion proof of the nullifier addresses
+/// and returns the instruction data and remaining accounts of a nullifier non-inclusion check.
+/// Fails if a nullifier is already spent.
+pub async fn build_check_nullifiers_instruction_data<R>(
+    indexer: &mut R,
+    domain: &[u8; 32],
+    nullifiers: &[[u8; 32]],
+    address_tree: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<(NullifierCheckInstructionData, Vec<AccountMeta>), RpcError>
+where
+    R: Indexer,
+{
+    let mut remaining_accounts = PackedAccounts::default();
+    let config = SystemAccountMetaConfig::new(*program_id);
+    remaining_accounts.add_system_accounts_v2(config)?;
+
+    let addresses = nullifiers
+        .iter()
+        .map(|nullifier| AddressWithTree {
+            address: derive_nullifier_address(domain, nullifier, address_tree, program_id).0,
+            tree: *address_tree,
+        })
+        .collect();
+    let rpc_result = indexer
+        .get_validity_proof(vec![], addresses, None)
+        .await?
+        .value;
+    let packed_address_tree_accounts = rpc_result
+        .pack_tree_infos(&mut remaining_accounts)
+        .address_trees;
+
+    let (remaining_accounts_metas, system_accounts_offset, _) =
+        remaining_accounts.to_account_metas();
+
+    let data = NullifierCheckInstructionData {
+        proof: rpc_result.proof,
+        address_tree_info: packed_address_tree_accounts[0],
+        system_accounts_offset: system_accounts_offset as u8,
+        domain: *domain,
+    };
+    Ok((data, remaining_accounts_metas))
+}
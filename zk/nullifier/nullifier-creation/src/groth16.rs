@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use groth16_solana::decompression::{decompress_g1, decompress_g2};
+use groth16_solana::groth16::{Groth16Verifier, Groth16Verifyingkey};
+use light_sdk::instruction::CompressedProof;
+
+use crate::{ErrorCode, NullifierProofVerifier};
+
+/// Verifies a compressed Groth16 proof whose public inputs are the nullifiers
+/// followed by the other public inputs of the circuit.
+/// `NR_INPUTS` is the number of public inputs of the verifying key.
+pub struct Groth16NullifierVerifier<'a, const NR_INPUTS: usize> {
+    pub proof: &'a CompressedProof,
+    pub verifying_key: &'a Groth16Verifyingkey<'a>,
+    /// Public inputs after the nullifiers, e.g. a Merkle root or an amount.
+    pub public_inputs: &'a [[u8; 32]],
+}
+
+impl<const NR_INPUTS: usize> NullifierProofVerifier for Groth16NullifierVerifier<'_, NR_INPUTS> {
+    fn verify(&self, nullifiers: &[[u8; 32]]) -> Result<()> {
+        if nullifiers.len() + self.public_inputs.len() != NR_INPUTS {
+            return err!(ErrorCode::InvalidPublicInputs);
+        }
+        let mut public_inputs = [[0u8; 32]; NR_INPUTS];
+        for (public_input, value) in public_inputs
+            .iter_mut()
+            .zip(nullifiers.iter().chain(self.public_inputs))
+        {
+            *public_input = *value;
+        }
+
+        let proof_a = decompress_g1(&self.proof.a).map_err(|_| ErrorCode::InvalidProof)?;
+        let proof_b = decompress_g2(&self.proof.b).map_err(|_| ErrorCode::InvalidProof)?;
+        let proof_c = decompress_g1(&self.proof.c).map_err(|_| ErrorCode::InvalidProof)?;
+
+        let mut verifier = Groth16Verifier::new(
+            &proof_a,
+            &proof_b,
+            &proof_c,
+            &public_inputs,
+            self.verifying_key,
+        )
+        .map_err(|_| ErrorCode::InvalidProof)?;
+
+        verifier.verify().map_err(|_| ErrorCode::InvalidProof)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+//! Creates nullifiers as compressed pdas, for programs that embed it or CPI into the `nullifier` program.
+//!
+//! A nullifier account is created at the address derived from `[NULLIFIER_PREFIX, domain, nullifier]`
+//! and owned by the program that calls [`create_nullifiers`], creating it twice fails.
+//! The domain namespaces nullifiers, applications that share a program don't collide on the same nullifier value.
+//! [`verify_and_create_nullifiers`] checks a proof with the nullifiers as public inputs first,
+//! see [`NullifierProofVerifier`] and `Groth16NullifierVerifier` (`groth16` feature).
+//! [`check_nullifiers_unused`] proves that nullifiers don't exist yet without creating them,
+//! clients look nullifiers up through the indexer with the `client` feature.
+#![allow(unexpected_cfgs)]
+#![allow(deprecated)]
+
+use anchor_lang::prelude::*;
+use borsh::{BorshDeserialize, BorshSerialize};
+use light_sdk::account::LightAccount;
+use light_sdk::cpi::v2::CpiAccounts;
+use light_sdk::{
+    address::{
+        v2::derive_address, AddressSeed, NewAddressParamsAssignedPacked, PackedReadOnlyAddress,
+    },
+    cpi::{v2::LightSystemProgramCpi, InvokeLightSystemProgram, LightCpiInstruction},
+    instruction::{PackedAddressTreeInfo, ValidityProof},
+    CpiSigner, LightDiscriminator, PackedAddressTreeInfoExt,
+};
+
+// Nullifier lookups and instruction data for clients.
+#[cfg(all(feature = "client", not(target_os = "solana")))]
+pub mod client;
+
+#[cfg(feature = "groth16")]
+mod groth16;
+#[cfg(feature = "groth16")]
+pub use groth16::Groth16NullifierVerifier;
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Not enough keys in remaining accounts")]
+    AccountNotEnoughKeys,
+    #[msg("Number of public inputs does not match the verifying key")]
+    InvalidPublicInputs,
+    #[msg("Proof verification failed")]
+    InvalidProof,
+}
+
+#[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize, LightDiscriminator)]
+pub struct NullifierAccount {}
+
+pub const NULLIFIER_PREFIX: &[u8] = b"nullifier";
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct NullifierInstructionData {
+    pub proof: ValidityProof,
+    pub address_tree_info: PackedAddressTreeInfo,
+    pub output_state_tree_index: u8,
+    pub system_accounts_offset: u8,
+    /// Namespace of the nullifiers, mixed into the address seeds.
+    pub domain: [u8; 32],
+}
+
+/// Instruction data of a nullifier non-inclusion check, the validity proof proves that
+/// the nullifier addresses are not in the address tree.
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct NullifierCheckInstructionData {
+    pub proof: ValidityProof,
+    pub address_tree_info: PackedAddressTreeInfo,
+    pub system_accounts_offset: u8,
+    /// Namespace of the nullifiers, mixed into the address seeds.
+    pub domain: [u8; 32],
+}
+
+/// Returns the address and address seed of the nullifier account of `nullifier` in `domain`,
+/// derived from `[NULLIFIER_PREFIX, domain, nullifier]` in the address tree for the program that owns the nullifiers.
+pub fn derive_nullifier_address(
+    domain: &[u8; 32],
+    nullifier: &[u8; 32],
+    address_tree_pubkey: &Pubkey,
+    program_id: &Pubkey,
+) -> ([u8; 32], AddressSeed) {
+    derive_address(
+        &[NULLIFIER_PREFIX, domain.as_slice(), nullifier.as_slice()],
+        address_tree_pubkey,
+        program_id,
+    )
+}
+
+/// Hook that checks a proof before nullifiers are created.
+/// The nullifiers are public inputs of the proof, so a proof can't be reused with other nullifiers.
+pub trait NullifierProofVerifier {
+    /// Verifies the proof with `nullifiers` among its public inputs.
+    fn verify(&self, nullifiers: &[[u8; 32]]) -> Result<()>;
+}
+
+/// Verifies the proof with `verifier` and creates the nullifier compressed pdas if it is valid.
+///
+/// # Arguments
+/// * `verifier` - Checks the proof with the nullifiers as public inputs
+/// * `nullifiers` - Slice of nullifier values to create compressed pdas for
+/// * `data` - Instruction data with proof and tree info
+/// * `remaining_accounts` - Remaining accounts must contain zk compression system program accounts and Merkle trees.
+/// * `cpi_signer` - Cpi signer of the calling program, which owns the nullifier accounts
+pub fn verify_and_create_nullifiers<'info>(
+    verifier: &impl NullifierProofVerifier,
+    nullifiers: &[[u8; 32]],
+    data: NullifierInstructionData,
+    signer: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    cpi_signer: CpiSigner,
+) -> Result<()> {
+    verifier.verify(nullifiers)?;
+    create_nullifiers(nullifiers, data, signer, remaining_accounts, cpi_signer)
+}
+
+/// Creates nullifier compressed pdas for the given nullifier values.
+///
+/// # Arguments
+/// * `nullifiers` - Slice of nullifier values to create compressed pdas for
+/// * `data` - Instruction data with proof and tree info
+/// * `remaining_accounts` - Remaining accounts must contain zk compression system program accounts and Merkle trees.
+/// * `cpi_signer` - Cpi signer of the calling program, which owns the nullifier accounts
+pub fn create_nullifiers<'info>(
+    nullifiers: &[[u8; 32]],
+    data: NullifierInstructionData,
+    signer: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    cpi_signer: CpiSigner,
+) -> Result<()> {
+    let program_id = Pubkey::new_from_array(cpi_signer.program_id);
+    let light_cpi_accounts = CpiAccounts::new(
+        signer,
+        &remaining_accounts[data.system_accounts_offset as usize..],
+        cpi_signer,
+    );
+
+    let address_tree_pubkey = data
+        .address_tree_info
+        .get_tree_pubkey(&light_cpi_accounts)
+        .map_err(|_| ErrorCode::AccountNotEnoughKeys)?;
+
+    if address_tree_pubkey.to_bytes() != light_sdk::constants::ADDRESS_TREE_V2 {
+        msg!("Invalid address tree");
+        return Err(ProgramError::InvalidAccountData.into());
+    }
+
+    let mut cpi_builder = LightSystemProgramCpi::new_cpi(cpi_signer, data.proof);
+    let mut new_address_params: Vec<NewAddressParamsAssignedPacked> =
+        Vec::with_capacity(nullifiers.len());
+
+    for (i, nullifier) in nullifiers.iter().enumerate() {
+        let (address, address_seed) =
+            derive_nullifier_address(&data.domain, nullifier, &address_tree_pubkey, &program_id);
+
+        let nullifier_account = LightAccount::<NullifierAccount>::new_init(
+            &program_id,
+            Some(address),
+            data.output_state_tree_index,
+        );
+
+        cpi_builder = cpi_builder.with_light_account(nullifier_account)?;
+        new_address_params.push(
+            data.address_tree_info
+                .into_new_address_params_assigned_packed(address_seed, Some(i as u8)),
+        );
+    }
+
+    cpi_builder
+        .with_new_addresses(&new_address_params)
+        .invoke(light_cpi_accounts)?;
+
+    Ok(())
+}
+
+/// Proves that the nullifier accounts of the given nullifier values don't exist, without creating them.
+/// The addresses are passed to the light system program as read-only addresses,
+/// it fails if the validity proof is not a non-inclusion proof of every address.
+///
+/// # Arguments
+/// * `nullifiers` - Slice of nullifier values to check
+/// * `data` - Instruction data with the non-inclusion proof and address tree info
+/// * `remaining_accounts` - Remaining accounts must contain zk compression system program accounts and the address tree.
+/// * `cpi_signer` - Cpi signer of the program that owns the nullifier accounts
+pub fn check_nullifiers_unused<'info>(
+    nullifiers: &[[u8; 32]],
+    data: NullifierCheckInstructionData,
+    signer: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    cpi_signer: CpiSigner,
+) -> Result<()> {
+    let program_id = Pubkey::new_from_array(cpi_signer.program_id);
+    let light_cpi_accounts = CpiAccounts::new(
+        signer,
+        &remaining_accounts[data.system_accounts_offset as usize..],
+        cpi_signer,
+    );
+
+    let address_tree_pubkey = data
+        .address_tree_info
+        .get_tree_pubkey(&light_cpi_accounts)
+        .map_err(|_| ErrorCode::AccountNotEnoughKeys)?;
+
+    // Read-only addresses are only supported in v2 address trees.
+    if address_tree_pubkey.to_bytes() != light_sdk::constants::ADDRESS_TREE_V2 {
+        msg!("Invalid address tree");
+        return Err(ProgramError::InvalidAccountData.into());
+    }
+
+    let read_only_addresses: Vec<PackedReadOnlyAddress> = nullifiers
+        .iter()
+        .map(|nullifier| PackedReadOnlyAddress {
+            address: derive_nullifier_address(
+                &data.domain,
+                nullifier,
+                &address_tree_pubkey,
+                &program_id,
+            )
+            .0,
+            address_merkle_tree_root_index: data.address_tree_info.root_index,
+            address_merkle_tree_account_index: data
+                .address_tree_info
+                .address_merkle_tree_pubkey_index,
+        })
+        .collect();
+
+    LightSystemProgramCpi::new_cpi(cpi_signer, data.proof)
+        .with_read_only_addresses(&read_only_addresses)
+        .invoke(light_cpi_accounts)?;
+
+    Ok(())
+}
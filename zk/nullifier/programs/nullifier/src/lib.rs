@@ -2,132 +2,164 @@
 #![allow(deprecated)]
 
 use anchor_lang::prelude::*;
-use nullifier_creation::{create_nullifiers, NullifierInstructionData};
+use anchor_lang::solana_program::hash::hashv;
+use light_sdk::{derive_light_cpi_signer, CpiSigner};
+// The nullifier creation library, re-exported for clients and programs that CPI into this program.
+pub use nullifier_creation;
+use nullifier_creation::{
+    check_nullifiers_unused, create_nullifiers, NullifierCheckInstructionData,
+    NullifierInstructionData,
+};
 
 declare_id!("Bw8aty8LJY5Kg2b6djghjWGwt6cBc1tVQUoreUehvVq4");
 
+pub const LIGHT_CPI_SIGNER: CpiSigner =
+    derive_light_cpi_signer!("Bw8aty8LJY5Kg2b6djghjWGwt6cBc1tVQUoreUehvVq4");
+
+pub const NULLIFIER_DOMAIN: &[u8] = b"nullifier_domain";
+
+/// First byte of the domains [`derive_domain`] returns, open domains can't start with it.
+pub const REGISTERED_DOMAIN_TAG: u8 = 0xff;
+
 #[program]
 pub mod nullifier {
     use super::*;
 
+    /// Registers the signing `authority` for its domain [`derive_domain`], only it can create nullifiers
+    /// in the domain afterwards. Other domains can't be registered, so nobody takes over an open domain.
+    /// A program registers a PDA of its own as authority and signs create_nullifier CPIs with it.
+    /// Domains without registration are open, anyone can create nullifiers in them.
+    /// Derived domains are reserved, nobody creates nullifiers in them before they are registered.
+    pub fn register_domain(ctx: Context<RegisterDomainAccounts>, domain: [u8; 32]) -> Result<()> {
+        ctx.accounts.domain_account.domain = domain;
+        ctx.accounts.domain_account.authority = ctx.accounts.authority.key();
+        Ok(())
+    }
+
     /// Creates nullifier accounts for the provided nullifier values.
+    /// Does not verify a proof, programs that do embed nullifier-creation.
     pub fn create_nullifier<'info>(
         ctx: Context<'_, '_, '_, 'info, CreateNullifierAccounts<'info>>,
         data: NullifierInstructionData,
         nullifiers: Vec<[u8; 32]>,
     ) -> Result<()> {
-        // Verify your proof here. Use nullifiers as public inputs
-        // among your other public inputs.
+        check_domain_authority(
+            &data.domain,
+            &ctx.accounts.domain_account,
+            ctx.accounts.authority.as_ref(),
+        )?;
+
+        // To verify a proof first, use verify_and_create_nullifiers
+        // with a NullifierProofVerifier that has the nullifiers among its public inputs.
         // Example:
-        // let public_inputs = [...nullifiers, ...your_other_inputs];
-        // Groth16Verifier::new(...).verify()?;
+        // let verifier = Groth16NullifierVerifier::<NR_INPUTS> { proof, verifying_key, public_inputs };
+        // verify_and_create_nullifiers(&verifier, &nullifiers, data, ...)?;
 
         create_nullifiers(
             &nullifiers,
             data,
             ctx.accounts.signer.as_ref(),
             ctx.remaining_accounts,
+            LIGHT_CPI_SIGNER,
+        )
+    }
+
+    /// Proves that no nullifier account exists for the provided nullifier values, without creating them.
+    /// Fails if any nullifier is already used.
+    pub fn check_nullifiers<'info>(
+        ctx: Context<'_, '_, '_, 'info, CheckNullifierAccounts<'info>>,
+        data: NullifierCheckInstructionData,
+        nullifiers: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        check_nullifiers_unused(
+            &nullifiers,
+            data,
+            ctx.accounts.signer.as_ref(),
+            ctx.remaining_accounts,
+            LIGHT_CPI_SIGNER,
         )
     }
 }
 
-#[derive(Accounts)]
-pub struct CreateNullifierAccounts<'info> {
-    #[account(mut)]
-    pub signer: Signer<'info>,
+/// Returns the domain an authority can register, `sha256(NULLIFIER_DOMAIN || authority)`
+/// with the first byte set to [`REGISTERED_DOMAIN_TAG`].
+pub fn derive_domain(authority: &Pubkey) -> [u8; 32] {
+    let mut domain = hashv(&[NULLIFIER_DOMAIN, authority.as_ref()]).to_bytes();
+    domain[0] = REGISTERED_DOMAIN_TAG;
+    domain
 }
 
-pub mod nullifier_creation {
-    use super::*;
-    use borsh::{BorshDeserialize, BorshSerialize};
-    use light_sdk::account::LightAccount;
-    use light_sdk::cpi::v2::CpiAccounts;
-    use light_sdk::{
-        address::{v2::derive_address, NewAddressParamsAssignedPacked},
-        cpi::{v2::LightSystemProgramCpi, InvokeLightSystemProgram, LightCpiInstruction},
-        derive_light_cpi_signer,
-        instruction::{PackedAddressTreeInfo, ValidityProof},
-        LightDiscriminator, PackedAddressTreeInfoExt,
-    };
-    use light_sdk::CpiSigner;
-    #[error_code]
-    pub enum ErrorCode {
-        #[msg("Not enough keys in remaining accounts")]
-        AccountNotEnoughKeys,
+/// Checks that the registered authority of a domain signed, registered domain accounts are owned by this program.
+/// Tagged domains that are not registered yet are rejected, so nobody squats them before registration.
+fn check_domain_authority<'info>(
+    domain: &[u8; 32],
+    domain_account: &UncheckedAccount<'info>,
+    authority: Option<&Signer<'info>>,
+) -> Result<()> {
+    if domain_account.owner != &crate::ID {
+        if domain[0] == REGISTERED_DOMAIN_TAG {
+            return err!(ErrorCode::UnregisteredDomain);
+        }
+        return Ok(());
     }
-
-    #[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize, LightDiscriminator)]
-    pub struct NullifierAccount {}
-
-    pub const LIGHT_CPI_SIGNER: CpiSigner =
-        derive_light_cpi_signer!("Bw8aty8LJY5Kg2b6djghjWGwt6cBc1tVQUoreUehvVq4");
-
-    pub const NULLIFIER_PREFIX: &[u8] = b"nullifier";
-
-    #[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
-    pub struct NullifierInstructionData {
-        pub proof: ValidityProof,
-        pub address_tree_info: PackedAddressTreeInfo,
-        pub output_state_tree_index: u8,
-        pub system_accounts_offset: u8,
+    let domain_account =
+        NullifierDomain::try_deserialize(&mut &domain_account.try_borrow_data()?[..])?;
+    match authority {
+        Some(authority) if authority.key() == domain_account.authority => Ok(()),
+        _ => err!(ErrorCode::InvalidDomainAuthority),
     }
+}
 
-    /// Creates nullifier compressed pdas for the given nullifier values.
-    ///
-    /// # Arguments
-    /// * `nullifiers` - Slice of nullifier values to create compressed pdas for
-    /// * `data` - Instruction data with proof and tree info
-    /// * `remaining_accounts` - Remaining accounts must contain zk compression system program accounts and Merkle trees.
-    pub fn create_nullifiers<'info>(
-        nullifiers: &[[u8; 32]],
-        data: NullifierInstructionData,
-        signer: &AccountInfo<'info>,
-        remaining_accounts: &[AccountInfo<'info>],
-    ) -> Result<()> {
-        let light_cpi_accounts = CpiAccounts::new(
-            signer,
-            &remaining_accounts[data.system_accounts_offset as usize..],
-            LIGHT_CPI_SIGNER,
-        );
-
-        let address_tree_pubkey = data
-            .address_tree_info
-            .get_tree_pubkey(&light_cpi_accounts)
-            .map_err(|_| ErrorCode::AccountNotEnoughKeys)?;
+#[derive(Accounts)]
+#[instruction(domain: [u8; 32])]
+pub struct RegisterDomainAccounts<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(constraint = domain == derive_domain(&authority.key()) @ ErrorCode::InvalidDomain)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + NullifierDomain::INIT_SPACE,
+        seeds = [NULLIFIER_DOMAIN, domain.as_ref()],
+        bump
+    )]
+    pub domain_account: Account<'info, NullifierDomain>,
+    pub system_program: Program<'info, System>,
+}
 
-        if address_tree_pubkey.to_bytes() != light_sdk::constants::ADDRESS_TREE_V2 {
-            msg!("Invalid address tree");
-            return Err(ProgramError::InvalidAccountData.into());
-        }
+#[derive(Accounts)]
+#[instruction(data: NullifierInstructionData)]
+pub struct CreateNullifierAccounts<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    /// CHECK: Registry account of the nullifier domain, empty if the domain is not registered.
+    #[account(seeds = [NULLIFIER_DOMAIN, data.domain.as_ref()], bump)]
+    pub domain_account: UncheckedAccount<'info>,
+    /// Registered authority of the domain, only required for registered domains.
+    pub authority: Option<Signer<'info>>,
+}
 
-        let mut cpi_builder = LightSystemProgramCpi::new_cpi(LIGHT_CPI_SIGNER, data.proof);
-        let mut new_address_params: Vec<NewAddressParamsAssignedPacked> =
-            Vec::with_capacity(nullifiers.len());
-
-        for (i, nullifier) in nullifiers.iter().enumerate() {
-            let (address, address_seed) = derive_address(
-                &[NULLIFIER_PREFIX, nullifier.as_slice()],
-                &address_tree_pubkey,
-                &crate::ID,
-            );
-
-            let nullifier_account = LightAccount::<NullifierAccount>::new_init(
-                &crate::ID,
-                Some(address),
-                data.output_state_tree_index,
-            );
-
-            cpi_builder = cpi_builder.with_light_account(nullifier_account)?;
-            new_address_params.push(
-                data.address_tree_info
-                    .into_new_address_params_assigned_packed(address_seed, Some(i as u8)),
-            );
-        }
+#[derive(Accounts)]
+pub struct CheckNullifierAccounts<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
 
-        cpi_builder
-            .with_new_addresses(&new_address_params)
-            .invoke(light_cpi_accounts)?;
+/// Registration of a nullifier domain.
+#[account]
+#[derive(InitSpace)]
+pub struct NullifierDomain {
+    pub domain: [u8; 32],
+    pub authority: Pubkey,
+}
 
-        Ok(())
-    }
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Domain is registered, its authority must sign")]
+    InvalidDomainAuthority,
+    #[msg("Domain is not derived from the authority")]
+    InvalidDomain,
+    #[msg("Domain is reserved for its authority and not registered yet")]
+    UnregisteredDomain,
 }
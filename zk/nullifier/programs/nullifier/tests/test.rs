@@ -3,7 +3,15 @@ use light_program_test::{
     program_test::LightProgramTest, Indexer, ProgramTestConfig, Rpc, RpcError,
 };
 use nullifier::nullifier_creation::NullifierInstructionData;
-use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signer};
+use nullifier_creation::client::{build_check_nullifiers_instruction_data, get_spent_nullifiers};
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+/// Open (unregistered) nullifier domain used by the tests
+const DOMAIN: [u8; 32] = [1u8; 32];
 
 #[tokio::test]
 async fn test_create_single_nullifier() {
@@ -14,7 +22,7 @@ async fn test_create_single_nullifier() {
     let nullifier = Pubkey::new_unique().to_bytes();
 
     let (data, remaining_accounts) =
-        build_create_nullifier_instruction_data(&mut rpc, &[nullifier])
+        build_create_nullifier_instruction_data(&mut rpc, &DOMAIN, &[nullifier])
             .await
             .unwrap();
 
@@ -24,6 +32,8 @@ async fn test_create_single_nullifier() {
     };
     let accounts = nullifier::accounts::CreateNullifierAccounts {
         signer: payer.pubkey(),
+        domain_account: domain_account_pda(&DOMAIN),
+        authority: None,
     };
     let instruction = Instruction {
         program_id: nullifier::ID,
@@ -35,11 +45,11 @@ async fn test_create_single_nullifier() {
         .await
         .unwrap();
 
-    assert_nullifiers_exist(&mut rpc, &[nullifier]).await;
+    assert_nullifiers_exist(&mut rpc, &DOMAIN, &[nullifier]).await;
 
     // Duplicate should fail
     let (dup_data, dup_remaining_accounts) =
-        build_create_nullifier_instruction_data(&mut rpc, &[nullifier])
+        build_create_nullifier_instruction_data(&mut rpc, &DOMAIN, &[nullifier])
             .await
             .unwrap();
 
@@ -49,6 +59,8 @@ async fn test_create_single_nullifier() {
     };
     let dup_accounts = nullifier::accounts::CreateNullifierAccounts {
         signer: payer.pubkey(),
+        domain_account: domain_account_pda(&DOMAIN),
+        authority: None,
     };
     let dup_instruction = Instruction {
         program_id: nullifier::ID,
@@ -70,45 +82,305 @@ async fn test_create_multiple_nullifiers() {
 
     let nullifiers: Vec<[u8; 32]> = (0..3).map(|_| Pubkey::new_unique().to_bytes()).collect();
 
-    let (data, remaining_accounts) = build_create_nullifier_instruction_data(&mut rpc, &nullifiers)
+    let (data, remaining_accounts) =
+        build_create_nullifier_instruction_data(&mut rpc, &DOMAIN, &nullifiers)
+            .await
+            .unwrap();
+
+    let instruction_data = nullifier::instruction::CreateNullifier {
+        data,
+        nullifiers: nullifiers.clone(),
+    };
+    let accounts = nullifier::accounts::CreateNullifierAccounts {
+        signer: payer.pubkey(),
+        domain_account: domain_account_pda(&DOMAIN),
+        authority: None,
+    };
+    let instruction = Instruction {
+        program_id: nullifier::ID,
+        accounts: [accounts.to_account_metas(None), remaining_accounts].concat(),
+        data: instruction_data.data(),
+    };
+
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer])
         .await
         .unwrap();
 
+    assert_nullifiers_exist(&mut rpc, &DOMAIN, &nullifiers).await;
+}
+
+#[tokio::test]
+async fn test_check_nullifiers() {
+    let config = ProgramTestConfig::new(true, Some(vec![("nullifier", nullifier::ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+    let address_tree = rpc.get_address_tree_v2().tree;
+
+    let spent_nullifier = Pubkey::new_unique().to_bytes();
+    let unused_nullifier = Pubkey::new_unique().to_bytes();
+
+    let (data, remaining_accounts) =
+        build_create_nullifier_instruction_data(&mut rpc, &DOMAIN, &[spent_nullifier])
+            .await
+            .unwrap();
     let instruction_data = nullifier::instruction::CreateNullifier {
         data,
-        nullifiers: nullifiers.clone(),
+        nullifiers: vec![spent_nullifier],
     };
     let accounts = nullifier::accounts::CreateNullifierAccounts {
         signer: payer.pubkey(),
+        domain_account: domain_account_pda(&DOMAIN),
+        authority: None,
     };
     let instruction = Instruction {
         program_id: nullifier::ID,
         accounts: [accounts.to_account_metas(None), remaining_accounts].concat(),
         data: instruction_data.data(),
     };
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    let spent = get_spent_nullifiers(
+        &mut rpc,
+        &DOMAIN,
+        &[spent_nullifier, unused_nullifier],
+        &address_tree,
+        &nullifier::ID,
+    )
+    .await
+    .unwrap();
+    assert_eq!(spent, vec![true, false]);
 
+    // The non-inclusion check of an unused nullifier succeeds and doesn't create it.
+    let (data, remaining_accounts) = build_check_nullifiers_instruction_data(
+        &mut rpc,
+        &DOMAIN,
+        &[unused_nullifier],
+        &address_tree,
+        &nullifier::ID,
+    )
+    .await
+    .unwrap();
+    let instruction_data = nullifier::instruction::CheckNullifiers {
+        data,
+        nullifiers: vec![unused_nullifier],
+    };
+    let accounts = nullifier::accounts::CheckNullifierAccounts {
+        signer: payer.pubkey(),
+    };
+    let instruction = Instruction {
+        program_id: nullifier::ID,
+        accounts: [accounts.to_account_metas(None), remaining_accounts].concat(),
+        data: instruction_data.data(),
+    };
     rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer])
         .await
         .unwrap();
+    let spent = get_spent_nullifiers(
+        &mut rpc,
+        &DOMAIN,
+        &[unused_nullifier],
+        &address_tree,
+        &nullifier::ID,
+    )
+    .await
+    .unwrap();
+    assert_eq!(spent, vec![false]);
+
+    // A spent nullifier has no non-inclusion proof.
+    let result = build_check_nullifiers_instruction_data(
+        &mut rpc,
+        &DOMAIN,
+        &[spent_nullifier],
+        &address_tree,
+        &nullifier::ID,
+    )
+    .await;
+    assert!(result.is_err());
 
-    assert_nullifiers_exist(&mut rpc, &nullifiers).await;
+    // A non-inclusion proof of an unused nullifier doesn't prove another, spent nullifier.
+    let (data, remaining_accounts) = build_check_nullifiers_instruction_data(
+        &mut rpc,
+        &DOMAIN,
+        &[unused_nullifier],
+        &address_tree,
+        &nullifier::ID,
+    )
+    .await
+    .unwrap();
+    let instruction_data = nullifier::instruction::CheckNullifiers {
+        data,
+        nullifiers: vec![spent_nullifier],
+    };
+    let instruction = Instruction {
+        program_id: nullifier::ID,
+        accounts: [accounts.to_account_metas(None), remaining_accounts].concat(),
+        data: instruction_data.data(),
+    };
+    let result = rpc
+        .create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer])
+        .await;
+    assert!(result.is_err());
 }
 
-async fn assert_nullifiers_exist<R>(rpc: &mut R, nullifiers: &[[u8; 32]])
+#[tokio::test]
+async fn test_nullifier_domains() {
+    let config = ProgramTestConfig::new(true, Some(vec![("nullifier", nullifier::ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    // The same nullifier value in two domains are two nullifiers.
+    let nullifier = Pubkey::new_unique().to_bytes();
+    let other_domain = [2u8; 32];
+    for domain in [DOMAIN, other_domain] {
+        create_nullifier(&mut rpc, &payer, &domain, None, &[nullifier])
+            .await
+            .unwrap();
+        assert_nullifiers_exist(&mut rpc, &domain, &[nullifier]).await;
+    }
+
+    // Only the authority of a registered domain can create nullifiers in it.
+    let authority = Keypair::new();
+    let registered_domain = nullifier::derive_domain(&authority.pubkey());
+    // A derived domain can't be used before it is registered.
+    let result = create_nullifier(&mut rpc, &payer, &registered_domain, None, &[nullifier]).await;
+    assert!(result.is_err());
+    // A domain can only be registered by the authority it is derived from.
+    let result = register_domain(&mut rpc, &payer, &Keypair::new(), &registered_domain).await;
+    assert!(result.is_err());
+    register_domain(&mut rpc, &payer, &authority, &registered_domain)
+        .await
+        .unwrap();
+    // A domain can only be registered once.
+    let result = register_domain(&mut rpc, &payer, &authority, &registered_domain).await;
+    assert!(result.is_err());
+
+    let result = create_nullifier(&mut rpc, &payer, &registered_domain, None, &[nullifier]).await;
+    assert!(result.is_err());
+    let result = create_nullifier(
+        &mut rpc,
+        &payer,
+        &registered_domain,
+        Some(&Keypair::new()),
+        &[nullifier],
+    )
+    .await;
+    assert!(result.is_err());
+
+    create_nullifier(
+        &mut rpc,
+        &payer,
+        &registered_domain,
+        Some(&authority),
+        &[nullifier],
+    )
+    .await
+    .unwrap();
+    assert_nullifiers_exist(&mut rpc, &registered_domain, &[nullifier]).await;
+}
+
+#[tokio::test]
+async fn test_open_domain_takeover() {
+    let config = ProgramTestConfig::new(true, Some(vec![("nullifier", nullifier::ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    // An application uses the open domain.
+    create_nullifier(&mut rpc, &payer, &DOMAIN, None, &[[1u8; 32]])
+        .await
+        .unwrap();
+
+    // A third party can't register it, it is not derived from their authority.
+    let attacker = Keypair::new();
+    let result = register_domain(&mut rpc, &payer, &attacker, &DOMAIN).await;
+    assert!(result.is_err());
+    assert!(rpc
+        .get_account(domain_account_pda(&DOMAIN))
+        .await
+        .unwrap()
+        .is_none());
+
+    // The domain stays open.
+    create_nullifier(&mut rpc, &payer, &DOMAIN, None, &[[2u8; 32]])
+        .await
+        .unwrap();
+    assert_nullifiers_exist(&mut rpc, &DOMAIN, &[[1u8; 32], [2u8; 32]]).await;
+}
+
+async fn register_domain<R>(
+    rpc: &mut R,
+    payer: &Keypair,
+    authority: &Keypair,
+    domain: &[u8; 32],
+) -> Result<solana_sdk::signature::Signature, RpcError>
 where
     R: Rpc + Indexer,
 {
-    use light_sdk::address::v2::derive_address;
-    use nullifier::nullifier_creation::NULLIFIER_PREFIX;
+    let instruction_data = nullifier::instruction::RegisterDomain { domain: *domain };
+    let accounts = nullifier::accounts::RegisterDomainAccounts {
+        signer: payer.pubkey(),
+        authority: authority.pubkey(),
+        domain_account: domain_account_pda(domain),
+        system_program: solana_sdk::system_program::ID,
+    };
+    let instruction = Instruction {
+        program_id: nullifier::ID,
+        accounts: accounts.to_account_metas(None),
+        data: instruction_data.data(),
+    };
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[payer, authority])
+        .await
+}
+
+async fn create_nullifier<R>(
+    rpc: &mut R,
+    payer: &Keypair,
+    domain: &[u8; 32],
+    authority: Option<&Keypair>,
+    nullifiers: &[[u8; 32]],
+) -> Result<solana_sdk::signature::Signature, RpcError>
+where
+    R: Rpc + Indexer,
+{
+    let (data, remaining_accounts) =
+        build_create_nullifier_instruction_data(rpc, domain, nullifiers).await?;
+    let instruction_data = nullifier::instruction::CreateNullifier {
+        data,
+        nullifiers: nullifiers.to_vec(),
+    };
+    let accounts = nullifier::accounts::CreateNullifierAccounts {
+        signer: payer.pubkey(),
+        domain_account: domain_account_pda(domain),
+        authority: authority.map(|authority| authority.pubkey()),
+    };
+    let instruction = Instruction {
+        program_id: nullifier::ID,
+        accounts: [accounts.to_account_metas(None), remaining_accounts].concat(),
+        data: instruction_data.data(),
+    };
+
+    let mut signers = vec![payer];
+    signers.extend(authority);
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &signers)
+        .await
+}
+
+fn domain_account_pda(domain: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[nullifier::NULLIFIER_DOMAIN, domain], &nullifier::ID).0
+}
+
+async fn assert_nullifiers_exist<R>(rpc: &mut R, domain: &[u8; 32], nullifiers: &[[u8; 32]])
+where
+    R: Rpc + Indexer,
+{
+    use nullifier::nullifier_creation::derive_nullifier_address;
 
     let address_tree_info = rpc.get_address_tree_v2();
 
     for nullifier in nullifiers {
-        let (address, _) = derive_address(
-            &[NULLIFIER_PREFIX, nullifier.as_slice()],
-            &address_tree_info.tree,
-            &nullifier::ID,
-        );
+        let (address, _) =
+            derive_nullifier_address(domain, nullifier, &address_tree_info.tree, &nullifier::ID);
 
         let account = rpc
             .get_compressed_account(address, None)
@@ -126,6 +398,7 @@ where
 
 async fn build_create_nullifier_instruction_data<R>(
     rpc: &mut R,
+    domain: &[u8; 32],
     nullifiers: &[[u8; 32]],
 ) -> Result<
     (
@@ -138,11 +411,8 @@ where
     R: Rpc + Indexer,
 {
     use light_program_test::AddressWithTree;
-    use light_sdk::{
-        address::v2::derive_address,
-        instruction::{PackedAccounts, SystemAccountMetaConfig},
-    };
-    use nullifier::nullifier_creation::NULLIFIER_PREFIX;
+    use light_sdk::instruction::{PackedAccounts, SystemAccountMetaConfig};
+    use nullifier::nullifier_creation::derive_nullifier_address;
 
     let address_tree_info = rpc.get_address_tree_v2();
 
@@ -153,11 +423,8 @@ where
     let address_with_trees: Vec<AddressWithTree> = nullifiers
         .iter()
         .map(|n| {
-            let (address, _) = derive_address(
-                &[NULLIFIER_PREFIX, n.as_slice()],
-                &address_tree_info.tree,
-                &nullifier::ID,
-            );
+            let (address, _) =
+                derive_nullifier_address(domain, n, &address_tree_info.tree, &nullifier::ID);
             AddressWithTree {
                 address,
                 tree: address_tree_info.tree,
@@ -186,6 +453,7 @@ where
         address_tree_info: packed_address_tree_accounts[0],
         output_state_tree_index,
         system_accounts_offset: system_accounts_offset as u8,
+        domain: *domain,
     };
 
     Ok((data, remaining_accounts_metas))
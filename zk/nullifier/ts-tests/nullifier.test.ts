@@ -9,6 +9,12 @@ const IDL = JSON.parse(
 );
 
 const PROGRAM_ID = new web3.PublicKey(IDL.address);
+// Open (unregistered) nullifier domain used by the tests.
+const DOMAIN = new Uint8Array(32).fill(1);
+const [DOMAIN_ACCOUNT] = web3.PublicKey.findProgramAddressSync(
+  [Buffer.from("nullifier_domain"), DOMAIN],
+  PROGRAM_ID
+);
 
 
 describe("nullifier", () => {
@@ -53,11 +59,15 @@ describe("nullifier", () => {
       const nullifier = randomBytes32();
 
       const { data, remainingAccounts } = await createNullifierInstructionData(
-        rpc, PROGRAM_ID, [nullifier]
+        rpc, PROGRAM_ID, DOMAIN, [nullifier]
       );
       const ix = await program.methods
         .createNullifier(data, [Array.from(nullifier)])
-        .accounts({ signer: signer.publicKey })
+        .accountsPartial({
+          signer: signer.publicKey,
+          domainAccount: DOMAIN_ACCOUNT,
+          authority: null,
+        })
         .remainingAccounts(remainingAccounts)
         .instruction();
       const computeIx = web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 200_000 });
@@ -85,11 +95,15 @@ describe("nullifier", () => {
       const nullifiers = [randomBytes32(), randomBytes32()];
 
       const { data, remainingAccounts } = await createNullifierInstructionData(
-        rpc, PROGRAM_ID, nullifiers
+        rpc, PROGRAM_ID, DOMAIN, nullifiers
       );
       const ix = await program.methods
         .createNullifier(data, nullifiers.map((n) => Array.from(n)))
-        .accounts({ signer: signer.publicKey })
+        .accountsPartial({
+          signer: signer.publicKey,
+          domainAccount: DOMAIN_ACCOUNT,
+          authority: null,
+        })
         .remainingAccounts(remainingAccounts)
         .instruction();
       const computeIx = web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 300_000 });
@@ -113,6 +127,7 @@ describe("nullifier", () => {
 async function createNullifierInstructionData(
   rpc: Rpc,
   programId: web3.PublicKey,
+  domain: Uint8Array,
   nullifiers: Uint8Array[]
 ) {
   const {
@@ -134,7 +149,7 @@ async function createNullifierInstructionData(
   const outputStateTree = defaultTestStateTreeAccounts().merkleTree;
 
   const addressesWithTree = nullifiers.map((nullifier) => {
-    const seed = deriveAddressSeedV2([NULLIFIER_PREFIX, nullifier]);
+    const seed = deriveAddressSeedV2([NULLIFIER_PREFIX, domain, nullifier]);
     const address = deriveAddressV2(seed, addressTree, programId);
     return { tree: addressTree, queue: addressTree, address: bn(address.toBytes()) };
   });
@@ -158,6 +173,7 @@ async function createNullifierInstructionData(
     },
     outputStateTreeIndex,
     systemAccountsOffset: systemStart,
+    domain: Array.from(domain),
   };
 
   return { data, remainingAccounts: accountMetas };
@@ -0,0 +1,415 @@
+//! Circuit artifacts of the zk-id program.
+//!
+//! `circuits/manifest.json` pins the hash of each circuit's sources, the circom and circomlib versions
+//! and the number of public inputs. `generate` (see main.rs) compiles the circuits, runs the Groth16 setup,
+//! verifies the zkeys against the circuits and writes `build/artifacts.json` with the hashes of the artifacts.
+//! build.rs checks the sources against the manifest and the artifacts against `build/artifacts.json`,
+//! so stale or modified artifacts fail the build instead of silently producing wrong keys and witnesses.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Committed manifest, relative to the project directory.
+pub const MANIFEST_PATH: &str = "circuits/manifest.json";
+/// Hashes of the generated artifacts, written by `generate`.
+pub const STAMP_PATH: &str = "build/artifacts.json";
+pub const CIRCUITS_DIR: &str = "circuits";
+pub const BUILD_DIR: &str = "build";
+
+#[derive(Error, Debug)]
+pub enum ArtifactError {
+    #[error("Cannot read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Cannot parse {path}: {source}")]
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("Sources of circuit {circuit} changed (manifest {expected}, sources {actual}). Run `cargo run -p zk-id-artifacts -- update-manifest` and commit circuits/manifest.json.")]
+    SourcesMismatch {
+        circuit: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("Main component {0} is not in circuits/manifest.json.")]
+    UnknownCircuit(String),
+    #[error("{tool} version {actual} does not match the manifest ({expected}).")]
+    VersionMismatch {
+        tool: &'static str,
+        expected: String,
+        actual: String,
+    },
+    #[error("{0} is missing. Run ./scripts/setup.sh.")]
+    MissingArtifact(PathBuf),
+    #[error("{0} was not generated from the current circuit sources. Run ./scripts/setup.sh.")]
+    StaleArtifact(PathBuf),
+    #[error("{path} has {actual} public inputs, the manifest expects {expected}.")]
+    PublicInputsMismatch {
+        path: PathBuf,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("{0} is not a valid r1cs file.")]
+    InvalidR1cs(PathBuf),
+    #[error("{0} is not a Groth16 bn128 verification key.")]
+    InvalidVerifyingKey(PathBuf),
+    #[error("Command {command} failed: {message}")]
+    Command { command: String, message: String },
+}
+
+pub type Result<T> = std::result::Result<T, ArtifactError>;
+
+/// Committed circuit manifest.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Manifest {
+    /// Version of circom the circuits are compiled with, the r1cs and witness generators depend on it.
+    pub circom_version: String,
+    /// Version of the circomlib npm package the circuits include.
+    pub circomlib_version: String,
+    pub circuits: Vec<CircuitManifest>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CircuitManifest {
+    /// Name of the main component in `circuits/main/`.
+    pub name: String,
+    /// Hash of the circuit sources, see [`sources_sha256`].
+    pub sources_sha256: String,
+    /// Number of public inputs, `NUM_PUBLIC_INPUTS` of the program.
+    pub num_public_inputs: usize,
+    /// Verification key exported into `build/`.
+    pub verification_key: String,
+}
+
+impl CircuitManifest {
+    pub fn main_path(&self, project_dir: &Path) -> PathBuf {
+        project_dir
+            .join(CIRCUITS_DIR)
+            .join("main")
+            .join(format!("{}.circom", self.name))
+    }
+
+    pub fn r1cs_path(&self, project_dir: &Path) -> PathBuf {
+        project_dir
+            .join(BUILD_DIR)
+            .join(format!("{}.r1cs", self.name))
+    }
+
+    pub fn wasm_path(&self, project_dir: &Path) -> PathBuf {
+        project_dir
+            .join(BUILD_DIR)
+            .join(format!("{}_js", self.name))
+            .join(format!("{}.wasm", self.name))
+    }
+
+    pub fn zkey_path(&self, project_dir: &Path) -> PathBuf {
+        project_dir
+            .join(BUILD_DIR)
+            .join(format!("{}_final.zkey", self.name))
+    }
+
+    pub fn verification_key_path(&self, project_dir: &Path) -> PathBuf {
+        project_dir.join(BUILD_DIR).join(&self.verification_key)
+    }
+}
+
+/// Hashes of the artifacts of the last `generate` run.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Stamp {
+    pub circuits: Vec<CircuitStamp>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CircuitStamp {
+    pub name: String,
+    /// Sources the artifacts were generated from.
+    pub sources_sha256: String,
+    pub r1cs_sha256: String,
+    pub wasm_sha256: String,
+    pub zkey_sha256: String,
+    pub verification_key_sha256: String,
+}
+
+impl Manifest {
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        read_json(&project_dir.join(MANIFEST_PATH))
+    }
+
+    pub fn save(&self, project_dir: &Path) -> Result<()> {
+        write_json(&project_dir.join(MANIFEST_PATH), self)
+    }
+}
+
+impl Stamp {
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        let path = project_dir.join(STAMP_PATH);
+        if !path.exists() {
+            return Err(ArtifactError::MissingArtifact(path));
+        }
+        read_json(&path)
+    }
+
+    pub fn save(&self, project_dir: &Path) -> Result<()> {
+        write_json(&project_dir.join(STAMP_PATH), self)
+    }
+
+    /// Hashes the current artifacts of the circuits in `manifest`.
+    pub fn new(project_dir: &Path, manifest: &Manifest) -> Result<Self> {
+        let circuits = manifest
+            .circuits
+            .iter()
+            .map(|circuit| {
+                Ok(CircuitStamp {
+                    name: circuit.name.clone(),
+                    sources_sha256: circuit.sources_sha256.clone(),
+                    r1cs_sha256: artifact_sha256(&circuit.r1cs_path(project_dir))?,
+                    wasm_sha256: artifact_sha256(&circuit.wasm_path(project_dir))?,
+                    zkey_sha256: artifact_sha256(&circuit.zkey_path(project_dir))?,
+                    verification_key_sha256: artifact_sha256(
+                        &circuit.verification_key_path(project_dir),
+                    )?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { circuits })
+    }
+}
+
+/// Source files of a circuit: all templates in `circuits/` and its main component.
+/// Sorted so that the hash doesn't depend on the directory order.
+pub fn circuit_sources(project_dir: &Path, circuit: &CircuitManifest) -> Result<Vec<PathBuf>> {
+    let circuits_dir = project_dir.join(CIRCUITS_DIR);
+    let entries = fs::read_dir(&circuits_dir).map_err(|source| ArtifactError::Io {
+        path: circuits_dir.clone(),
+        source,
+    })?;
+    let mut sources = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|source| ArtifactError::Io {
+                path: circuits_dir.clone(),
+                source,
+            })?
+            .path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "circom")
+        {
+            sources.push(path);
+        }
+    }
+    sources.sort();
+    sources.push(circuit.main_path(project_dir));
+    Ok(sources)
+}
+
+/// Sha256 over `path || 0 || len (u64 LE) || contents` of every source file,
+/// paths are relative to the project directory.
+pub fn sources_sha256(project_dir: &Path, circuit: &CircuitManifest) -> Result<String> {
+    let mut hasher = Sha256::new();
+    for path in circuit_sources(project_dir, circuit)? {
+        let contents = read(&path)?;
+        let relative_path = path.strip_prefix(project_dir).unwrap_or(&path);
+        hasher.update(relative_path.to_string_lossy().as_bytes());
+        hasher.update([0u8]);
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+    Ok(hex(&hasher.finalize()))
+}
+
+/// Loads the manifest and checks it against the circuit sources and the circomlib version of package.json.
+pub fn check_sources(project_dir: &Path) -> Result<Manifest> {
+    let manifest = Manifest::load(project_dir)?;
+
+    let package_json: serde_json::Value = read_json(&project_dir.join("package.json"))?;
+    let circomlib_version = package_json["dependencies"]["circomlib"]
+        .as_str()
+        .unwrap_or_default();
+    if circomlib_version != manifest.circomlib_version {
+        return Err(ArtifactError::VersionMismatch {
+            tool: "circomlib",
+            expected: manifest.circomlib_version,
+            actual: circomlib_version.to_string(),
+        });
+    }
+
+    // Every main component must be pinned.
+    let main_dir = project_dir.join(CIRCUITS_DIR).join("main");
+    let entries = fs::read_dir(&main_dir).map_err(|source| ArtifactError::Io {
+        path: main_dir.clone(),
+        source,
+    })?;
+    for entry in entries {
+        let path = entry
+            .map_err(|source| ArtifactError::Io {
+                path: main_dir.clone(),
+                source,
+            })?
+            .path();
+        if path
+            .extension()
+            .is_none_or(|extension| extension != "circom")
+        {
+            continue;
+        }
+        let name = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        if !manifest.circuits.iter().any(|circuit| circuit.name == name) {
+            return Err(ArtifactError::UnknownCircuit(name));
+        }
+    }
+
+    for circuit in manifest.circuits.iter() {
+        let actual = sources_sha256(project_dir, circuit)?;
+        if actual != circuit.sources_sha256 {
+            return Err(ArtifactError::SourcesMismatch {
+                circuit: circuit.name.clone(),
+                expected: circuit.sources_sha256.clone(),
+                actual,
+            });
+        }
+    }
+    Ok(manifest)
+}
+
+/// Checks that the artifacts in `build/` were generated from the sources pinned in `manifest`
+/// and weren't modified since, and that the r1cs and verification keys have the pinned number of public inputs.
+pub fn check_artifacts(project_dir: &Path, manifest: &Manifest) -> Result<()> {
+    let stamp = Stamp::load(project_dir)?;
+    let current = Stamp::new(project_dir, manifest)?;
+    for circuit in current.circuits.iter() {
+        let stamped = stamp
+            .circuits
+            .iter()
+            .find(|stamped| stamped.name == circuit.name)
+            .ok_or_else(|| ArtifactError::StaleArtifact(project_dir.join(STAMP_PATH)))?;
+        if stamped != circuit {
+            return Err(ArtifactError::StaleArtifact(
+                project_dir.join(BUILD_DIR).join(&circuit.name),
+            ));
+        }
+    }
+
+    for circuit in manifest.circuits.iter() {
+        let r1cs_path = circuit.r1cs_path(project_dir);
+        let actual = r1cs_num_public_inputs(&r1cs_path)?;
+        if actual != circuit.num_public_inputs {
+            return Err(ArtifactError::PublicInputsMismatch {
+                path: r1cs_path,
+                expected: circuit.num_public_inputs,
+                actual,
+            });
+        }
+        let vk_path = circuit.verification_key_path(project_dir);
+        let actual = verification_key_num_public_inputs(&vk_path)?;
+        if actual != circuit.num_public_inputs {
+            return Err(ArtifactError::PublicInputsMismatch {
+                path: vk_path,
+                expected: circuit.num_public_inputs,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Number of public signals (outputs and public inputs) in the header of an r1cs file.
+///
+/// Layout: "r1cs", version (u32), number of sections (u32), sections of (type (u32), size (u64), data).
+/// The header section (type 1) is field size (u32), prime, wires (u32), public outputs (u32), public inputs (u32), ...
+pub fn r1cs_num_public_inputs(path: &Path) -> Result<usize> {
+    let data = read(path)?;
+    let invalid = || ArtifactError::InvalidR1cs(path.to_path_buf());
+    let u32_at = |offset: usize| -> Result<u32> {
+        data.get(offset..offset + 4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+            .ok_or_else(invalid)
+    };
+    if data.get(..4) != Some(b"r1cs".as_slice()) {
+        return Err(invalid());
+    }
+    let num_sections = u32_at(8)?;
+    let mut offset = 12;
+    for _ in 0..num_sections {
+        let section_type = u32_at(offset)?;
+        let section_size = data
+            .get(offset + 4..offset + 12)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()) as usize)
+            .ok_or_else(invalid)?;
+        offset += 12;
+        if section_type == 1 {
+            let field_size = u32_at(offset)? as usize;
+            let wires_offset = offset + 4 + field_size;
+            let num_public_outputs = u32_at(wires_offset + 4)?;
+            let num_public_inputs = u32_at(wires_offset + 8)?;
+            return Ok((num_public_outputs + num_public_inputs) as usize);
+        }
+        offset += section_size;
+    }
+    Err(invalid())
+}
+
+/// `nPublic` of a snarkjs Groth16 verification key.
+pub fn verification_key_num_public_inputs(path: &Path) -> Result<usize> {
+    if !path.exists() {
+        return Err(ArtifactError::MissingArtifact(path.to_path_buf()));
+    }
+    let verification_key: serde_json::Value = read_json(path)?;
+    if verification_key["protocol"] != "groth16" || verification_key["curve"] != "bn128" {
+        return Err(ArtifactError::InvalidVerifyingKey(path.to_path_buf()));
+    }
+    verification_key["nPublic"]
+        .as_u64()
+        .map(|num_public_inputs| num_public_inputs as usize)
+        .ok_or_else(|| ArtifactError::InvalidVerifyingKey(path.to_path_buf()))
+}
+
+/// Sha256 of a generated artifact.
+pub fn artifact_sha256(path: &Path) -> Result<String> {
+    if !path.exists() {
+        return Err(ArtifactError::MissingArtifact(path.to_path_buf()));
+    }
+    Ok(hex(&Sha256::digest(read(path)?)))
+}
+
+fn read(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).map_err(|source| ArtifactError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T> {
+    serde_json::from_slice(&read(path)?).map_err(|source| ArtifactError::Json {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let mut json = serde_json::to_string_pretty(value).map_err(|source| ArtifactError::Json {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    json.push('\n');
+    fs::write(path, json).map_err(|source| ArtifactError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,292 @@
+//! Generates and checks the circuit artifacts of zk-id.
+//!
+//! ```bash
+//! cargo run -p zk-id-artifacts -- generate [ptau]  # compile, setup, verify and stamp the artifacts
+//! cargo run -p zk-id-artifacts -- check [ptau]     # check the artifacts and verify the zkeys and verification keys
+//! cargo run -p zk-id-artifacts -- update-manifest  # pin the current circuit sources after changing a circuit
+//! ```
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use zk_id_artifacts::{
+    check_artifacts, check_sources, r1cs_num_public_inputs, sources_sha256,
+    verification_key_num_public_inputs, ArtifactError, CircuitManifest, Manifest, Result, Stamp,
+    BUILD_DIR, CIRCUITS_DIR,
+};
+
+/// Powers of Tau file downloaded by ./scripts/setup.sh.
+const DEFAULT_PTAU: &str = "pot/powersOfTau28_hez_final_16.ptau";
+
+fn main() {
+    let project_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let ptau = project_dir.join(args.get(1).map(String::as_str).unwrap_or(DEFAULT_PTAU));
+
+    let result = match args.first().map(String::as_str) {
+        Some("generate") => generate(&project_dir, &ptau),
+        Some("check") => check(&project_dir, &ptau),
+        Some("update-manifest") => update_manifest(&project_dir),
+        _ => {
+            eprintln!("Usage: zk-id-artifacts <generate [ptau] | check [ptau] | update-manifest>");
+            std::process::exit(2);
+        }
+    };
+    if let Err(error) = result {
+        eprintln!("Error: {}", error);
+        std::process::exit(1);
+    }
+}
+
+/// Compiles the circuits pinned in the manifest, runs the Groth16 setup,
+/// exports and verifies the verification keys and stamps the artifacts.
+fn generate(project_dir: &Path, ptau: &Path) -> Result<()> {
+    let manifest = check_sources(project_dir)?;
+    check_circom_version(&manifest)?;
+    if !ptau.exists() {
+        return Err(ArtifactError::MissingArtifact(ptau.to_path_buf()));
+    }
+
+    for circuit in manifest.circuits.iter() {
+        println!("Compiling {}", circuit.name);
+        compile(project_dir, circuit)?;
+        let r1cs_path = circuit.r1cs_path(project_dir);
+        let num_public_inputs = r1cs_num_public_inputs(&r1cs_path)?;
+        if num_public_inputs != circuit.num_public_inputs {
+            return Err(ArtifactError::PublicInputsMismatch {
+                path: r1cs_path,
+                expected: circuit.num_public_inputs,
+                actual: num_public_inputs,
+            });
+        }
+
+        println!("Generating the zkey of {}", circuit.name);
+        let initial_zkey = project_dir
+            .join(BUILD_DIR)
+            .join(format!("{}_0000.zkey", circuit.name));
+        snarkjs(
+            project_dir,
+            &[
+                "groth16",
+                "setup",
+                &path_arg(&r1cs_path),
+                &path_arg(ptau),
+                &path_arg(&initial_zkey),
+            ],
+        )?;
+        // The contribution is random, the zkey and verification key differ per setup.
+        // The verification key is registered on-chain per deployment, see register_verifying_key.
+        snarkjs(
+            project_dir,
+            &[
+                "zkey",
+                "contribute",
+                &path_arg(&initial_zkey),
+                &path_arg(&circuit.zkey_path(project_dir)),
+                "--name=zk-id",
+                &format!("-e={}", entropy()?),
+            ],
+        )?;
+        snarkjs(
+            project_dir,
+            &[
+                "zkey",
+                "export",
+                "verificationkey",
+                &path_arg(&circuit.zkey_path(project_dir)),
+                &path_arg(&circuit.verification_key_path(project_dir)),
+            ],
+        )?;
+        verify_zkey(project_dir, ptau, circuit)?;
+    }
+
+    Stamp::new(project_dir, &manifest)?.save(project_dir)?;
+    check_artifacts(project_dir, &manifest)?;
+    println!("Circuit artifacts generated and verified");
+    Ok(())
+}
+
+/// Checks the artifacts against the manifest and verifies that each zkey belongs to its circuit
+/// and each verification key was exported from its zkey.
+fn check(project_dir: &Path, ptau: &Path) -> Result<()> {
+    let manifest = check_sources(project_dir)?;
+    check_artifacts(project_dir, &manifest)?;
+    for circuit in manifest.circuits.iter() {
+        verify_zkey(project_dir, ptau, circuit)?;
+    }
+    println!("Circuit artifacts match circuits/manifest.json");
+    Ok(())
+}
+
+/// Compiles every main component and pins its sources and number of public inputs.
+fn update_manifest(project_dir: &Path) -> Result<()> {
+    let mut manifest = Manifest::load(project_dir)?;
+    check_circom_version(&manifest)?;
+
+    let main_dir = project_dir.join(CIRCUITS_DIR).join("main");
+    let mut names: Vec<String> = fs::read_dir(&main_dir)
+        .map_err(|source| ArtifactError::Io {
+            path: main_dir.clone(),
+            source,
+        })?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "circom")
+        })
+        .map(|path| path.file_stem().unwrap().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+
+    let mut circuits = Vec::with_capacity(names.len());
+    for name in names {
+        let mut circuit = manifest
+            .circuits
+            .iter()
+            .find(|circuit| circuit.name == name)
+            .cloned()
+            .unwrap_or_else(|| CircuitManifest {
+                verification_key: format!("{}_verification_key.json", name),
+                name,
+                sources_sha256: String::new(),
+                num_public_inputs: 0,
+            });
+        println!("Compiling {}", circuit.name);
+        compile(project_dir, &circuit)?;
+        circuit.sources_sha256 = sources_sha256(project_dir, &circuit)?;
+        circuit.num_public_inputs = r1cs_num_public_inputs(&circuit.r1cs_path(project_dir))?;
+        circuits.push(circuit);
+    }
+    manifest.circuits = circuits;
+    manifest.save(project_dir)?;
+    println!("Updated circuits/manifest.json, run ./scripts/setup.sh to regenerate the artifacts");
+    Ok(())
+}
+
+fn compile(project_dir: &Path, circuit: &CircuitManifest) -> Result<()> {
+    fs::create_dir_all(project_dir.join(BUILD_DIR)).map_err(|source| ArtifactError::Io {
+        path: project_dir.join(BUILD_DIR),
+        source,
+    })?;
+    run(Command::new("circom")
+        .current_dir(project_dir)
+        .arg(circuit.main_path(project_dir))
+        .args(["--r1cs", "--wasm", "--sym", "-o", BUILD_DIR]))
+    .map(|_| ())
+}
+
+/// Verifies the zkey against the circuit and the Powers of Tau,
+/// and that the verification key in build/ is the one of the zkey.
+fn verify_zkey(project_dir: &Path, ptau: &Path, circuit: &CircuitManifest) -> Result<()> {
+    snarkjs(
+        project_dir,
+        &[
+            "zkey",
+            "verify",
+            &path_arg(&circuit.r1cs_path(project_dir)),
+            &path_arg(ptau),
+            &path_arg(&circuit.zkey_path(project_dir)),
+        ],
+    )?;
+
+    let exported_path = project_dir
+        .join(BUILD_DIR)
+        .join(format!("{}_verification_key.check.json", circuit.name));
+    snarkjs(
+        project_dir,
+        &[
+            "zkey",
+            "export",
+            "verificationkey",
+            &path_arg(&circuit.zkey_path(project_dir)),
+            &path_arg(&exported_path),
+        ],
+    )?;
+    let exported = read_json_value(&exported_path);
+    let _ = fs::remove_file(&exported_path);
+    let verification_key_path = circuit.verification_key_path(project_dir);
+    if exported? != read_json_value(&verification_key_path)? {
+        return Err(ArtifactError::StaleArtifact(verification_key_path));
+    }
+    let num_public_inputs = verification_key_num_public_inputs(&verification_key_path)?;
+    if num_public_inputs != circuit.num_public_inputs {
+        return Err(ArtifactError::PublicInputsMismatch {
+            path: verification_key_path,
+            expected: circuit.num_public_inputs,
+            actual: num_public_inputs,
+        });
+    }
+    Ok(())
+}
+
+fn check_circom_version(manifest: &Manifest) -> Result<()> {
+    // "circom compiler 2.2.2"
+    let output = run(Command::new("circom").arg("--version"))?;
+    let version = output.split_whitespace().last().unwrap_or_default();
+    if version != manifest.circom_version {
+        return Err(ArtifactError::VersionMismatch {
+            tool: "circom",
+            expected: manifest.circom_version.clone(),
+            actual: version.to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn snarkjs(project_dir: &Path, args: &[&str]) -> Result<String> {
+    run(Command::new("npx")
+        .current_dir(project_dir)
+        .arg("snarkjs")
+        .args(args))
+}
+
+fn run(command: &mut Command) -> Result<String> {
+    let display = format!("{:?}", command);
+    let output = command.output().map_err(|error| ArtifactError::Command {
+        command: display.clone(),
+        message: error.to_string(),
+    })?;
+    if !output.status.success() {
+        return Err(ArtifactError::Command {
+            command: display,
+            message: format!(
+                "{}{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            ),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn read_json_value(path: &Path) -> Result<serde_json::Value> {
+    let data = fs::read(path).map_err(|source| ArtifactError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    serde_json::from_slice(&data).map_err(|source| ArtifactError::Json {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// 32 random bytes of the zkey contribution, hex encoded.
+fn entropy() -> Result<String> {
+    let path = PathBuf::from("/dev/urandom");
+    let mut bytes = [0u8; 32];
+    fs::File::open(&path)
+        .and_then(|mut file| file.read_exact(&mut bytes))
+        .map_err(|source| ArtifactError::Io { path, source })?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+fn path_arg(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
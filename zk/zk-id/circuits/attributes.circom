@@ -0,0 +1,69 @@
+pragma circom 2.0.0;
+
+include "../node_modules/circomlib/circuits/poseidon.circom";
+include "../node_modules/circomlib/circuits/bitify.circom";
+include "../node_modules/circomlib/circuits/comparators.circom";
+
+// Attributes Hash Template
+// Computes the commitment to the credential attributes stored on-chain
+// attributes_hash = Poseidon(attributes[0], ..., attributes[n - 1], blinding)
+// The blinding hides low entropy attributes (e.g. an age) from brute forcing.
+template AttributesHash(n) {
+    signal input attributes[n];
+    signal input blinding;
+
+    signal output hash;
+
+    component hasher = Poseidon(n + 1);
+    for (var i = 0; i < n; i++) {
+        hasher.inputs[i] <== attributes[i];
+    }
+    hasher.inputs[n] <== blinding;
+
+    hash <== hasher.out;
+}
+
+// Attribute Predicates Template
+// Proves attribute_min[i] <= attributes[i] <= attribute_max[i] for every attribute
+// - equality: attribute_min[i] == attribute_max[i]
+// - unconstrained: attribute_min[i] = 0, attribute_max[i] = 2^64 - 1
+template AttributePredicates(n) {
+    signal input attributes[n];
+    signal input attribute_min[n];
+    signal input attribute_max[n];
+
+    component attributeBits[n];
+    component lowerBound[n];
+    component upperBound[n];
+
+    for (var i = 0; i < n; i++) {
+        // Attributes are u64, the range check keeps the comparators sound
+        attributeBits[i] = Num2Bits(64);
+        attributeBits[i].in <== attributes[i];
+
+        lowerBound[i] = GreaterEqThan(64);
+        lowerBound[i].in[0] <== attributes[i];
+        lowerBound[i].in[1] <== attribute_min[i];
+        lowerBound[i].out === 1;
+
+        upperBound[i] = LessEqThan(64);
+        upperBound[i].in[0] <== attributes[i];
+        upperBound[i].in[1] <== attribute_max[i];
+        upperBound[i].out === 1;
+    }
+}
+
+// Not Expired Template
+// Proves current_slot < expiry_slot without revealing expiry_slot
+template NotExpired() {
+    signal input expiry_slot;
+    signal input current_slot;
+
+    component expiryBits = Num2Bits(64);
+    expiryBits.in <== expiry_slot;
+
+    component beforeExpiry = LessThan(64);
+    beforeExpiry.in[0] <== current_slot;
+    beforeExpiry.in[1] <== expiry_slot;
+    beforeExpiry.out === 1;
+}
@@ -4,12 +4,12 @@ include "../node_modules/circomlib/circuits/poseidon.circom";
 include "./credential.circom";
 include "./compressed_account.circom";
 include "./merkle_proof.circom";
-
-
+include "./attributes.circom";
 
 // Main Circuit: Compressed Account Merkle Proof Verification
 // Computes compressed account hash and verifies it exists in a Merkle tree
-template CompressedAccountMerkleProof(levels) {
+// levels is the height of the state tree, the main components for v1 and v2 state trees are in main/.
+template CompressedAccountMerkleProof(levels, numAttributes) {
     // ============ PUBLIC INPUTS ============
     // Account identifiers
     signal input owner_hashed;
@@ -23,18 +23,31 @@ template CompressedAccountMerkleProof(levels) {
     // Verification context (external nullifier)
     signal input verification_id;
 
-    // Data commitments
+    // Hash of the event data encrypted to the verifier, binds the ciphertext to the proof
     signal input public_encrypted_data_hash;
 
     // Nullifier (prevents double-spending)
     signal input nullifier;
 
+    // Slot the credential must not be expired at
+    signal input current_slot;
+
+    // Attribute predicates: attribute_min[i] <= attributes[i] <= attribute_max[i]
+    signal input attribute_min[numAttributes];
+    signal input attribute_max[numAttributes];
+
+    // Sybil mode: campaign scoped nullifier, both are zero without a campaign
+    // campaign_id is the campaign id hashed with the verifier, see campaign_input in lib.rs
+    signal input campaign_id;
+    signal input campaign_nullifier;
+
     // ============ PRIVATE INPUTS ============
     // Credential secret
     signal input credentialPrivateKey;
 
     // Account position
     signal input leaf_index;
+    // leaf index as hashed into the account hash (u32 LE in v1, u32 BE in v2 trees)
     signal input account_leaf_index;
     signal input address;
 
@@ -44,16 +57,27 @@ template CompressedAccountMerkleProof(levels) {
     // Private data
     signal input encrypted_data_hash;
 
+    // Credential expiry and attributes (committed to by the credential account)
+    signal input expiry_slot;
+    signal input attributes[numAttributes];
+    signal input attributes_blinding;
+
     // Step 1: Verify credential ownership using private key
     component keypair = Keypair();
     keypair.privateKey <== credentialPrivateKey;
     signal credential_pubkey_commitment <== keypair.publicKey;
 
     // Step 2: Compute the credential data hash
-    // data_hash = Poseidon(issuer_hashed, credential_pubkey_commitment)
-    component data_hasher = Poseidon(2);
+    // data_hash = Poseidon(issuer_hashed, credential_pubkey_commitment, expiry_slot, attributes_hash)
+    component attributesHasher = AttributesHash(numAttributes);
+    attributesHasher.attributes <== attributes;
+    attributesHasher.blinding <== attributes_blinding;
+
+    component data_hasher = Poseidon(4);
     data_hasher.inputs[0] <== issuer_hashed;
     data_hasher.inputs[1] <== credential_pubkey_commitment;
+    data_hasher.inputs[2] <== expiry_slot;
+    data_hasher.inputs[3] <== attributesHasher.hash;
     signal data_hash <== data_hasher.out;
 
     // Step 3: Compute and verify nullifier
@@ -82,20 +106,24 @@ template CompressedAccountMerkleProof(levels) {
     merkleProof.leafIndex <== leaf_index;
     merkleProof.root === expectedRoot;
 
+    // Step 6: Verify the credential is not expired at current_slot
+    component notExpired = NotExpired();
+    notExpired.expiry_slot <== expiry_slot;
+    notExpired.current_slot <== current_slot;
+
     // Step 7: Verify encrypted data hash matches
     public_encrypted_data_hash === encrypted_data_hash;
-}
 
-// Main component with 26 levels (typical for Solana state trees)
-component main {
-    public [
-        owner_hashed,
-        merkle_tree_hashed,
-        discriminator,
-        issuer_hashed,
-        expectedRoot,
-        verification_id,
-        public_encrypted_data_hash,
-        nullifier
-    ]
-} = CompressedAccountMerkleProof(26);
+    // Step 8: Verify the attribute predicates
+    component predicates = AttributePredicates(numAttributes);
+    predicates.attributes <== attributes;
+    predicates.attribute_min <== attribute_min;
+    predicates.attribute_max <== attribute_max;
+
+    // Step 9: Verify the campaign nullifier
+    // campaign_nullifier = Poseidon(campaign_id, credentialPrivateKey) if campaign_id != 0
+    component campaignNullifier = CampaignNullifier();
+    campaignNullifier.campaign_id <== campaign_id;
+    campaignNullifier.privateKey <== credentialPrivateKey;
+    campaign_nullifier === campaignNullifier.nullifier;
+}
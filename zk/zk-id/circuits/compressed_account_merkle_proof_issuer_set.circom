@@ -0,0 +1,75 @@
+pragma circom 2.0.0;
+
+include "./compressed_account_merkle_proof.circom";
+include "./issuer_set.circom";
+
+// Anonymity Set Circuit: Compressed Account Merkle Proof with a hidden issuer
+// Proves the same statement as CompressedAccountMerkleProof, but the issuer is a private input
+// and the proof only shows that it is in the issuer set of the verifier.
+// The public inputs are those of CompressedAccountMerkleProof with issuer_set_root in place of issuer_hashed,
+// in the same order, so the program verifies both circuits with the same public input layout.
+template CompressedAccountMerkleProofIssuerSet(levels, numAttributes, issuerSetLevels) {
+    // ============ PUBLIC INPUTS ============
+    signal input owner_hashed;
+    signal input merkle_tree_hashed;
+    signal input discriminator;
+    // Merkle root of the issuers the verifier accepts
+    signal input issuer_set_root;
+    signal input expectedRoot;
+    signal input verification_id;
+    signal input public_encrypted_data_hash;
+    signal input nullifier;
+    signal input current_slot;
+    signal input attribute_min[numAttributes];
+    signal input attribute_max[numAttributes];
+    signal input campaign_id;
+    signal input campaign_nullifier;
+
+    // ============ PRIVATE INPUTS ============
+    // Issuer and its position in the issuer set
+    signal input issuer_hashed;
+    signal input issuerPathElements[issuerSetLevels];
+    signal input issuer_index;
+
+    signal input credentialPrivateKey;
+    signal input leaf_index;
+    signal input account_leaf_index;
+    signal input address;
+    signal input pathElements[levels];
+    signal input encrypted_data_hash;
+    signal input expiry_slot;
+    signal input attributes[numAttributes];
+    signal input attributes_blinding;
+
+    // Step 1: Prove the credential with the issuer as private input
+    component credential = CompressedAccountMerkleProof(levels, numAttributes);
+    credential.owner_hashed <== owner_hashed;
+    credential.merkle_tree_hashed <== merkle_tree_hashed;
+    credential.discriminator <== discriminator;
+    credential.issuer_hashed <== issuer_hashed;
+    credential.expectedRoot <== expectedRoot;
+    credential.verification_id <== verification_id;
+    credential.public_encrypted_data_hash <== public_encrypted_data_hash;
+    credential.nullifier <== nullifier;
+    credential.current_slot <== current_slot;
+    credential.attribute_min <== attribute_min;
+    credential.attribute_max <== attribute_max;
+    credential.campaign_id <== campaign_id;
+    credential.campaign_nullifier <== campaign_nullifier;
+    credential.credentialPrivateKey <== credentialPrivateKey;
+    credential.leaf_index <== leaf_index;
+    credential.account_leaf_index <== account_leaf_index;
+    credential.address <== address;
+    credential.pathElements <== pathElements;
+    credential.encrypted_data_hash <== encrypted_data_hash;
+    credential.expiry_slot <== expiry_slot;
+    credential.attributes <== attributes;
+    credential.attributes_blinding <== attributes_blinding;
+
+    // Step 2: Verify the issuer is in the issuer set
+    component issuerSet = IssuerSetMembership(issuerSetLevels);
+    issuerSet.issuer_hashed <== issuer_hashed;
+    issuerSet.issuer_set_root <== issuer_set_root;
+    issuerSet.pathElements <== issuerPathElements;
+    issuerSet.leafIndex <== issuer_index;
+}
@@ -1,6 +1,7 @@
 pragma circom 2.0.0;
 
 include "../node_modules/circomlib/circuits/poseidon.circom";
+include "../node_modules/circomlib/circuits/comparators.circom";
 
 // Based on Tornado Cash Nova's keypair design
 // Proves knowledge of a private key that corresponds to a public key commitment
@@ -38,4 +39,25 @@ template CredentialOwnership() {
     credHasher.inputs[0] <== issuer;
     credHasher.inputs[1] <== credentialPublicKey;
     credentialHash <== credHasher.out;
-}
\ No newline at end of file
+}
+
+// Campaign scoped nullifier (sybil mode)
+// nullifier = Poseidon(campaign_id, privateKey) if campaign_id != 0, else 0
+// The nullifier does not depend on the verification id, so a credential holder
+// can only pass one verification of a campaign. Without a campaign the nullifier
+// is zero so that proofs of unrelated verifications stay unlinkable.
+template CampaignNullifier() {
+    signal input campaign_id;
+    signal input privateKey;
+
+    signal output nullifier;
+
+    component hasher = Poseidon(2);
+    hasher.inputs[0] <== campaign_id;
+    hasher.inputs[1] <== privateKey;
+
+    component noCampaign = IsZero();
+    noCampaign.in <== campaign_id;
+
+    nullifier <== (1 - noCampaign.out) * hasher.out;
+}
@@ -0,0 +1,31 @@
+pragma circom 2.0.0;
+
+include "../node_modules/circomlib/circuits/comparators.circom";
+include "./merkle_proof.circom";
+
+// Height of the issuer set Merkle tree (up to 256 issuers)
+function ISSUER_SET_HEIGHT() {
+    return 8;
+}
+
+// Issuer Set Membership Template
+// Proves that issuer_hashed is a leaf of the issuer set Merkle tree with root issuer_set_root
+// without revealing the issuer or its position.
+// Leaves are the hashed issuer pubkeys, empty leaves are zero.
+template IssuerSetMembership(levels) {
+    signal input issuer_hashed;
+    signal input issuer_set_root;
+    signal input pathElements[levels];
+    signal input leafIndex;
+
+    // An empty leaf is not an issuer
+    component isEmpty = IsZero();
+    isEmpty.in <== issuer_hashed;
+    isEmpty.out === 0;
+
+    component merkleProof = MerkleProof(levels);
+    merkleProof.leaf <== issuer_hashed;
+    merkleProof.pathElements <== pathElements;
+    merkleProof.leafIndex <== leafIndex;
+    merkleProof.root === issuer_set_root;
+}
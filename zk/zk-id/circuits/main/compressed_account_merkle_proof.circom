@@ -0,0 +1,22 @@
+pragma circom 2.0.0;
+
+include "../compressed_account_merkle_proof.circom";
+
+// Main component for credentials in v1 concurrent state trees (26 levels) with 4 attributes
+component main {
+    public [
+        owner_hashed,
+        merkle_tree_hashed,
+        discriminator,
+        issuer_hashed,
+        expectedRoot,
+        verification_id,
+        public_encrypted_data_hash,
+        nullifier,
+        current_slot,
+        attribute_min,
+        attribute_max,
+        campaign_id,
+        campaign_nullifier
+    ]
+} = CompressedAccountMerkleProof(STATE_TREE_V1_HEIGHT(), 4);
@@ -4,6 +4,16 @@ include "../node_modules/circomlib/circuits/poseidon.circom";
 include "../node_modules/circomlib/circuits/bitify.circom";
 include "../node_modules/circomlib/circuits/switcher.circom";
 
+// Height of v1 concurrent state Merkle trees
+function STATE_TREE_V1_HEIGHT() {
+    return 26;
+}
+
+// Height of v2 batched state Merkle trees
+function STATE_TREE_V2_HEIGHT() {
+    return 32;
+}
+
 // Merkle Proof Verification Template
 // Verifies that a leaf is in a Merkle tree with a given root
 template MerkleProof(levels) {
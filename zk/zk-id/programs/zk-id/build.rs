@@ -1,32 +1,98 @@
+use std::path::Path;
+
 use groth16_solana::vk_parser::generate_vk_file;
+use zk_id_artifacts::{check_artifacts, check_sources, ArtifactError, Manifest};
 
 fn main() {
+    println!("cargo:rerun-if-changed=../../circuits");
+    println!("cargo:rerun-if-changed=../../package.json");
+    println!("cargo:rerun-if-changed=../../build/artifacts.json");
     println!("cargo:rerun-if-changed=../../build/verification_key.json");
+    println!("cargo:rerun-if-changed=../../build/verification_key_v2.json");
     println!("cargo:rerun-if-changed=../../build/compressed_account_merkle_proof_js");
+    println!("cargo:rerun-if-changed=../../build/compressed_account_merkle_proof_v2_js");
+    println!("cargo:rerun-if-changed=../../build/verification_key_issuer_set.json");
+    println!("cargo:rerun-if-changed=../../build/verification_key_issuer_set_v2.json");
+    println!("cargo:rerun-if-changed=../../build/compressed_account_merkle_proof_issuer_set_js");
+    println!("cargo:rerun-if-changed=../../build/compressed_account_merkle_proof_issuer_set_v2_js");
+    println!("cargo:rustc-check-cfg=cfg(verifying_key_v2)");
+    println!("cargo:rustc-check-cfg=cfg(verifying_key_issuer_set)");
 
-    // Generate the verifying key Rust file from the JSON
-    let vk_json_path = "../../build/verification_key.json";
-    let output_dir = "./src";
-    let output_file = "verifying_key.rs";
-
-    if std::path::Path::new(vk_json_path).exists() {
-        generate_vk_file(vk_json_path, output_dir, output_file)
-            .expect("Failed to generate verifying key Rust file");
-        // Successfully generated verifying_key.rs
-    } else {
-        println!("cargo:warning=Verification key JSON not found. Run './scripts/setup.sh' first.");
-    }
-
-    // Only transpile witness generators for non-Solana targets
     // Check the TARGET environment variable since build scripts run on the host
     let target = std::env::var("TARGET").unwrap_or_default();
-    if !target.contains("sbf") && !target.contains("solana") {
-        let witness_wasm_dir = "../../build/compressed_account_merkle_proof_js";
-        if std::path::Path::new(witness_wasm_dir).exists() {
-            rust_witness::transpile::transpile_wasm(witness_wasm_dir.to_string());
-            // Successfully transpiled witness generator
-        } else {
-            println!("cargo:warning=Witness WASM not found. Run './scripts/setup.sh' first.");
+    let is_solana = target.contains("sbf") || target.contains("solana");
+
+    // The circuit sources must match circuits/manifest.json,
+    // and the artifacts in build/ must be generated from them by ./scripts/setup.sh.
+    let project_dir = Path::new("../..");
+    let manifest = check_sources(project_dir).unwrap_or_else(|error| panic!("{}", error));
+    match check_artifacts(project_dir, &manifest) {
+        Ok(()) => {}
+        // The program doesn't use the artifacts, verifying keys are registered on-chain.
+        Err(ArtifactError::MissingArtifact(path)) if is_solana => {
+            println!(
+                "cargo:warning={} not found, skipping verifying key generation. Run './scripts/setup.sh' first.",
+                path.display()
+            );
+            return;
         }
+        Err(error) => panic!("{}", error),
+    }
+
+    // Generate the verifying key Rust file from the JSON into OUT_DIR,
+    // it always matches the artifacts checked against circuits/manifest.json above.
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    generate_vk_file(
+        &verification_key_path(project_dir, &manifest, "compressed_account_merkle_proof"),
+        &out_dir,
+        "verifying_key.rs",
+    )
+    .expect("Failed to generate verifying key Rust file");
+
+    // The verifying key of the v2 state tree circuit is only used off-chain (tests, clients),
+    // it is exposed with cfg(verifying_key_v2).
+    generate_vk_file(
+        &verification_key_path(project_dir, &manifest, "compressed_account_merkle_proof_v2"),
+        &out_dir,
+        "verifying_key_v2.rs",
+    )
+    .expect("Failed to generate v2 verifying key Rust file");
+    println!("cargo:rustc-cfg=verifying_key_v2");
+
+    // Verifying keys of the issuer set circuits (anonymity set mode), also only used off-chain.
+    for (circuit, output_file) in [
+        (
+            "compressed_account_merkle_proof_issuer_set",
+            "verifying_key_issuer_set.rs",
+        ),
+        (
+            "compressed_account_merkle_proof_issuer_set_v2",
+            "verifying_key_issuer_set_v2.rs",
+        ),
+    ] {
+        generate_vk_file(
+            &verification_key_path(project_dir, &manifest, circuit),
+            &out_dir,
+            output_file,
+        )
+        .expect("Failed to generate issuer set verifying key Rust file");
     }
+    println!("cargo:rustc-cfg=verifying_key_issuer_set");
+
+    // Only transpile witness generators for non-Solana targets
+    if !is_solana {
+        // Transpiles the witness generators of all circuits in build/ into one library
+        rust_witness::transpile::transpile_wasm("../../build".to_string());
+    }
+}
+
+fn verification_key_path(project_dir: &Path, manifest: &Manifest, circuit: &str) -> String {
+    manifest
+        .circuits
+        .iter()
+        .find(|manifest_circuit| manifest_circuit.name == circuit)
+        .unwrap_or_else(|| panic!("Circuit {} is not in circuits/manifest.json", circuit))
+        .verification_key_path(project_dir)
+        .to_string_lossy()
+        .to_string()
 }
@@ -0,0 +1,1132 @@
+//! Client side helpers to prove credential ownership and verify it with `zk_verify_credential`.
+//!
+//! Derives credential keypairs and nullifiers, fetches the credential's Merkle proof through the indexer,
+//! generates the witness with the circuits transpiled by build.rs and proves it with the zkeys of `./scripts/setup.sh`.
+//! Event data is encrypted to the verifier, see [`encryption`].
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anchor_lang::{AnchorDeserialize, InstructionData, ToAccountMetas};
+use circom_prover::{prover::ProofLib, witness::WitnessFn, CircomProver};
+use groth16_solana::{
+    groth16::{Groth16Verifier, Groth16Verifyingkey},
+    proof_parser::circom_prover::{
+        convert_proof, convert_proof_to_compressed, convert_public_inputs,
+    },
+};
+use light_batched_merkle_tree::merkle_tree::BatchedMerkleTreeAccount;
+use light_client::{
+    indexer::{AddressWithTree, CompressedAccount, Indexer, IndexerError, TreeInfo},
+    rpc::{Rpc, RpcError},
+};
+use light_compressed_account::TreeType;
+use light_hasher::{
+    hash_to_field_size::{hash_to_bn254_field_size_be, hashv_to_bn254_field_size_be_const_array},
+    Hasher, Poseidon, Sha256,
+};
+use light_sdk::{
+    address::v2::derive_address,
+    instruction::{
+        account_meta::CompressedAccountMetaReadOnly, CompressedProof, PackedAccounts,
+        PackedAddressTreeInfo, SystemAccountMetaConfig, ValidityProof,
+    },
+};
+use num_bigint::BigUint;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use thiserror::Error;
+
+pub mod encryption;
+
+use crate::{
+    campaign_input, CredentialAccount, CredentialProofData, VerificationAccount,
+    CAMPAIGN_NULLIFIER, CONFIG, NUM_CREDENTIAL_ATTRIBUTES, NUM_PUBLIC_INPUTS, VERIFYING_KEY,
+    ZK_ID_CHECK,
+};
+
+// The witness library is transpiled and linked by build.rs.
+rust_witness::witness!(compressedaccountmerkleproof);
+rust_witness::witness!(compressedaccountmerkleproofv2);
+rust_witness::witness!(compressedaccountmerkleproofissuerset);
+rust_witness::witness!(compressedaccountmerkleproofissuersetv2);
+
+/// Circuit ids the verifying keys of the credential circuits for v1 and v2 state trees are registered with.
+pub const CREDENTIAL_CIRCUIT_ID: u32 = 0;
+pub const CREDENTIAL_CIRCUIT_V2_ID: u32 = 1;
+/// Circuit ids of the issuer set circuits (anonymity set mode) for v1 and v2 state trees.
+pub const CREDENTIAL_ISSUER_SET_CIRCUIT_ID: u32 = 2;
+pub const CREDENTIAL_ISSUER_SET_CIRCUIT_V2_ID: u32 = 3;
+
+/// Height of the issuer set Merkle tree, ISSUER_SET_HEIGHT() in circuits/issuer_set.circom.
+pub const ISSUER_SET_HEIGHT: usize = 8;
+
+/// Length of the root history of v1 state trees.
+const STATE_TREE_V1_ROOT_HISTORY_LEN: u64 = 2400;
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("Rpc error: {0}")]
+    Rpc(#[from] RpcError),
+    #[error("Indexer error: {0}")]
+    Indexer(#[from] IndexerError),
+    #[error("Deserialization error: {0}")]
+    Deserialize(#[from] std::io::Error),
+    #[error("Prover error: {0}")]
+    Prover(String),
+    #[error("Compressed account has no data.")]
+    MissingAccountData,
+    #[error("Account {0} not found.")]
+    AccountNotFound(Pubkey),
+    #[error("No credential circuit for tree type {0:?}.")]
+    UnsupportedTreeType(TreeType),
+    #[error("State tree {0} cannot be deserialized.")]
+    InvalidStateTree(Pubkey),
+    #[error("Root is not in the root history of the state tree.")]
+    RootNotFound,
+    #[error("Credential data hash does not match the credential account.")]
+    DataHashMismatch,
+    #[error("Leaf hash does not match the credential account hash.")]
+    LeafHashMismatch,
+    #[error("Generated proof is invalid.")]
+    InvalidProof,
+    #[error("Event data cannot be encrypted.")]
+    Encryption,
+    #[error("Event data cannot be decrypted.")]
+    Decryption,
+    #[error("Issuer set has more than 2^ISSUER_SET_HEIGHT issuers.")]
+    IssuerSetTooLarge,
+    #[error("Issuer {0} is not in the issuer set.")]
+    IssuerNotInSet(Pubkey),
+    #[error("Issuer set root does not match the verification.")]
+    IssuerSetMismatch,
+    #[error("Credential proof does not reveal its issuer.")]
+    MissingIssuer,
+    #[error("Validity proof has no state tree of the verification account.")]
+    MissingStateTree,
+    #[error("Credential account was revoked or changed.")]
+    CredentialRevoked,
+}
+
+/// Derives a credential keypair from a Solana keypair
+/// The private key is derived by signing "CREDENTIAL" and truncating to 248 bits
+/// The public key is Poseidon(private_key)
+#[derive(Debug, Clone)]
+pub struct CredentialKeypair {
+    pub private_key: [u8; 32], // 248 bits
+    pub public_key: [u8; 32],  // Poseidon hash of private key
+}
+
+impl CredentialKeypair {
+    pub fn new(solana_keypair: &Keypair) -> Self {
+        // Sign the message "CREDENTIAL" with the Solana keypair
+        let message = b"CREDENTIAL";
+        let signature = solana_keypair.sign_message(message);
+
+        // Hash the signature to get entropy
+        let hashed = Sha256::hash(signature.as_ref()).unwrap();
+
+        // Truncate to 248 bits (31 bytes) for BN254 field compatibility
+        let mut private_key = [0u8; 32];
+        private_key[1..32].copy_from_slice(&hashed[0..31]);
+
+        let public_key = Poseidon::hashv(&[&private_key]).unwrap();
+
+        Self {
+            private_key,
+            public_key,
+        }
+    }
+
+    /// Compute nullifier for a given verification_id and credential data hash
+    /// Nullifier = Poseidon(verification_id, private_key, data_hash)
+    /// where data_hash = Poseidon(issuer_hashed, public_key, expiry_slot, attributes_hash)
+    pub fn compute_nullifier(&self, verification_id: &[u8; 31], data_hash: &[u8; 32]) -> [u8; 32] {
+        // verification_id needs to be padded to 32 bytes for Poseidon
+        let mut padded_verification = [0u8; 32];
+        padded_verification[1..32].copy_from_slice(verification_id);
+
+        Poseidon::hashv(&[&padded_verification, &self.private_key, data_hash]).unwrap()
+    }
+
+    /// Compute the campaign nullifier of a sybil mode verification
+    /// Campaign nullifier = Poseidon(campaign_input, private_key), see [`crate::campaign_input`]
+    /// It is the same for all credentials of this keypair and all verifications of the campaign.
+    pub fn compute_campaign_nullifier(&self, campaign_input: &[u8; 32]) -> [u8; 32] {
+        Poseidon::hashv(&[campaign_input, &self.private_key]).unwrap()
+    }
+}
+
+/// Attributes of a credential
+/// The issuer shares the values and the blinding with the holder off-chain,
+/// the credential account only stores attributes_hash = Poseidon(values, blinding).
+#[derive(Debug, Clone)]
+pub struct CredentialAttributes {
+    pub values: [u64; NUM_CREDENTIAL_ATTRIBUTES],
+    pub blinding: [u8; 32],
+}
+
+impl CredentialAttributes {
+    pub fn new(values: [u64; NUM_CREDENTIAL_ATTRIBUTES]) -> Self {
+        // Random blinding truncated to 248 bits for BN254 field compatibility
+        let mut blinding = Pubkey::new_unique().to_bytes();
+        blinding[0] = 0;
+        Self { values, blinding }
+    }
+
+    pub fn hash(&self) -> [u8; 32] {
+        let values: Vec<[u8; 32]> = self
+            .values
+            .iter()
+            .map(|value| u64_to_field(*value))
+            .collect();
+        let mut inputs: Vec<&[u8]> = values.iter().map(|value| value.as_slice()).collect();
+        inputs.push(self.blinding.as_slice());
+        Poseidon::hashv(&inputs).unwrap()
+    }
+}
+
+/// Public predicates of a verification: attribute_min[i] <= attributes[i] <= attribute_max[i]
+#[derive(Debug, Clone, Copy)]
+pub struct AttributePredicates {
+    pub attribute_min: [u64; NUM_CREDENTIAL_ATTRIBUTES],
+    pub attribute_max: [u64; NUM_CREDENTIAL_ATTRIBUTES],
+}
+
+impl AttributePredicates {
+    /// Does not constrain any attribute
+    pub fn none() -> Self {
+        Self {
+            attribute_min: [0; NUM_CREDENTIAL_ATTRIBUTES],
+            attribute_max: [u64::MAX; NUM_CREDENTIAL_ATTRIBUTES],
+        }
+    }
+
+    pub fn with_range(mut self, index: usize, min: u64, max: u64) -> Self {
+        self.attribute_min[index] = min;
+        self.attribute_max[index] = max;
+        self
+    }
+}
+
+/// Big-endian field element of a u64, as hashed by LightHasher
+pub fn u64_to_field(value: u64) -> [u8; 32] {
+    let mut field = [0u8; 32];
+    field[24..].copy_from_slice(&value.to_be_bytes());
+    field
+}
+
+/// data_hash = Poseidon(issuer_hashed, credential_pubkey, expiry_slot, attributes_hash)
+pub fn credential_data_hash(
+    issuer_hashed: &[u8; 32],
+    credential_pubkey: &[u8; 32],
+    expiry_slot: u64,
+    attributes_hash: &[u8; 32],
+) -> [u8; 32] {
+    Poseidon::hashv(&[
+        issuer_hashed,
+        credential_pubkey,
+        &u64_to_field(expiry_slot),
+        attributes_hash,
+    ])
+    .unwrap()
+}
+
+pub fn config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[CONFIG], &crate::ID).0
+}
+
+/// Program data account of the program, its upgrade authority initializes the config.
+pub fn program_data_pda() -> Pubkey {
+    Pubkey::find_program_address(
+        &[crate::ID.as_ref()],
+        &anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+    )
+    .0
+}
+
+pub fn verifying_key_pda(circuit_id: u32) -> Pubkey {
+    Pubkey::find_program_address(&[VERIFYING_KEY, &circuit_id.to_le_bytes()], &crate::ID).0
+}
+
+/// Issuers a verifier accepts in anonymity set mode, see `zk_verify_credential_issuer_set`.
+/// Poseidon Merkle tree of height ISSUER_SET_HEIGHT whose leaves are the hashed issuer pubkeys,
+/// empty leaves are zero.
+#[derive(Debug, Clone)]
+pub struct IssuerSet {
+    issuers: Vec<Pubkey>,
+    /// Levels of the tree from the leaves to the root.
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl IssuerSet {
+    pub fn new(issuers: &[Pubkey]) -> Result<Self, ClientError> {
+        if issuers.len() > 1 << ISSUER_SET_HEIGHT {
+            return Err(ClientError::IssuerSetTooLarge);
+        }
+        // Same hashing as the issuer_hashed public input
+        let mut leaves: Vec<[u8; 32]> = issuers
+            .iter()
+            .map(|issuer| {
+                hashv_to_bn254_field_size_be_const_array::<2>(&[issuer.as_ref()]).unwrap()
+            })
+            .collect();
+        leaves.resize(1 << ISSUER_SET_HEIGHT, [0u8; 32]);
+
+        let mut layers = vec![leaves];
+        for _ in 0..ISSUER_SET_HEIGHT {
+            let layer = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| Poseidon::hashv(&[&pair[0], &pair[1]]).unwrap())
+                .collect();
+            layers.push(layer);
+        }
+        Ok(Self {
+            issuers: issuers.to_vec(),
+            layers,
+        })
+    }
+
+    /// Root stored in the verification, see `create_verification`.
+    pub fn root(&self) -> [u8; 32] {
+        self.layers[ISSUER_SET_HEIGHT][0]
+    }
+
+    /// Returns the index and the path elements of an issuer, `None` if it isn't in the set.
+    pub fn merkle_proof(&self, issuer: &Pubkey) -> Option<(u32, Vec<[u8; 32]>)> {
+        let index = self.issuers.iter().position(|member| member == issuer)?;
+        let path = self.layers[..ISSUER_SET_HEIGHT]
+            .iter()
+            .enumerate()
+            .map(|(level, layer)| layer[(index >> level) ^ 1])
+            .collect();
+        Some((index as u32, path))
+    }
+}
+
+/// Credential circuit matching the height of a state tree.
+#[derive(Clone)]
+pub struct CredentialCircuit {
+    pub tree_type: TreeType,
+    pub circuit_id: u32,
+    pub zkey_path: PathBuf,
+    pub verifying_key: &'static Groth16Verifyingkey<'static>,
+    /// Whether the circuit hides the issuer in an issuer set.
+    pub issuer_set: bool,
+}
+
+impl CredentialCircuit {
+    /// `build_dir` contains the zkeys generated by `./scripts/setup.sh`.
+    pub fn new(tree_type: TreeType, build_dir: impl AsRef<Path>) -> Result<Self, ClientError> {
+        let (circuit_id, zkey, verifying_key) = match tree_type {
+            #[cfg(verifying_key_v2)]
+            TreeType::StateV2 => (
+                CREDENTIAL_CIRCUIT_V2_ID,
+                "compressed_account_merkle_proof_v2_final.zkey",
+                &crate::verifying_key_v2::VERIFYINGKEY,
+            ),
+            #[cfg(not(verifying_key_v2))]
+            TreeType::StateV2 => return Err(ClientError::UnsupportedTreeType(tree_type)),
+            _ => (
+                CREDENTIAL_CIRCUIT_ID,
+                "compressed_account_merkle_proof_final.zkey",
+                &crate::verifying_key::VERIFYINGKEY,
+            ),
+        };
+        Ok(Self {
+            tree_type,
+            circuit_id,
+            zkey_path: build_dir.as_ref().join(zkey),
+            verifying_key,
+            issuer_set: false,
+        })
+    }
+
+    /// Issuer set circuit (anonymity set mode) matching the height of a state tree.
+    pub fn new_issuer_set(
+        tree_type: TreeType,
+        build_dir: impl AsRef<Path>,
+    ) -> Result<Self, ClientError> {
+        let (circuit_id, zkey, verifying_key) = match tree_type {
+            #[cfg(verifying_key_issuer_set)]
+            TreeType::StateV2 => (
+                CREDENTIAL_ISSUER_SET_CIRCUIT_V2_ID,
+                "compressed_account_merkle_proof_issuer_set_v2_final.zkey",
+                &crate::verifying_key_issuer_set_v2::VERIFYINGKEY,
+            ),
+            #[cfg(verifying_key_issuer_set)]
+            _ => (
+                CREDENTIAL_ISSUER_SET_CIRCUIT_ID,
+                "compressed_account_merkle_proof_issuer_set_final.zkey",
+                &crate::verifying_key_issuer_set::VERIFYINGKEY,
+            ),
+            #[cfg(not(verifying_key_issuer_set))]
+            _ => return Err(ClientError::UnsupportedTreeType(tree_type)),
+        };
+        Ok(Self {
+            tree_type,
+            circuit_id,
+            zkey_path: build_dir.as_ref().join(zkey),
+            verifying_key,
+            issuer_set: true,
+        })
+    }
+
+    pub fn witness_fn(&self) -> WitnessFn {
+        match (self.tree_type, self.issuer_set) {
+            (TreeType::StateV2, false) => {
+                WitnessFn::RustWitness(compressedaccountmerkleproofv2_witness)
+            }
+            (TreeType::StateV2, true) => {
+                WitnessFn::RustWitness(compressedaccountmerkleproofissuersetv2_witness)
+            }
+            (_, false) => WitnessFn::RustWitness(compressedaccountmerkleproof_witness),
+            (_, true) => WitnessFn::RustWitness(compressedaccountmerkleproofissuerset_witness),
+        }
+    }
+}
+
+/// Merkle proof of a credential account against a root of its state tree.
+#[derive(Debug, Clone)]
+pub struct CredentialMerkleProof {
+    pub merkle_tree: Pubkey,
+    pub tree_type: TreeType,
+    pub leaf_index: u32,
+    pub proof: Vec<[u8; 32]>,
+    pub root: [u8; 32],
+    /// Index of `root` in the root history of the state tree.
+    pub root_index: u16,
+}
+
+/// Fetches the Merkle proof of a credential account from the indexer.
+pub async fn get_credential_merkle_proof<R>(
+    rpc: &mut R,
+    credential_account: &CompressedAccount,
+) -> Result<CredentialMerkleProof, ClientError>
+where
+    R: Rpc + Indexer,
+{
+    let mut proofs = rpc
+        .get_multiple_compressed_account_proofs(vec![credential_account.hash], None)
+        .await?
+        .value
+        .items;
+    let merkle_proof = proofs.remove(0);
+    let merkle_tree = merkle_proof.merkle_tree;
+    let tree_type = credential_account.tree_info.tree_type;
+
+    let root_index = match tree_type {
+        TreeType::StateV2 => {
+            // The root history length of batched trees is configurable, look the root up on-chain.
+            let mut tree_account = rpc
+                .get_account(merkle_tree)
+                .await?
+                .ok_or(ClientError::AccountNotFound(merkle_tree))?;
+            let batched_tree = BatchedMerkleTreeAccount::state_from_bytes(
+                &mut tree_account.data,
+                &merkle_tree.to_bytes().into(),
+            )
+            .map_err(|_| ClientError::InvalidStateTree(merkle_tree))?;
+            batched_tree
+                .root_history
+                .iter()
+                .position(|root| *root == merkle_proof.root)
+                .ok_or(ClientError::RootNotFound)? as u16
+        }
+        _ => (merkle_proof.root_seq % STATE_TREE_V1_ROOT_HISTORY_LEN) as u16,
+    };
+
+    Ok(CredentialMerkleProof {
+        merkle_tree,
+        tree_type,
+        leaf_index: merkle_proof.leaf_index as u32,
+        proof: merkle_proof.proof,
+        root: merkle_proof.root,
+        root_index,
+    })
+}
+
+/// Private and public inputs of a credential proof.
+pub struct CredentialProofInputs<'a> {
+    pub credential_account: &'a CompressedAccount,
+    pub merkle_proof: &'a CredentialMerkleProof,
+    pub credential: &'a CredentialKeypair,
+    pub attributes: &'a CredentialAttributes,
+    pub predicates: &'a AttributePredicates,
+    pub current_slot: u64,
+    /// Encrypted event data, see [`encryption::encrypt_event_data`].
+    pub public_data: &'a [u8],
+    pub verification_id: &'a [u8; 31],
+    /// Campaign of a sybil mode verification, see [`crate::campaign_input`].
+    pub campaign_input: Option<[u8; 32]>,
+    /// Issuer set of an anonymity set mode verification, requires an issuer set circuit.
+    pub issuer_set: Option<&'a IssuerSet>,
+}
+
+/// Generates and locally verifies a credential proof.
+/// Returns the compressed proof, the nullifier and the campaign nullifier (zero without a campaign).
+pub fn generate_credential_proof(
+    circuit: &CredentialCircuit,
+    inputs: &CredentialProofInputs,
+) -> Result<(CompressedProof, [u8; 32], [u8; 32]), ClientError> {
+    let credential_account = inputs.credential_account;
+    let merkle_proof = inputs.merkle_proof;
+    let credential_data = credential_account
+        .data
+        .as_ref()
+        .ok_or(ClientError::MissingAccountData)?;
+    let credential_account_parsed =
+        CredentialAccount::deserialize(&mut credential_data.data.as_slice())?;
+
+    let owner_hashed = hash_to_bn254_field_size_be(crate::ID.as_ref());
+    let merkle_tree_hashed = hash_to_bn254_field_size_be(merkle_proof.merkle_tree.as_ref());
+    // Same hashing as the #[hash] attribute on-chain
+    let issuer_hashed =
+        hashv_to_bn254_field_size_be_const_array::<2>(&[credential_account_parsed.issuer.as_ref()])
+            .unwrap();
+
+    // Public data is hashed like on-chain: Sha256(len, data) truncated to the field size
+    let mut hash_input = Vec::new();
+    hash_input.extend_from_slice((inputs.public_data.len() as u32).to_le_bytes().as_ref());
+    hash_input.extend_from_slice(inputs.public_data);
+    let mut encrypted_data_hash = Sha256::hash(hash_input.as_slice()).unwrap();
+    encrypted_data_hash[0] = 0;
+
+    let data_hash = credential_data_hash(
+        &issuer_hashed,
+        &inputs.credential.public_key,
+        credential_account_parsed.expiry_slot,
+        &inputs.attributes.hash(),
+    );
+    if data_hash != credential_data.data_hash {
+        return Err(ClientError::DataHashMismatch);
+    }
+
+    // 32-byte array with leaf_index at [28..32], LE in v1 and BE in v2 (batched) trees
+    let mut leaf_index_bytes = [0u8; 32];
+    if merkle_proof.tree_type == TreeType::StateV2 {
+        leaf_index_bytes[28..32].copy_from_slice(&merkle_proof.leaf_index.to_be_bytes());
+    } else {
+        leaf_index_bytes[28..32].copy_from_slice(&merkle_proof.leaf_index.to_le_bytes());
+    }
+
+    // 32-byte array with discriminator at [24..32] and prefix 2 at [23]
+    let discriminator = credential_data.discriminator;
+    let mut discriminator_bytes = [0u8; 32];
+    discriminator_bytes[24..32].copy_from_slice(&discriminator);
+    discriminator_bytes[23] = 2;
+
+    let address = credential_account.address.unwrap_or([0u8; 32]);
+    let leaf_hash = Poseidon::hashv(&[
+        owner_hashed.as_slice(),
+        leaf_index_bytes.as_slice(),
+        merkle_tree_hashed.as_slice(),
+        address.as_slice(),
+        discriminator_bytes.as_slice(),
+        data_hash.as_slice(),
+    ])
+    .unwrap();
+    if leaf_hash != credential_account.hash {
+        return Err(ClientError::LeafHashMismatch);
+    }
+
+    let mut padded_verification = [0u8; 32];
+    padded_verification[1..32].copy_from_slice(inputs.verification_id);
+    let nullifier = inputs
+        .credential
+        .compute_nullifier(inputs.verification_id, &data_hash);
+    // Without a campaign the circuit expects campaign_id = 0 and campaign_nullifier = 0
+    let campaign_input = inputs.campaign_input.unwrap_or_default();
+    let campaign_nullifier = inputs
+        .campaign_input
+        .map(|input| inputs.credential.compute_campaign_nullifier(&input))
+        .unwrap_or_default();
+
+    let field = |bytes: &[u8]| vec![BigUint::from_bytes_be(bytes).to_string()];
+    let values =
+        |values: &[u64]| -> Vec<String> { values.iter().map(|value| value.to_string()).collect() };
+    let mut proof_inputs: HashMap<String, Vec<String>> = HashMap::new();
+    proof_inputs.insert("owner_hashed".to_string(), field(&owner_hashed));
+    proof_inputs.insert(
+        "leaf_index".to_string(),
+        vec![merkle_proof.leaf_index.to_string()],
+    );
+    // account_leaf_index needs to be in the same format as the leaf hash
+    proof_inputs.insert("account_leaf_index".to_string(), field(&leaf_index_bytes));
+    proof_inputs.insert("address".to_string(), field(&address));
+    proof_inputs.insert("merkle_tree_hashed".to_string(), field(&merkle_tree_hashed));
+    proof_inputs.insert("discriminator".to_string(), field(&discriminator));
+    proof_inputs.insert("issuer_hashed".to_string(), field(&issuer_hashed));
+    proof_inputs.insert(
+        "credentialPrivateKey".to_string(),
+        field(&inputs.credential.private_key),
+    );
+    proof_inputs.insert(
+        "encrypted_data_hash".to_string(),
+        field(&encrypted_data_hash),
+    );
+    proof_inputs.insert(
+        "public_encrypted_data_hash".to_string(),
+        field(&encrypted_data_hash),
+    );
+    proof_inputs.insert("verification_id".to_string(), field(&padded_verification));
+    proof_inputs.insert("nullifier".to_string(), field(&nullifier));
+    proof_inputs.insert("campaign_id".to_string(), field(&campaign_input));
+    proof_inputs.insert("campaign_nullifier".to_string(), field(&campaign_nullifier));
+    proof_inputs.insert(
+        "current_slot".to_string(),
+        vec![inputs.current_slot.to_string()],
+    );
+    proof_inputs.insert(
+        "expiry_slot".to_string(),
+        vec![credential_account_parsed.expiry_slot.to_string()],
+    );
+    proof_inputs.insert("attributes".to_string(), values(&inputs.attributes.values));
+    proof_inputs.insert(
+        "attributes_blinding".to_string(),
+        field(&inputs.attributes.blinding),
+    );
+    proof_inputs.insert(
+        "attribute_min".to_string(),
+        values(&inputs.predicates.attribute_min),
+    );
+    proof_inputs.insert(
+        "attribute_max".to_string(),
+        values(&inputs.predicates.attribute_max),
+    );
+    proof_inputs.insert(
+        "pathElements".to_string(),
+        merkle_proof
+            .proof
+            .iter()
+            .map(|hash| BigUint::from_bytes_be(hash).to_string())
+            .collect(),
+    );
+    proof_inputs.insert("expectedRoot".to_string(), field(&merkle_proof.root));
+    // In anonymity set mode issuer_hashed is private and the issuer set root is public
+    if let Some(issuer_set) = inputs.issuer_set {
+        let (issuer_index, issuer_path) = issuer_set
+            .merkle_proof(&credential_account_parsed.issuer)
+            .ok_or(ClientError::IssuerNotInSet(
+                credential_account_parsed.issuer,
+            ))?;
+        proof_inputs.insert("issuer_set_root".to_string(), field(&issuer_set.root()));
+        proof_inputs.insert(
+            "issuerPathElements".to_string(),
+            issuer_path
+                .iter()
+                .map(|hash| BigUint::from_bytes_be(hash).to_string())
+                .collect(),
+        );
+        proof_inputs.insert("issuer_index".to_string(), vec![issuer_index.to_string()]);
+    }
+
+    let circuit_inputs =
+        serde_json::to_string(&proof_inputs).map_err(|e| ClientError::Prover(e.to_string()))?;
+    let zkey_path = circuit.zkey_path.to_string_lossy().to_string();
+    let proof = CircomProver::prove(
+        ProofLib::Arkworks,
+        circuit.witness_fn(),
+        circuit_inputs,
+        zkey_path,
+    )
+    .map_err(|e| ClientError::Prover(format!("{:?}", e)))?;
+
+    // Convert to groth16-solana format and compress
+    let (proof_a, proof_b, proof_c) =
+        convert_proof(&proof.proof).map_err(|e| ClientError::Prover(format!("{:?}", e)))?;
+    let (a, b, c) = convert_proof_to_compressed(&proof_a, &proof_b, &proof_c)
+        .map_err(|e| ClientError::Prover(format!("{:?}", e)))?;
+
+    // Verify with groth16-solana locally (same as on-chain)
+    let public_inputs: [[u8; 32]; NUM_PUBLIC_INPUTS] = convert_public_inputs(&proof.pub_inputs);
+    Groth16Verifier::new(
+        &proof_a,
+        &proof_b,
+        &proof_c,
+        &public_inputs,
+        circuit.verifying_key,
+    )
+    .map_err(|_| ClientError::InvalidProof)?
+    .verify()
+    .map_err(|_| ClientError::InvalidProof)?;
+
+    Ok((CompressedProof { a, b, c }, nullifier, campaign_nullifier))
+}
+
+/// Accounts and public inputs of a `zk_verify_credential` instruction.
+pub struct VerifyCredentialParams<'a> {
+    pub payer: Pubkey,
+    pub credential_account: &'a CompressedAccount,
+    pub verification_account: &'a CompressedAccount,
+    pub address_tree_info: TreeInfo,
+    pub credential: &'a CredentialKeypair,
+    pub attributes: &'a CredentialAttributes,
+    pub predicates: &'a AttributePredicates,
+    /// Plaintext of the event, encrypted to the verification's `encryption_pubkey`.
+    pub disclosed_data: Vec<u8>,
+}
+
+/// Credential proven in a `zk_verify_credentials` instruction.
+pub struct CredentialProofParams<'a> {
+    pub credential_account: &'a CompressedAccount,
+    pub credential: &'a CredentialKeypair,
+    pub attributes: &'a CredentialAttributes,
+    pub predicates: &'a AttributePredicates,
+    /// Plaintext of the event, encrypted to the verification's `encryption_pubkey`.
+    pub disclosed_data: Vec<u8>,
+    /// Hides the issuer in the verification's issuer set.
+    pub issuer_set: Option<&'a IssuerSet>,
+}
+
+/// Accounts and credentials of a `zk_verify_credentials` instruction.
+pub struct VerifyCredentialsParams<'a> {
+    pub payer: Pubkey,
+    pub verification_account: &'a CompressedAccount,
+    pub address_tree_info: TreeInfo,
+    pub credentials: Vec<CredentialProofParams<'a>>,
+}
+
+/// Proven credential with the state tree and circuit its proof is verified with.
+struct ProvenCredential {
+    proof: CredentialProofData,
+    merkle_tree: Pubkey,
+    circuit_id: u32,
+}
+
+/// Credential proof of a `zk_verify_credential` or `zk_verify_credential_issuer_set` instruction
+/// with the accounts and the validity proof of the verification.
+struct PreparedVerifyCredential {
+    proof: ValidityProof,
+    address_tree_info: PackedAddressTreeInfo,
+    output_state_tree_index: u8,
+    system_accounts_offset: u8,
+    verification_account_meta: CompressedAccountMetaReadOnly,
+    verification: VerificationAccount,
+    current_slot: u64,
+    credential_proof: CredentialProofData,
+    accounts: Vec<AccountMeta>,
+}
+
+/// Generates the credential proof against the current root of the credential's tree
+/// and returns the instruction data and accounts of `zk_verify_credential`.
+pub async fn build_verify_credential<R>(
+    rpc: &mut R,
+    build_dir: impl AsRef<Path>,
+    params: VerifyCredentialParams<'_>,
+) -> Result<(crate::instruction::ZkVerifyCredential, Vec<AccountMeta>), ClientError>
+where
+    R: Rpc + Indexer,
+{
+    let prepared = prepare_verify_credential(rpc, build_dir.as_ref(), params, None).await?;
+    let CredentialProofData {
+        input_root_index,
+        public_data,
+        credential_proof,
+        issuer,
+        nullifier,
+        attribute_min,
+        attribute_max,
+        campaign_nullifier,
+    } = prepared.credential_proof;
+    let instruction_data = crate::instruction::ZkVerifyCredential {
+        proof: prepared.proof,
+        address_tree_info: prepared.address_tree_info,
+        output_state_tree_index: prepared.output_state_tree_index,
+        system_accounts_offset: prepared.system_accounts_offset,
+        input_root_index,
+        public_data,
+        credential_proof,
+        issuer: issuer.ok_or(ClientError::MissingIssuer)?,
+        nullifier,
+        verification_account_meta: prepared.verification_account_meta,
+        verification: prepared.verification,
+        current_slot: prepared.current_slot,
+        attribute_min,
+        attribute_max,
+        campaign_nullifier,
+    };
+    Ok((instruction_data, prepared.accounts))
+}
+
+/// Generates a credential proof that hides the issuer in `issuer_set`
+/// and returns the instruction data and accounts of `zk_verify_credential_issuer_set`.
+/// The root of `issuer_set` must be the `issuer_set_root` of the verification.
+pub async fn build_verify_credential_issuer_set<R>(
+    rpc: &mut R,
+    build_dir: impl AsRef<Path>,
+    params: VerifyCredentialParams<'_>,
+    issuer_set: &IssuerSet,
+) -> Result<
+    (
+        crate::instruction::ZkVerifyCredentialIssuerSet,
+        Vec<AccountMeta>,
+    ),
+    ClientError,
+>
+where
+    R: Rpc + Indexer,
+{
+    let prepared =
+        prepare_verify_credential(rpc, build_dir.as_ref(), params, Some(issuer_set)).await?;
+    let CredentialProofData {
+        input_root_index,
+        public_data,
+        credential_proof,
+        nullifier,
+        attribute_min,
+        attribute_max,
+        campaign_nullifier,
+        ..
+    } = prepared.credential_proof;
+    let instruction_data = crate::instruction::ZkVerifyCredentialIssuerSet {
+        proof: prepared.proof,
+        address_tree_info: prepared.address_tree_info,
+        output_state_tree_index: prepared.output_state_tree_index,
+        system_accounts_offset: prepared.system_accounts_offset,
+        input_root_index,
+        public_data,
+        credential_proof,
+        nullifier,
+        verification_account_meta: prepared.verification_account_meta,
+        verification: prepared.verification,
+        current_slot: prepared.current_slot,
+        attribute_min,
+        attribute_max,
+        campaign_nullifier,
+    };
+    Ok((instruction_data, prepared.accounts))
+}
+
+async fn prepare_verify_credential<R>(
+    rpc: &mut R,
+    build_dir: &Path,
+    params: VerifyCredentialParams<'_>,
+    issuer_set: Option<&IssuerSet>,
+) -> Result<PreparedVerifyCredential, ClientError>
+where
+    R: Rpc + Indexer,
+{
+    let verification = deserialize_verification(params.verification_account)?;
+    // The credential must not be expired at the current slot
+    let current_slot = rpc.get_slot().await?;
+    let proven = prove_credential(
+        rpc,
+        build_dir,
+        &verification,
+        current_slot,
+        CredentialProofParams {
+            credential_account: params.credential_account,
+            credential: params.credential,
+            attributes: params.attributes,
+            predicates: params.predicates,
+            disclosed_data: params.disclosed_data,
+            issuer_set,
+        },
+    )
+    .await?;
+
+    let mut remaining_accounts = PackedAccounts::default();
+    remaining_accounts.add_pre_accounts_signer(params.payer);
+    let (proof, address_tree_info, verification_account_meta, output_state_tree_index) =
+        pack_verification(
+            rpc,
+            &mut remaining_accounts,
+            params.verification_account,
+            &params.address_tree_info,
+            &verification,
+            std::slice::from_ref(&proven.proof),
+        )
+        .await?;
+    let (remaining_accounts_metas, system_accounts_offset, _) =
+        remaining_accounts.to_account_metas();
+
+    let accounts = crate::accounts::VerifyAccounts {
+        signer: params.payer,
+        input_merkle_tree: proven.merkle_tree,
+        verifying_key: verifying_key_pda(proven.circuit_id),
+    };
+
+    Ok(PreparedVerifyCredential {
+        proof,
+        address_tree_info,
+        output_state_tree_index,
+        system_accounts_offset: system_accounts_offset as u8,
+        verification_account_meta,
+        verification,
+        current_slot,
+        credential_proof: proven.proof,
+        accounts: [accounts.to_account_metas(None), remaining_accounts_metas].concat(),
+    })
+}
+
+/// Generates the proofs of several credentials for one verification
+/// and returns the instruction data and accounts of `zk_verify_credentials`.
+pub async fn build_verify_credentials<R>(
+    rpc: &mut R,
+    build_dir: impl AsRef<Path>,
+    params: VerifyCredentialsParams<'_>,
+) -> Result<(crate::instruction::ZkVerifyCredentials, Vec<AccountMeta>), ClientError>
+where
+    R: Rpc + Indexer,
+{
+    let verification = deserialize_verification(params.verification_account)?;
+    let current_slot = rpc.get_slot().await?;
+
+    let mut remaining_accounts = PackedAccounts::default();
+    let mut credential_proofs = Vec::with_capacity(params.credentials.len());
+    for credential in params.credentials {
+        let proven = prove_credential(
+            rpc,
+            build_dir.as_ref(),
+            &verification,
+            current_slot,
+            credential,
+        )
+        .await?;
+        // The state tree and verifying key of proof i are remaining accounts 2 * i and 2 * i + 1.
+        remaining_accounts
+            .add_pre_accounts_meta(AccountMeta::new_readonly(proven.merkle_tree, false));
+        remaining_accounts.add_pre_accounts_meta(AccountMeta::new_readonly(
+            verifying_key_pda(proven.circuit_id),
+            false,
+        ));
+        credential_proofs.push(proven.proof);
+    }
+
+    let (proof, address_tree_info, verification_account_meta, output_state_tree_index) =
+        pack_verification(
+            rpc,
+            &mut remaining_accounts,
+            params.verification_account,
+            &params.address_tree_info,
+            &verification,
+            &credential_proofs,
+        )
+        .await?;
+    let (remaining_accounts_metas, system_accounts_offset, _) =
+        remaining_accounts.to_account_metas();
+
+    let instruction_data = crate::instruction::ZkVerifyCredentials {
+        proof,
+        address_tree_info,
+        output_state_tree_index,
+        system_accounts_offset: system_accounts_offset as u8,
+        verification_account_meta,
+        verification,
+        current_slot,
+        credential_proofs,
+    };
+
+    let accounts = crate::accounts::GenericAnchorAccounts {
+        signer: params.payer,
+    };
+
+    Ok((
+        instruction_data,
+        [accounts.to_account_metas(None), remaining_accounts_metas].concat(),
+    ))
+}
+
+fn deserialize_verification(
+    verification_account: &CompressedAccount,
+) -> Result<VerificationAccount, ClientError> {
+    let verification_data = verification_account
+        .data
+        .as_ref()
+        .ok_or(ClientError::MissingAccountData)?;
+    Ok(VerificationAccount::deserialize(
+        &mut verification_data.data.as_slice(),
+    )?)
+}
+
+/// Encrypts the disclosed data to the verifier and proves the credential.
+async fn prove_credential<R>(
+    rpc: &mut R,
+    build_dir: &Path,
+    verification: &VerificationAccount,
+    current_slot: u64,
+    params: CredentialProofParams<'_>,
+) -> Result<ProvenCredential, ClientError>
+where
+    R: Rpc + Indexer,
+{
+    // Revoked credentials are burned, their leaf stays in the state tree until a forester nullifies it.
+    // Proving against it would succeed until then, the indexer no longer returns the account.
+    let credential_address = params
+        .credential_account
+        .address
+        .ok_or(ClientError::MissingAccountData)?;
+    let current_credential_account = rpc
+        .get_compressed_account(credential_address, None)
+        .await?
+        .value;
+    if current_credential_account.map(|account| account.hash)
+        != Some(params.credential_account.hash)
+    {
+        return Err(ClientError::CredentialRevoked);
+    }
+    let merkle_proof = get_credential_merkle_proof(rpc, params.credential_account).await?;
+    let circuit = match params.issuer_set {
+        Some(issuer_set) => {
+            if verification.issuer_set_root != Some(issuer_set.root()) {
+                return Err(ClientError::IssuerSetMismatch);
+            }
+            CredentialCircuit::new_issuer_set(merkle_proof.tree_type, build_dir)?
+        }
+        None => CredentialCircuit::new(merkle_proof.tree_type, build_dir)?,
+    };
+
+    let credential_data = params
+        .credential_account
+        .data
+        .as_ref()
+        .ok_or(ClientError::MissingAccountData)?;
+    let credential_account = CredentialAccount::deserialize(&mut credential_data.data.as_slice())?;
+    let verification_id = verification.verification_id;
+    let public_data = encryption::encrypt_event_data(
+        &verification.encryption_pubkey,
+        &verification_id,
+        &params.disclosed_data,
+    )?;
+
+    let (credential_proof, nullifier, campaign_nullifier) = generate_credential_proof(
+        &circuit,
+        &CredentialProofInputs {
+            credential_account: params.credential_account,
+            merkle_proof: &merkle_proof,
+            credential: params.credential,
+            attributes: params.attributes,
+            predicates: params.predicates,
+            current_slot,
+            public_data: &public_data,
+            verification_id: &verification_id,
+            campaign_input: verification
+                .campaign_id
+                .map(|campaign_id| campaign_input(&verification.verifier, &campaign_id)),
+            issuer_set: params.issuer_set,
+        },
+    )?;
+
+    Ok(ProvenCredential {
+        proof: CredentialProofData {
+            input_root_index: merkle_proof.root_index,
+            public_data,
+            credential_proof,
+            // The issuer is only revealed without an issuer set
+            issuer: params
+                .issuer_set
+                .is_none()
+                .then(|| credential_account.issuer.to_bytes()),
+            nullifier,
+            attribute_min: params.predicates.attribute_min,
+            attribute_max: params.predicates.attribute_max,
+            campaign_nullifier,
+        },
+        merkle_tree: merkle_proof.merkle_tree,
+        circuit_id: circuit.circuit_id,
+    })
+}
+
+/// Adds the system accounts, the verification account and the event addresses of the credential proofs,
+/// followed by their campaign nullifier addresses if the verification has a campaign,
+/// to the remaining accounts and fetches their validity proof.
+async fn pack_verification<R>(
+    rpc: &mut R,
+    remaining_accounts: &mut PackedAccounts,
+    verification_account: &CompressedAccount,
+    address_tree_info: &TreeInfo,
+    verification: &VerificationAccount,
+    credential_proofs: &[CredentialProofData],
+) -> Result<
+    (
+        ValidityProof,
+        PackedAddressTreeInfo,
+        CompressedAccountMetaReadOnly,
+        u8,
+    ),
+    ClientError,
+>
+where
+    R: Rpc + Indexer,
+{
+    let config = SystemAccountMetaConfig::new(crate::ID);
+    remaining_accounts
+        .add_system_accounts_v2(config)
+        .map_err(RpcError::from)?;
+
+    let mut seeds: Vec<Vec<&[u8]>> = credential_proofs
+        .iter()
+        .map(|credential_proof| {
+            vec![
+                ZK_ID_CHECK,
+                credential_proof.nullifier.as_slice(),
+                verification.verification_id.as_slice(),
+            ]
+        })
+        .collect();
+    if let Some(campaign_id) = verification.campaign_id.as_ref() {
+        seeds.extend(credential_proofs.iter().map(|credential_proof| {
+            vec![
+                CAMPAIGN_NULLIFIER,
+                credential_proof.campaign_nullifier.as_slice(),
+                verification.verifier.as_ref(),
+                campaign_id.as_slice(),
+            ]
+        }));
+    }
+    let new_addresses = seeds
+        .iter()
+        .map(|address_seeds| AddressWithTree {
+            address: derive_address(address_seeds, &address_tree_info.tree, &crate::ID).0,
+            tree: address_tree_info.tree,
+        })
+        .collect();
+    let rpc_result = rpc
+        .get_validity_proof(vec![verification_account.hash], new_addresses, None)
+        .await?
+        .value;
+
+    let packed_tree_accounts = rpc_result.pack_tree_infos(remaining_accounts);
+    let packed_state_tree_accounts = packed_tree_accounts
+        .state_trees
+        .ok_or(ClientError::MissingStateTree)?;
+    // All new addresses are in the same address tree.
+    let packed_address_tree_info = packed_tree_accounts.address_trees[0];
+
+    let verification_account_meta = CompressedAccountMetaReadOnly {
+        tree_info: packed_state_tree_accounts.packed_tree_infos[0],
+        address: verification_account
+            .address
+            .ok_or(ClientError::MissingAccountData)?,
+    };
+
+    let output_state_tree_index = rpc
+        .get_random_state_tree_info_v1()?
+        .pack_output_tree_index(remaining_accounts)
+        .map_err(RpcError::from)?;
+
+    Ok((
+        rpc_result.proof,
+        packed_address_tree_info,
+        verification_account_meta,
+        output_state_tree_index,
+    ))
+}
+
+pub fn verify_credential_instruction(
+    instruction_data: &impl InstructionData,
+    accounts: Vec<AccountMeta>,
+) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: instruction_data.data(),
+    }
+}
@@ -0,0 +1,145 @@
+//! Encryption of event data to the verifier of a verification.
+//!
+//! The event data is encrypted with ECIES over X25519:
+//! 1. `shared_secret = X25519(ephemeral_secret, encryption_pubkey)`
+//! 2. `key = HKDF-SHA256(salt = ephemeral_pubkey || encryption_pubkey, ikm = shared_secret, info = EVENT_ENCRYPTION_INFO)`
+//! 3. `data = ephemeral_pubkey || ChaCha20Poly1305(key, nonce = 0, plaintext, aad = verification_id)`
+//!
+//! Every event uses a fresh ephemeral key, so the zero nonce is never reused with the same key.
+//! The proof commits to the hash of `data`, the ciphertext can't be replaced after proving.
+use anchor_lang::{AnchorDeserialize, AnchorSerialize};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use hkdf::Hkdf;
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+use solana_sdk::signature::{Keypair, Signer};
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+
+use super::{ClientError, CredentialAttributes};
+use crate::EVENT_ENCRYPTION_OVERHEAD;
+
+/// HKDF info of the event encryption key.
+pub const EVENT_ENCRYPTION_INFO: &[u8] = b"zk-id event encryption";
+
+/// X25519 keypair of a verifier, the public key is registered with `create_verification`.
+pub struct EventEncryptionKeypair {
+    secret: StaticSecret,
+    pub public_key: [u8; 32],
+}
+
+impl EventEncryptionKeypair {
+    /// Derives the keypair from a Solana keypair by signing "EVENT_ENCRYPTION",
+    /// so that a verifier doesn't need to store another secret.
+    pub fn new(solana_keypair: &Keypair) -> Self {
+        let signature = solana_keypair.sign_message(b"EVENT_ENCRYPTION");
+        Self::from_secret(Sha256::digest(signature.as_ref()).into())
+    }
+
+    pub fn from_secret(secret: [u8; 32]) -> Self {
+        let secret = StaticSecret::from(secret);
+        let public_key = PublicKey::from(&secret).to_bytes();
+        Self { secret, public_key }
+    }
+
+    /// Decrypts the data of an `EncryptedEventAccount` of the verification `verification_id`.
+    pub fn decrypt(&self, verification_id: &[u8; 31], data: &[u8]) -> Result<Vec<u8>, ClientError> {
+        if data.len() < EVENT_ENCRYPTION_OVERHEAD {
+            return Err(ClientError::Decryption);
+        }
+        let (ephemeral_pubkey, ciphertext) = data.split_at(32);
+        let ephemeral_pubkey: [u8; 32] = ephemeral_pubkey.try_into().unwrap();
+        let shared_secret = self
+            .secret
+            .diffie_hellman(&PublicKey::from(ephemeral_pubkey));
+        if !shared_secret.was_contributory() {
+            return Err(ClientError::Decryption);
+        }
+        let cipher = event_cipher(
+            shared_secret.as_bytes(),
+            &ephemeral_pubkey,
+            &self.public_key,
+        )?;
+        cipher
+            .decrypt(
+                Nonce::from_slice(&[0u8; 12]),
+                Payload {
+                    msg: ciphertext,
+                    aad: verification_id,
+                },
+            )
+            .map_err(|_| ClientError::Decryption)
+    }
+}
+
+/// Encrypts event data to the `encryption_pubkey` of the verification `verification_id`.
+pub fn encrypt_event_data(
+    encryption_pubkey: &[u8; 32],
+    verification_id: &[u8; 31],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, ClientError> {
+    let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+    let ephemeral_pubkey = PublicKey::from(&ephemeral_secret).to_bytes();
+    let shared_secret = ephemeral_secret.diffie_hellman(&PublicKey::from(*encryption_pubkey));
+    if !shared_secret.was_contributory() {
+        return Err(ClientError::Encryption);
+    }
+    let cipher = event_cipher(
+        shared_secret.as_bytes(),
+        &ephemeral_pubkey,
+        encryption_pubkey,
+    )?;
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&[0u8; 12]),
+            Payload {
+                msg: plaintext,
+                aad: verification_id,
+            },
+        )
+        .map_err(|_| ClientError::Encryption)?;
+    Ok([ephemeral_pubkey.as_slice(), ciphertext.as_slice()].concat())
+}
+
+fn event_cipher(
+    shared_secret: &[u8; 32],
+    ephemeral_pubkey: &[u8; 32],
+    encryption_pubkey: &[u8; 32],
+) -> Result<ChaCha20Poly1305, ClientError> {
+    let salt = [ephemeral_pubkey.as_slice(), encryption_pubkey.as_slice()].concat();
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(&salt), shared_secret)
+        .expand(EVENT_ENCRYPTION_INFO, &mut key)
+        .map_err(|_| ClientError::Encryption)?;
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+}
+
+/// Attribute values a holder discloses to the verifier, as `(index, value)` pairs.
+/// The proof doesn't cover the disclosed values, only the attribute predicates.
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct DisclosedAttributes {
+    pub attributes: Vec<(u8, u64)>,
+}
+
+impl DisclosedAttributes {
+    pub fn new(attributes: &CredentialAttributes, indices: &[usize]) -> Self {
+        Self {
+            attributes: indices
+                .iter()
+                .map(|index| (*index as u8, attributes.values[*index]))
+                .collect(),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.serialize(&mut bytes).unwrap();
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ClientError> {
+        Ok(Self::try_from_slice(bytes)?)
+    }
+}
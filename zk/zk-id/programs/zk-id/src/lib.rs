@@ -3,19 +3,27 @@
 
 use anchor_lang::prelude::*;
 use borsh::{BorshDeserialize, BorshSerialize};
-use groth16_solana::groth16::Groth16Verifier;
+use groth16_solana::decompression::{decompress_g1, decompress_g2};
+use groth16_solana::groth16::{Groth16Verifier, Groth16Verifyingkey};
+use light_batched_merkle_tree::merkle_tree::BatchedMerkleTreeAccount;
+use light_concurrent_merkle_tree::zero_copy::ConcurrentMerkleTreeZeroCopy;
+use light_hasher::hash_to_field_size::hashv_to_bn254_field_size_be_const_array;
 use light_hasher::to_byte_array::ToByteArray;
-use light_hasher::{Hasher, HasherError, Sha256};
+use light_hasher::{Hasher, HasherError, Poseidon, Sha256};
 use light_sdk::account::{poseidon::LightAccount as LightAccountPoseidon, LightAccount};
 use light_sdk::cpi::v2::CpiAccounts;
 use light_sdk::{
     address::v2::derive_address,
+    constants::ACCOUNT_COMPRESSION_PROGRAM_ID,
     cpi::{v2::LightSystemProgramCpi, InvokeLightSystemProgram, LightCpiInstruction},
     derive_light_cpi_signer,
     instruction::{
-        account_meta::CompressedAccountMeta, CompressedProof, PackedAddressTreeInfo, ValidityProof,
+        account_meta::{
+            CompressedAccountMeta, CompressedAccountMetaBurn, CompressedAccountMetaReadOnly,
+        },
+        CompressedProof, PackedAddressTreeInfo, ValidityProof,
     },
-    merkle_tree::v1::read_state_merkle_tree_root,
+    merkle_tree::v1::{STATE_MERKLE_TREE_ACCOUNT_METADATA_LEN, STATE_MERKLE_TREE_DISCRIMINATOR},
     LightDiscriminator, LightHasher, PackedAddressTreeInfoExt,
 };
 use light_sdk::CpiSigner;
@@ -28,18 +36,101 @@ pub const LIGHT_CPI_SIGNER: CpiSigner =
 pub const ISSUER: &[u8] = b"issuer";
 pub const CREDENTIAL: &[u8] = b"credential";
 pub const ZK_ID_CHECK: &[u8] = b"ZK_ID_CHECK";
+pub const VERIFICATION: &[u8] = b"verification";
+pub const CONFIG: &[u8] = b"config";
+pub const VERIFYING_KEY: &[u8] = b"verifying_key";
+pub const CAMPAIGN_NULLIFIER: &[u8] = b"campaign_nullifier";
 
-// Include the generated verifying key module
-pub mod verifying_key;
+/// Number of attributes committed to by a credential.
+pub const NUM_CREDENTIAL_ATTRIBUTES: usize = 4;
+/// Number of public inputs of the credential circuit.
+pub const NUM_PUBLIC_INPUTS: usize = 11 + 2 * NUM_CREDENTIAL_ATTRIBUTES;
+/// Maximum number of slots `current_slot` of a verification may lag behind the clock.
+pub const MAX_CURRENT_SLOT_AGE: u64 = 150;
+/// Maximum number of root updates the root of a credential proof may lag behind the current root of its state tree.
+pub const MAX_ROOT_AGE: usize = 16;
+/// Maximum number of authorities of an issuer.
+pub const MAX_ISSUER_AUTHORITIES: usize = 8;
+/// Maximum number of issuers a verification accepts.
+pub const MAX_ALLOWED_ISSUERS: usize = 16;
+/// Maximum number of circuits a verification accepts.
+pub const MAX_VERIFICATION_CIRCUITS: usize = 4;
+/// Maximum number of credential proofs of `zk_verify_credentials`.
+pub const MAX_BATCH_CREDENTIAL_PROOFS: usize = 4;
+/// Bytes an encrypted event adds to the plaintext: ephemeral X25519 public key and Poly1305 tag.
+pub const EVENT_ENCRYPTION_OVERHEAD: usize = 32 + 16;
+
+// Verifying key of the credential circuit, generated into OUT_DIR by build.rs.
+// It is not used on-chain, clients register it with register_verifying_key.
+#[cfg(not(target_os = "solana"))]
+pub mod verifying_key {
+    include!(concat!(env!("OUT_DIR"), "/verifying_key.rs"));
+}
+// Verifying key of the v2 state tree circuit, generated into OUT_DIR by build.rs.
+#[cfg(all(not(target_os = "solana"), verifying_key_v2))]
+pub mod verifying_key_v2 {
+    include!(concat!(env!("OUT_DIR"), "/verifying_key_v2.rs"));
+}
+// Verifying keys of the issuer set circuits for v1 and v2 state trees, generated into OUT_DIR by build.rs.
+#[cfg(all(not(target_os = "solana"), verifying_key_issuer_set))]
+pub mod verifying_key_issuer_set {
+    include!(concat!(env!("OUT_DIR"), "/verifying_key_issuer_set.rs"));
+}
+#[cfg(all(not(target_os = "solana"), verifying_key_issuer_set))]
+pub mod verifying_key_issuer_set_v2 {
+    include!(concat!(env!("OUT_DIR"), "/verifying_key_issuer_set_v2.rs"));
+}
+// Credential keypairs, proof generation and instruction builders for clients.
+#[cfg(all(feature = "client", not(target_os = "solana")))]
+pub mod client;
 
 #[program]
 pub mod zk_id {
 
-    use groth16_solana::decompression::{decompress_g1, decompress_g2};
-    use light_hasher::hash_to_field_size::hashv_to_bn254_field_size_be_const_array;
-
     use super::*;
 
+    /// Creates the program config and sets its admin.
+    /// Only the upgrade authority of the program can create the config, once, right after deployment.
+    pub fn initialize_config(ctx: Context<InitializeConfig>, admin: Pubkey) -> Result<()> {
+        ctx.accounts.config.admin = admin;
+        Ok(())
+    }
+
+    /// Creates the verifying key account of a circuit, only the admin can register circuits.
+    /// The IC points exceed the transaction size and are written with `append_verifying_key_ic`.
+    pub fn register_verifying_key(
+        ctx: Context<RegisterVerifyingKey>,
+        circuit_id: u32,
+        vk_alpha_g1: [u8; 64],
+        vk_beta_g2: [u8; 128],
+        vk_gamma_g2: [u8; 128],
+        vk_delta_g2: [u8; 128],
+    ) -> Result<()> {
+        let verifying_key = &mut ctx.accounts.verifying_key;
+        verifying_key.circuit_id = circuit_id;
+        verifying_key.vk_alpha_g1 = vk_alpha_g1;
+        verifying_key.vk_beta_g2 = vk_beta_g2;
+        verifying_key.vk_gamma_g2 = vk_gamma_g2;
+        verifying_key.vk_delta_g2 = vk_delta_g2;
+        verifying_key.vk_ic = Vec::with_capacity(NUM_PUBLIC_INPUTS + 1);
+        msg!("Registered verifying key for circuit: {}", circuit_id);
+        Ok(())
+    }
+
+    /// Appends IC points to a registered verifying key.
+    /// The key can be used once all `NUM_PUBLIC_INPUTS + 1` points are written.
+    pub fn append_verifying_key_ic(
+        ctx: Context<AppendVerifyingKeyIc>,
+        vk_ic: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        let verifying_key = &mut ctx.accounts.verifying_key;
+        if verifying_key.vk_ic.len() + vk_ic.len() > NUM_PUBLIC_INPUTS + 1 {
+            return err!(ErrorCode::InvalidVerifyingKey);
+        }
+        verifying_key.vk_ic.extend_from_slice(&vk_ic);
+        Ok(())
+    }
+
     /// Creates a new issuer compressed account
     pub fn create_issuer<'info>(
         ctx: Context<'_, '_, '_, 'info, GenericAnchorAccounts<'info>>,
@@ -77,6 +168,7 @@ pub mod zk_id {
 
         issuer_account.issuer_pubkey = ctx.accounts.signer.key();
         issuer_account.num_credentials_issued = 0;
+        issuer_account.authority = IssuerAuthority::single(ctx.accounts.signer.key());
 
         msg!(
             "Created issuer account for pubkey: {}",
@@ -93,8 +185,63 @@ pub mod zk_id {
         Ok(())
     }
 
+    /// Replaces the authority set of an issuer, e.g. to rotate a lost or leaked key
+    /// or to require M-of-N authorities.
+    /// Requires `threshold` signatures of the current authorities, co-signers are passed
+    /// as signers in the remaining accounts before `system_accounts_offset`.
+    /// The issuer address and `issuer_pubkey` stay the same, credentials and verifiers keep referencing them.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_issuer_authority<'info>(
+        ctx: Context<'_, '_, '_, 'info, GenericAnchorAccounts<'info>>,
+        proof: ValidityProof,
+        system_accounts_offset: u8,
+        issuer_account_meta: CompressedAccountMeta,
+        issuer_pubkey: Pubkey,
+        num_credentials_issued: u64,
+        authority: IssuerAuthority,
+        new_authority: IssuerAuthority,
+    ) -> Result<()> {
+        authority.check_signers(
+            ctx.accounts.signer.as_ref(),
+            &ctx.remaining_accounts[..system_accounts_offset as usize],
+        )?;
+        new_authority.validate()?;
+
+        let light_cpi_accounts = CpiAccounts::new(
+            ctx.accounts.signer.as_ref(),
+            &ctx.remaining_accounts[system_accounts_offset as usize..],
+            crate::LIGHT_CPI_SIGNER,
+        );
+
+        let mut issuer_account = LightAccount::<IssuerAccount>::new_mut(
+            &crate::ID,
+            &issuer_account_meta,
+            IssuerAccount {
+                issuer_pubkey,
+                num_credentials_issued,
+                authority,
+            },
+        )?;
+        issuer_account.authority = new_authority;
+
+        msg!(
+            "Updated authority of issuer {}: {} of {} authorities",
+            issuer_pubkey,
+            issuer_account.authority.threshold,
+            issuer_account.authority.authorities.len()
+        );
+
+        LightSystemProgramCpi::new_cpi(LIGHT_CPI_SIGNER, proof)
+            .with_light_account(issuer_account)?
+            .invoke(light_cpi_accounts)?;
+
+        Ok(())
+    }
+
     /// Creates a new credential compressed account storing a pubkey
-    /// Requires a valid issuer account - only the issuer can create credentials
+    /// Requires a valid issuer account - `threshold` of the issuer's authorities must sign
+    /// The credential expires at `expiry_slot` and commits to its attributes with
+    /// `attributes_hash = Poseidon(attributes, blinding)`, the issuer shares attributes and blinding with the holder off-chain.
     #[allow(clippy::too_many_arguments)]
     pub fn add_credential<'info>(
         ctx: Context<'_, '_, '_, 'info, GenericAnchorAccounts<'info>>,
@@ -104,21 +251,31 @@ pub mod zk_id {
         system_accounts_offset: u8,
         issuer_account_meta: CompressedAccountMeta,
         credential_pubkey: Pubkey,
+        issuer_pubkey: Pubkey,
         num_credentials_issued: u64,
+        authority: IssuerAuthority,
+        expiry_slot: u64,
+        attributes_hash: [u8; 32],
     ) -> Result<()> {
+        authority.check_signers(
+            ctx.accounts.signer.as_ref(),
+            &ctx.remaining_accounts[..system_accounts_offset as usize],
+        )?;
+
         let light_cpi_accounts = CpiAccounts::new(
             ctx.accounts.signer.as_ref(),
             &ctx.remaining_accounts[system_accounts_offset as usize..],
             crate::LIGHT_CPI_SIGNER,
         );
 
-        // Verify the issuer account - read it to ensure it exists and signer is the issuer
+        // Verify the issuer account - read it to ensure it exists with the checked authority
         let mut issuer_account = LightAccount::<IssuerAccount>::new_mut(
             &crate::ID,
             &issuer_account_meta,
             IssuerAccount {
-                issuer_pubkey: ctx.accounts.signer.key(),
+                issuer_pubkey,
                 num_credentials_issued,
+                authority,
             },
         )?;
 
@@ -149,8 +306,10 @@ pub mod zk_id {
             output_state_tree_index,
         );
 
-        credential_account.issuer = ctx.accounts.signer.key();
+        credential_account.issuer = issuer_pubkey;
         credential_account.credential_pubkey = CredentialPubkey::new(credential_pubkey);
+        credential_account.expiry_slot = expiry_slot;
+        credential_account.attributes_hash = AttributesHash::new(attributes_hash);
 
         msg!(
             "Created credential account for pubkey: {} (issuer credential count: {})",
@@ -169,7 +328,176 @@ pub mod zk_id {
         Ok(())
     }
 
+    /// Revokes a credential by burning its compressed account.
+    /// Only the issuer of the credential can revoke it, `threshold` of its authorities must sign.
+    /// Proofs fail once the credential leaf is nullified in the state tree
+    /// and the roots still containing it are older than `MAX_ROOT_AGE`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn revoke_credential<'info>(
+        ctx: Context<'_, '_, '_, 'info, GenericAnchorAccounts<'info>>,
+        proof: ValidityProof,
+        system_accounts_offset: u8,
+        issuer_account_meta: CompressedAccountMeta,
+        issuer_pubkey: Pubkey,
+        num_credentials_issued: u64,
+        authority: IssuerAuthority,
+        credential_account_meta: CompressedAccountMetaBurn,
+        credential_pubkey: Pubkey,
+        expiry_slot: u64,
+        attributes_hash: [u8; 32],
+    ) -> Result<()> {
+        authority.check_signers(
+            ctx.accounts.signer.as_ref(),
+            &ctx.remaining_accounts[..system_accounts_offset as usize],
+        )?;
+
+        let light_cpi_accounts = CpiAccounts::new(
+            ctx.accounts.signer.as_ref(),
+            &ctx.remaining_accounts[system_accounts_offset as usize..],
+            crate::LIGHT_CPI_SIGNER,
+        );
+
+        // Verify the issuer account - read it to ensure it exists with the checked authority
+        let issuer_account = LightAccount::<IssuerAccount>::new_mut(
+            &crate::ID,
+            &issuer_account_meta,
+            IssuerAccount {
+                issuer_pubkey,
+                num_credentials_issued,
+                authority,
+            },
+        )?;
+
+        // The credential hash commits to its issuer,
+        // burning fails unless the credential was issued by this issuer.
+        let credential_account = LightAccountPoseidon::<CredentialAccount>::new_burn(
+            &crate::ID,
+            &credential_account_meta,
+            CredentialAccount {
+                issuer: issuer_pubkey,
+                credential_pubkey: CredentialPubkey::new(credential_pubkey),
+                expiry_slot,
+                attributes_hash: AttributesHash::new(attributes_hash),
+            },
+        )?;
+
+        msg!(
+            "Revoked credential for pubkey: {} (issuer: {})",
+            credential_pubkey,
+            issuer_pubkey
+        );
+
+        LightSystemProgramCpi::new_cpi(LIGHT_CPI_SIGNER, proof)
+            .with_light_account(issuer_account)?
+            .with_light_account_poseidon(credential_account)?
+            .invoke(light_cpi_accounts)?;
+
+        Ok(())
+    }
+
+    /// Creates a verification context, the compressed account derived from `[VERIFICATION, verification_id]`
+    /// records the verifier (signer), the circuits and the issuers whose credentials it accepts and its expiry.
+    /// Event data of the verification is encrypted to `encryption_pubkey` (X25519).
+    /// With a `campaign_id` (sybil mode) a credential holder can pass only one verification of the campaign.
+    /// With an `issuer_set_root` (anonymity set mode) holders can prove with `zk_verify_credential_issuer_set`
+    /// that their issuer is in the set without revealing it, `allowed_issuers` may then be empty.
+    /// `zk_verify_credential` only accepts proofs for registered verification contexts
+    /// and reads them as read-only accounts, which requires a v2 output state tree.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_verification<'info>(
+        ctx: Context<'_, '_, '_, 'info, GenericAnchorAccounts<'info>>,
+        proof: ValidityProof,
+        address_tree_info: PackedAddressTreeInfo,
+        output_state_tree_index: u8,
+        system_accounts_offset: u8,
+        verification_id: [u8; 31],
+        encryption_pubkey: [u8; 32],
+        circuit_ids: Vec<u32>,
+        allowed_issuers: Vec<Pubkey>,
+        expiry_slot: u64,
+        campaign_id: Option<[u8; 31]>,
+        issuer_set_root: Option<[u8; 32]>,
+    ) -> Result<()> {
+        // A zero campaign_id is the circuit's no campaign value.
+        if campaign_id == Some([0u8; 31]) {
+            return err!(ErrorCode::InvalidCampaignId);
+        }
+        if (allowed_issuers.is_empty() && issuer_set_root.is_none())
+            || allowed_issuers.len() > MAX_ALLOWED_ISSUERS
+        {
+            return err!(ErrorCode::InvalidAllowedIssuers);
+        }
+        for (i, issuer) in allowed_issuers.iter().enumerate() {
+            if allowed_issuers[i + 1..].contains(issuer) {
+                return err!(ErrorCode::InvalidAllowedIssuers);
+            }
+        }
+        if circuit_ids.is_empty() || circuit_ids.len() > MAX_VERIFICATION_CIRCUITS {
+            return err!(ErrorCode::InvalidCircuitIds);
+        }
+        if expiry_slot <= Clock::get()?.slot {
+            return err!(ErrorCode::VerificationExpired);
+        }
+
+        let light_cpi_accounts = CpiAccounts::new(
+            ctx.accounts.signer.as_ref(),
+            &ctx.remaining_accounts[system_accounts_offset as usize..],
+            crate::LIGHT_CPI_SIGNER,
+        );
+
+        let address_tree_pubkey = address_tree_info
+            .get_tree_pubkey(&light_cpi_accounts)
+            .map_err(|_| ErrorCode::AccountNotEnoughKeys)?;
+
+        if address_tree_pubkey.to_bytes() != light_sdk::constants::ADDRESS_TREE_V2 {
+            msg!("Invalid address tree");
+            return Err(ProgramError::InvalidAccountData.into());
+        }
+
+        let (address, address_seed) = derive_address(
+            &[VERIFICATION, verification_id.as_slice()],
+            &address_tree_pubkey,
+            &crate::ID,
+        );
+
+        let mut verification_account = LightAccount::<VerificationAccount>::new_init(
+            &crate::ID,
+            Some(address),
+            output_state_tree_index,
+        );
+        verification_account.verifier = ctx.accounts.signer.key();
+        verification_account.verification_id = verification_id;
+        verification_account.encryption_pubkey = encryption_pubkey;
+        verification_account.circuit_ids = circuit_ids;
+        verification_account.allowed_issuers = allowed_issuers;
+        verification_account.issuer_set_root = issuer_set_root;
+        verification_account.expiry_slot = expiry_slot;
+        verification_account.campaign_id = campaign_id;
+
+        msg!(
+            "Created verification for verifier: {} ({} allowed issuers)",
+            verification_account.verifier,
+            verification_account.allowed_issuers.len()
+        );
+
+        LightSystemProgramCpi::new_cpi(LIGHT_CPI_SIGNER, proof)
+            .with_light_account(verification_account)?
+            .with_new_addresses(&[
+                address_tree_info.into_new_address_params_assigned_packed(address_seed, Some(0))
+            ])
+            .invoke(light_cpi_accounts)?;
+
+        Ok(())
+    }
+
     /// Verifies a ZK proof of credential ownership and creates an encrypted event account.
+    /// The verification context must be registered with `create_verification`, not expired,
+    /// and accept the credential's issuer. The proof is verified with the verifying key of the verification's circuit.
+    /// The proof also shows that the credential is not expired at `current_slot`
+    /// and that `attribute_min[i] <= attributes[i] <= attribute_max[i]`, without revealing expiry or attributes.
+    /// `public_data` is encrypted to the verification's `encryption_pubkey`, its hash is a public input of the proof.
+    /// If the verification has a campaign, the campaign nullifier account is created as well,
+    /// which fails if the credential holder already passed a verification of the campaign.
     #[allow(clippy::too_many_arguments)]
     pub fn zk_verify_credential<'info>(
         ctx: Context<'_, '_, '_, 'info, VerifyAccounts<'info>>,
@@ -182,13 +510,117 @@ pub mod zk_id {
         credential_proof: CompressedProof,
         issuer: [u8; 32],
         nullifier: [u8; 32],
-        verification_id: [u8; 31],
+        verification_account_meta: CompressedAccountMetaReadOnly,
+        verification: VerificationAccount,
+        current_slot: u64,
+        attribute_min: [u64; NUM_CREDENTIAL_ATTRIBUTES],
+        attribute_max: [u64; NUM_CREDENTIAL_ATTRIBUTES],
+        campaign_nullifier: [u8; 32],
+    ) -> Result<()> {
+        process_verify_credential(
+            ctx,
+            proof,
+            address_tree_info,
+            output_state_tree_index,
+            system_accounts_offset,
+            verification_account_meta,
+            verification,
+            current_slot,
+            CredentialProofData {
+                input_root_index,
+                public_data,
+                credential_proof,
+                issuer: Some(issuer),
+                nullifier,
+                attribute_min,
+                attribute_max,
+                campaign_nullifier,
+            },
+        )
+    }
+
+    /// Verifies a ZK proof of credential ownership like `zk_verify_credential` without revealing the issuer (anonymity set mode).
+    /// The proof shows that the credential's issuer is a leaf of the verification's `issuer_set_root`,
+    /// verified with the verifying key of an issuer set circuit the verification accepts.
+    #[allow(clippy::too_many_arguments)]
+    pub fn zk_verify_credential_issuer_set<'info>(
+        ctx: Context<'_, '_, '_, 'info, VerifyAccounts<'info>>,
+        proof: ValidityProof,
+        address_tree_info: PackedAddressTreeInfo,
+        output_state_tree_index: u8,
+        system_accounts_offset: u8,
+        input_root_index: u16,
+        public_data: Vec<u8>,
+        credential_proof: CompressedProof,
+        nullifier: [u8; 32],
+        verification_account_meta: CompressedAccountMetaReadOnly,
+        verification: VerificationAccount,
+        current_slot: u64,
+        attribute_min: [u64; NUM_CREDENTIAL_ATTRIBUTES],
+        attribute_max: [u64; NUM_CREDENTIAL_ATTRIBUTES],
+        campaign_nullifier: [u8; 32],
+    ) -> Result<()> {
+        process_verify_credential(
+            ctx,
+            proof,
+            address_tree_info,
+            output_state_tree_index,
+            system_accounts_offset,
+            verification_account_meta,
+            verification,
+            current_slot,
+            CredentialProofData {
+                input_root_index,
+                public_data,
+                credential_proof,
+                issuer: None,
+                nullifier,
+                attribute_min,
+                attribute_max,
+                campaign_nullifier,
+            },
+        )
+    }
+
+    /// Verifies up to `MAX_BATCH_CREDENTIAL_PROOFS` credential proofs for one verification,
+    /// e.g. credentials of a KYC and an accreditation issuer, and creates their event accounts.
+    /// Every proof is checked like in `zk_verify_credential`.
+    /// The state tree and verifying key of proof `i` are `remaining_accounts[2 * i]` and `remaining_accounts[2 * i + 1]`,
+    /// the light system accounts follow at `system_accounts_offset`.
+    /// All event and campaign nullifier addresses are created with one validity proof.
+    #[allow(clippy::too_many_arguments)]
+    pub fn zk_verify_credentials<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GenericAnchorAccounts<'info>>,
+        proof: ValidityProof,
+        address_tree_info: PackedAddressTreeInfo,
+        output_state_tree_index: u8,
+        system_accounts_offset: u8,
+        verification_account_meta: CompressedAccountMetaReadOnly,
+        verification: VerificationAccount,
+        current_slot: u64,
+        credential_proofs: Vec<CredentialProofData>,
     ) -> Result<()> {
+        if credential_proofs.is_empty() || credential_proofs.len() > MAX_BATCH_CREDENTIAL_PROOFS {
+            return err!(ErrorCode::InvalidCredentialProofs);
+        }
+        if (system_accounts_offset as usize) < 2 * credential_proofs.len() {
+            return err!(ErrorCode::AccountNotEnoughKeys);
+        }
         let light_cpi_accounts = CpiAccounts::new(
             ctx.accounts.signer.as_ref(),
             &ctx.remaining_accounts[system_accounts_offset as usize..],
             crate::LIGHT_CPI_SIGNER,
         );
+        let verification_account = LightAccount::<VerificationAccount>::new_read_only(
+            &crate::ID,
+            &verification_account_meta,
+            verification,
+            &light_cpi_accounts
+                .tree_pubkeys()
+                .map_err(|_| ErrorCode::AccountNotEnoughKeys)?,
+        )?;
+        check_verification(&verification_account, current_slot)?;
+
         let address_pubkey = address_tree_info
             .get_tree_pubkey(&light_cpi_accounts)
             .map_err(|_| ErrorCode::AccountNotEnoughKeys)?;
@@ -198,109 +630,403 @@ pub mod zk_id {
             return Err(ProgramError::InvalidAccountData.into());
         }
 
-        let (address, address_seed) = derive_address(
-            &[
-                ZK_ID_CHECK,
-                nullifier.as_slice(),
-                verification_id.as_slice(),
-            ],
-            &address_pubkey,
-            &crate::ID,
-        );
+        let num_proofs = credential_proofs.len();
+        let mut event_accounts = Vec::with_capacity(num_proofs);
+        let mut campaign_nullifier_accounts = Vec::new();
+        let mut new_address_params = Vec::with_capacity(2 * num_proofs);
+        let mut campaign_nullifier_address_params = Vec::new();
+        for (i, credential_proof) in credential_proofs.into_iter().enumerate() {
+            let input_merkle_tree = &ctx.remaining_accounts[2 * i];
+            let verifying_key =
+                Account::<VerifyingKeyAccount>::try_from(&ctx.remaining_accounts[2 * i + 1])?;
+            let (address, address_seed) = verify_credential_proof(
+                &verification_account,
+                &verifying_key,
+                input_merkle_tree,
+                &address_pubkey,
+                current_slot,
+                &credential_proof,
+            )?;
 
-        // Get root from input Merkle tree (example of reading on-chain state)
-        let expected_root = read_state_merkle_tree_root(
-            &ctx.accounts.input_merkle_tree.to_account_info(),
-            input_root_index,
-        )?;
+            if let Some((address, address_seed)) = derive_campaign_nullifier_address(
+                &verification_account,
+                &address_pubkey,
+                &credential_proof,
+            ) {
+                // Campaign nullifier accounts follow the event accounts.
+                campaign_nullifier_address_params.push(
+                    address_tree_info.into_new_address_params_assigned_packed(
+                        address_seed,
+                        Some((num_proofs + campaign_nullifier_accounts.len()) as u8),
+                    ),
+                );
+                campaign_nullifier_accounts.push(
+                    LightAccount::<CampaignNullifierAccount>::new_init(
+                        &crate::ID,
+                        Some(address),
+                        output_state_tree_index,
+                    ),
+                );
+            }
+
+            let mut event_account = LightAccount::<EncryptedEventAccount>::new_init(
+                &crate::ID,
+                Some(address),
+                output_state_tree_index,
+            );
+            event_account.data = credential_proof.public_data;
+            event_accounts.push(event_account);
+            // The verification account is read only, the event accounts are the first output accounts.
+            new_address_params.push(
+                address_tree_info
+                    .into_new_address_params_assigned_packed(address_seed, Some(i as u8)),
+            );
+        }
+
+        let mut cpi = LightSystemProgramCpi::new_cpi(LIGHT_CPI_SIGNER, proof)
+            .with_light_account(verification_account)?;
+        for event_account in event_accounts {
+            cpi = cpi.with_light_account(event_account)?;
+        }
+        for campaign_nullifier_account in campaign_nullifier_accounts {
+            cpi = cpi.with_light_account(campaign_nullifier_account)?;
+        }
+        new_address_params.extend(campaign_nullifier_address_params);
+        cpi.with_new_addresses(&new_address_params)
+            .invoke(light_cpi_accounts)?;
+
+        Ok(())
+    }
+}
+
+/// Verifies a credential proof for a registered verification and creates its event account,
+/// and its campaign nullifier account if the verification has a campaign.
+#[allow(clippy::too_many_arguments)]
+fn process_verify_credential<'info>(
+    ctx: Context<'_, '_, '_, 'info, VerifyAccounts<'info>>,
+    proof: ValidityProof,
+    address_tree_info: PackedAddressTreeInfo,
+    output_state_tree_index: u8,
+    system_accounts_offset: u8,
+    verification_account_meta: CompressedAccountMetaReadOnly,
+    verification: VerificationAccount,
+    current_slot: u64,
+    credential_proof: CredentialProofData,
+) -> Result<()> {
+    let light_cpi_accounts = CpiAccounts::new(
+        ctx.accounts.signer.as_ref(),
+        &ctx.remaining_accounts[system_accounts_offset as usize..],
+        crate::LIGHT_CPI_SIGNER,
+    );
+    // The verification account is created by create_verification only,
+    // its data is checked against the account hash. It is read only,
+    // proofs of the same verification don't contend for it.
+    let verification_account = LightAccount::<VerificationAccount>::new_read_only(
+        &crate::ID,
+        &verification_account_meta,
+        verification,
+        &light_cpi_accounts
+            .tree_pubkeys()
+            .map_err(|_| ErrorCode::AccountNotEnoughKeys)?,
+    )?;
+    check_verification(&verification_account, current_slot)?;
+
+    let address_pubkey = address_tree_info
+        .get_tree_pubkey(&light_cpi_accounts)
+        .map_err(|_| ErrorCode::AccountNotEnoughKeys)?;
+
+    if address_pubkey.to_bytes() != light_sdk::constants::ADDRESS_TREE_V2 {
+        msg!("Invalid address tree");
+        return Err(ProgramError::InvalidAccountData.into());
+    }
 
-        let merkle_tree_pubkey = ctx.accounts.input_merkle_tree.key();
-        let merkle_tree_hashed =
-            hashv_to_bn254_field_size_be_const_array::<2>(&[&merkle_tree_pubkey.to_bytes()])
-                .unwrap();
+    let (address, address_seed) = verify_credential_proof(
+        &verification_account,
+        &ctx.accounts.verifying_key,
+        &ctx.accounts.input_merkle_tree.to_account_info(),
+        &address_pubkey,
+        current_slot,
+        &credential_proof,
+    )?;
 
-        let mut discriminator = [0u8; 32];
-        discriminator[24..].copy_from_slice(CredentialAccount::LIGHT_DISCRIMINATOR_SLICE);
-        let issuer_hashed = hashv_to_bn254_field_size_be_const_array::<2>(&[&issuer]).unwrap();
-        let account_owner_hashed =
-            hashv_to_bn254_field_size_be_const_array::<2>(&[&crate::ID.to_bytes()]).unwrap();
+    let mut event_account = LightAccount::<EncryptedEventAccount>::new_init(
+        &crate::ID,
+        Some(address),
+        output_state_tree_index,
+    );
+    let campaign_nullifier_address = derive_campaign_nullifier_address(
+        &verification_account,
+        &address_pubkey,
+        &credential_proof,
+    );
+    event_account.data = credential_proof.public_data;
 
-        let mut event_account = LightAccount::<EncryptedEventAccount>::new_init(
+    let mut new_address_params =
+        vec![address_tree_info.into_new_address_params_assigned_packed(address_seed, Some(0))];
+    let mut cpi = LightSystemProgramCpi::new_cpi(LIGHT_CPI_SIGNER, proof)
+        .with_light_account(verification_account)?
+        .with_light_account(event_account)?;
+    if let Some((address, address_seed)) = campaign_nullifier_address {
+        cpi = cpi.with_light_account(LightAccount::<CampaignNullifierAccount>::new_init(
             &crate::ID,
             Some(address),
             output_state_tree_index,
-        );
-        event_account.data = public_data;
+        ))?;
+        new_address_params
+            .push(address_tree_info.into_new_address_params_assigned_packed(address_seed, Some(1)));
+    }
+    cpi.with_new_addresses(&new_address_params)
+        .invoke(light_cpi_accounts)?;
 
-        // Compute the data hash for the event account to use in ZK proof verification
-        // Use SHA256 with length prefix to match the flat hashing scheme
-        let mut hash_input = Vec::new();
-        hash_input.extend_from_slice(&(event_account.data.len() as u32).to_le_bytes());
-        hash_input.extend_from_slice(&event_account.data);
-        let mut event_data_hash =
-            Sha256::hash(&hash_input).map_err(|_| ProgramError::InvalidAccountData)?;
-        event_data_hash[0] = 0; // Ensure hash is in BN254 field
+    Ok(())
+}
 
-        {
-            // Construct public inputs array for the circuit
-            // Order MUST match the circuit's public declaration exactly:
-            // owner_hashed, merkle_tree_hashed, discriminator, issuer_hashed, expectedRoot, public_encrypted_data_hash, public_data_hash
-            let mut padded_verification_id = [0u8; 32];
-            padded_verification_id[1..].copy_from_slice(&verification_id);
-
-            let public_inputs: [[u8; 32]; 8] = [
-                account_owner_hashed,
-                merkle_tree_hashed,
-                discriminator,
-                issuer_hashed,
-                expected_root,
-                padded_verification_id,
-                event_data_hash, // This is public_encrypted_data_hash
-                nullifier,
-            ];
-            msg!("public_inputs {:?}", public_inputs);
-
-            let proof_a = decompress_g1(&credential_proof.a).map_err(|e| {
-                let code: u32 = e.into();
-                Error::from(ProgramError::Custom(code))
-            })?;
-
-            let proof_b = decompress_g2(&credential_proof.b).map_err(|e| {
-                let code: u32 = e.into();
-                Error::from(ProgramError::Custom(code))
-            })?;
-            let proof_c = decompress_g1(&credential_proof.c).map_err(|e| {
-                let code: u32 = e.into();
-                Error::from(ProgramError::Custom(code))
-            })?;
-
-            // Verify the Groth16 proof
-            let mut verifier = Groth16Verifier::new(
-                &proof_a,
-                &proof_b,
-                &proof_c,
-                &public_inputs,
-                &crate::verifying_key::VERIFYINGKEY,
-            )
-            .map_err(|e| {
-                let code: u32 = e.into();
-                Error::from(ProgramError::Custom(code))
-            })?;
-
-            verifier.verify().map_err(|e| {
-                let code: u32 = e.into();
-                Error::from(ProgramError::Custom(code))
-            })?;
+/// Checks that a verification accepts proofs at `current_slot`.
+fn check_verification(verification: &VerificationAccount, current_slot: u64) -> Result<()> {
+    let slot = Clock::get()?.slot;
+    if slot >= verification.expiry_slot {
+        return err!(ErrorCode::VerificationExpired);
+    }
+    // The proof is created off-chain, current_slot must be recent so that expired credentials fail.
+    if current_slot > slot || slot - current_slot > MAX_CURRENT_SLOT_AGE {
+        return err!(ErrorCode::InvalidCurrentSlot);
+    }
+    Ok(())
+}
+
+/// Verifies a credential proof for `verification`,
+/// returns the address and address seed of its event account.
+fn verify_credential_proof(
+    verification: &VerificationAccount,
+    verifying_key: &VerifyingKeyAccount,
+    input_merkle_tree: &AccountInfo,
+    address_tree_pubkey: &Pubkey,
+    current_slot: u64,
+    credential_proof: &CredentialProofData,
+) -> Result<([u8; 32], [u8; 32])> {
+    // The issuer public input is the hashed issuer,
+    // or the root of the verification's issuer set if the proof hides the issuer.
+    let issuer_input = match credential_proof.issuer {
+        Some(issuer) => {
+            if !verification
+                .allowed_issuers
+                .contains(&Pubkey::new_from_array(issuer))
+            {
+                return err!(ErrorCode::IssuerNotAllowed);
+            }
+            hashv_to_bn254_field_size_be_const_array::<2>(&[&issuer]).unwrap()
         }
-        LightSystemProgramCpi::new_cpi(LIGHT_CPI_SIGNER, proof)
-            .with_light_account(event_account)?
-            .with_new_addresses(&[
-                address_tree_info.into_new_address_params_assigned_packed(address_seed, Some(0))
-            ])
-            .invoke(light_cpi_accounts)?;
+        None => verification
+            .issuer_set_root
+            .ok_or(ErrorCode::IssuerSetNotSupported)?,
+    };
+    if !verification.circuit_ids.contains(&verifying_key.circuit_id) {
+        return err!(ErrorCode::InvalidCircuit);
+    }
+    if verifying_key.vk_ic.len() != NUM_PUBLIC_INPUTS + 1 {
+        return err!(ErrorCode::InvalidVerifyingKey);
+    }
+    // public_data is encrypted to the verification's encryption_pubkey,
+    // the program can't check the encryption, only that the payload is well-formed.
+    if credential_proof.public_data.len() < EVENT_ENCRYPTION_OVERHEAD {
+        return err!(ErrorCode::InvalidEventData);
+    }
+    let verification_id = verification.verification_id;
 
-        Ok(())
+    let (address, address_seed) = derive_address(
+        &[
+            ZK_ID_CHECK,
+            credential_proof.nullifier.as_slice(),
+            verification_id.as_slice(),
+        ],
+        address_tree_pubkey,
+        &crate::ID,
+    );
+
+    // The proof must use one of the recent roots of the input Merkle tree,
+    // appends to the shared tree while the transaction is in flight don't invalidate it.
+    let expected_root = read_recent_state_root(
+        input_merkle_tree,
+        credential_proof.input_root_index as usize,
+    )?;
+
+    let merkle_tree_hashed =
+        hashv_to_bn254_field_size_be_const_array::<2>(&[&input_merkle_tree.key.to_bytes()])
+            .unwrap();
+
+    let mut discriminator = [0u8; 32];
+    discriminator[24..].copy_from_slice(CredentialAccount::LIGHT_DISCRIMINATOR_SLICE);
+    let account_owner_hashed =
+        hashv_to_bn254_field_size_be_const_array::<2>(&[&crate::ID.to_bytes()]).unwrap();
+
+    // Compute the data hash of the event account to use in ZK proof verification
+    // Use SHA256 with length prefix to match the flat hashing scheme
+    let mut hash_input = Vec::new();
+    hash_input.extend_from_slice(&(credential_proof.public_data.len() as u32).to_le_bytes());
+    hash_input.extend_from_slice(&credential_proof.public_data);
+    let mut event_data_hash =
+        Sha256::hash(&hash_input).map_err(|_| ProgramError::InvalidAccountData)?;
+    event_data_hash[0] = 0; // Ensure hash is in BN254 field
+
+    // Construct public inputs array for the circuit
+    // Order MUST match the circuit's public declaration exactly:
+    // owner_hashed, merkle_tree_hashed, discriminator, issuer_hashed (issuer_set_root), expectedRoot, verification_id,
+    // public_encrypted_data_hash, nullifier, current_slot, attribute_min[..], attribute_max[..],
+    // campaign_id, campaign_nullifier
+    let mut padded_verification_id = [0u8; 32];
+    padded_verification_id[1..].copy_from_slice(&verification_id);
+
+    let mut public_inputs = [[0u8; 32]; NUM_PUBLIC_INPUTS];
+    public_inputs[..9].copy_from_slice(&[
+        account_owner_hashed,
+        merkle_tree_hashed,
+        discriminator,
+        issuer_input,
+        expected_root,
+        padded_verification_id,
+        event_data_hash, // This is public_encrypted_data_hash
+        credential_proof.nullifier,
+        u64_to_field(current_slot),
+    ]);
+    for (i, (min, max)) in credential_proof
+        .attribute_min
+        .iter()
+        .zip(credential_proof.attribute_max.iter())
+        .enumerate()
+    {
+        public_inputs[9 + i] = u64_to_field(*min);
+        public_inputs[9 + NUM_CREDENTIAL_ATTRIBUTES + i] = u64_to_field(*max);
+    }
+    // Without a campaign both inputs are zero, the circuit enforces a zero campaign nullifier.
+    if let Some(campaign_id) = verification.campaign_id {
+        public_inputs[9 + 2 * NUM_CREDENTIAL_ATTRIBUTES] =
+            campaign_input(&verification.verifier, &campaign_id);
+    }
+    public_inputs[10 + 2 * NUM_CREDENTIAL_ATTRIBUTES] = credential_proof.campaign_nullifier;
+    msg!("public_inputs {:?}", public_inputs);
+
+    let proof_a = decompress_g1(&credential_proof.credential_proof.a).map_err(|e| {
+        let code: u32 = e.into();
+        Error::from(ProgramError::Custom(code))
+    })?;
+
+    let proof_b = decompress_g2(&credential_proof.credential_proof.b).map_err(|e| {
+        let code: u32 = e.into();
+        Error::from(ProgramError::Custom(code))
+    })?;
+    let proof_c = decompress_g1(&credential_proof.credential_proof.c).map_err(|e| {
+        let code: u32 = e.into();
+        Error::from(ProgramError::Custom(code))
+    })?;
+
+    // Verify the Groth16 proof
+    let mut verifier = Groth16Verifier::new(
+        &proof_a,
+        &proof_b,
+        &proof_c,
+        &public_inputs,
+        &verifying_key.groth16_verifying_key(),
+    )
+    .map_err(|e| {
+        let code: u32 = e.into();
+        Error::from(ProgramError::Custom(code))
+    })?;
+
+    verifier.verify().map_err(|e| {
+        let code: u32 = e.into();
+        Error::from(ProgramError::Custom(code))
+    })?;
+
+    Ok((address, address_seed))
+}
+
+/// Campaign input of the credential circuit, the campaign id hashed with the verifier
+/// so that verifiers reusing a campaign id do not share campaign nullifiers.
+pub fn campaign_input(verifier: &Pubkey, campaign_id: &[u8; 31]) -> [u8; 32] {
+    hashv_to_bn254_field_size_be_const_array::<3>(&[verifier.as_ref(), campaign_id.as_slice()])
+        .unwrap()
+}
+
+/// Returns the address and address seed of the campaign nullifier account of a credential proof,
+/// derived from `[CAMPAIGN_NULLIFIER, campaign_nullifier, verifier, campaign_id]`,
+/// if the verification has a campaign.
+fn derive_campaign_nullifier_address(
+    verification: &VerificationAccount,
+    address_tree_pubkey: &Pubkey,
+    credential_proof: &CredentialProofData,
+) -> Option<([u8; 32], [u8; 32])> {
+    verification.campaign_id.map(|campaign_id| {
+        derive_address(
+            &[
+                CAMPAIGN_NULLIFIER,
+                credential_proof.campaign_nullifier.as_slice(),
+                verification.verifier.as_ref(),
+                campaign_id.as_slice(),
+            ],
+            address_tree_pubkey,
+            &crate::ID,
+        )
+    })
+}
+
+/// Reads the root at `root_index` of a v1 concurrent or v2 batched state Merkle tree,
+/// after checking the account owner and discriminator.
+/// Only the current root and the `MAX_ROOT_AGE` roots before it are accepted.
+fn read_recent_state_root(merkle_tree: &AccountInfo, root_index: usize) -> Result<[u8; 32]> {
+    if let Ok(batched_tree) = BatchedMerkleTreeAccount::state_from_account_info(merkle_tree) {
+        let root_history = &batched_tree.root_history;
+        check_root_age(
+            root_index,
+            root_history.last_index(),
+            root_history.capacity(),
+        )?;
+        return root_history
+            .get(root_index)
+            .copied()
+            .ok_or_else(|| error!(ErrorCode::InvalidRootIndex));
+    }
+    if merkle_tree.owner.to_bytes() != ACCOUNT_COMPRESSION_PROGRAM_ID {
+        return Err(ProgramError::IllegalOwner.into());
+    }
+    let account_data = merkle_tree.try_borrow_data()?;
+    if account_data.len() < STATE_MERKLE_TREE_ACCOUNT_METADATA_LEN
+        || account_data[..8] != STATE_MERKLE_TREE_DISCRIMINATOR
+    {
+        return Err(ProgramError::InvalidAccountData.into());
+    }
+    let concurrent_tree = ConcurrentMerkleTreeZeroCopy::<Poseidon, 26>::from_bytes_zero_copy(
+        &account_data[STATE_MERKLE_TREE_ACCOUNT_METADATA_LEN..],
+    )
+    .map_err(|_| ProgramError::InvalidAccountData)?;
+    check_root_age(
+        root_index,
+        concurrent_tree.root_index(),
+        concurrent_tree.roots.capacity(),
+    )?;
+    concurrent_tree
+        .roots
+        .get(root_index)
+        .copied()
+        .ok_or_else(|| error!(ErrorCode::InvalidRootIndex))
+}
+
+/// Checks that the root at `root_index` of a cyclic root history
+/// is at most `MAX_ROOT_AGE` roots older than the current root.
+fn check_root_age(root_index: usize, current_root_index: usize, capacity: usize) -> Result<()> {
+    if root_index >= capacity {
+        return err!(ErrorCode::InvalidRootIndex);
+    }
+    if (current_root_index + capacity - root_index) % capacity > MAX_ROOT_AGE {
+        return err!(ErrorCode::StaleRoot);
     }
+    Ok(())
+}
+
+/// Big-endian field element of a u64 public input.
+fn u64_to_field(value: u64) -> [u8; 32] {
+    let mut field = [0u8; 32];
+    field[24..].copy_from_slice(&value.to_be_bytes());
+    field
 }
 
 #[derive(Accounts)]
@@ -312,8 +1038,92 @@ pub struct GenericAnchorAccounts<'info> {
 pub struct VerifyAccounts<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
-    /// CHECK: read_state_merkle_tree_root checks account owner, and discriminator
+    /// CHECK: read_state_root checks account owner, and discriminator
     pub input_merkle_tree: UncheckedAccount<'info>,
+    /// Verifying key of a circuit the verification accepts,
+    /// the circuit's tree height must match the input Merkle tree.
+    pub verifying_key: Box<Account<'info, VerifyingKeyAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(init, payer = signer, space = 8 + 32, seeds = [CONFIG], bump)]
+    pub config: Account<'info, ConfigAccount>,
+    /// Program data of this program, its upgrade authority must sign.
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(signer.key())
+            @ ErrorCode::InvalidUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(circuit_id: u32)]
+pub struct RegisterVerifyingKey<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(seeds = [CONFIG], bump, has_one = admin)]
+    pub config: Account<'info, ConfigAccount>,
+    #[account(
+        init,
+        payer = admin,
+        space = VerifyingKeyAccount::SPACE,
+        seeds = [VERIFYING_KEY, circuit_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub verifying_key: Box<Account<'info, VerifyingKeyAccount>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AppendVerifyingKeyIc<'info> {
+    pub admin: Signer<'info>,
+    #[account(seeds = [CONFIG], bump, has_one = admin)]
+    pub config: Account<'info, ConfigAccount>,
+    #[account(mut)]
+    pub verifying_key: Box<Account<'info, VerifyingKeyAccount>>,
+}
+
+#[account]
+#[derive(Debug)]
+pub struct ConfigAccount {
+    /// Registers verifying keys.
+    pub admin: Pubkey,
+}
+
+/// Groth16 verifying key of a circuit with `NUM_PUBLIC_INPUTS` public inputs,
+/// at the address derived from `[VERIFYING_KEY, circuit_id]`.
+#[account]
+#[derive(Debug)]
+pub struct VerifyingKeyAccount {
+    pub circuit_id: u32,
+    pub vk_alpha_g1: [u8; 64],
+    pub vk_beta_g2: [u8; 128],
+    pub vk_gamma_g2: [u8; 128],
+    pub vk_delta_g2: [u8; 128],
+    pub vk_ic: Vec<[u8; 64]>,
+}
+
+impl VerifyingKeyAccount {
+    /// [discriminator, circuit_id, alpha, beta, gamma, delta, vk_ic]
+    pub const SPACE: usize = 8 + 4 + 64 + 3 * 128 + 4 + 64 * (NUM_PUBLIC_INPUTS + 1);
+
+    pub fn groth16_verifying_key(&self) -> Groth16Verifyingkey<'_> {
+        Groth16Verifyingkey {
+            nr_pubinputs: self.vk_ic.len().saturating_sub(1),
+            vk_alpha_g1: self.vk_alpha_g1,
+            vk_beta_g2: self.vk_beta_g2,
+            vk_gamma_g2: self.vk_gamma_g2,
+            vk_delta_g2: self.vk_delta_g2,
+            vk_ic: &self.vk_ic,
+        }
+    }
 }
 
 #[derive(
@@ -324,6 +1134,10 @@ pub struct CredentialAccount {
     pub issuer: Pubkey,
     /// CredentialPubkey (is a Poseidon hash -> no need to annotate with #[hash])
     pub credential_pubkey: CredentialPubkey,
+    /// Slot from which on the credential can no longer be proven.
+    pub expiry_slot: u64,
+    /// Poseidon(attributes[..NUM_CREDENTIAL_ATTRIBUTES], blinding)
+    pub attributes_hash: AttributesHash,
 }
 
 #[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize, LightDiscriminator)]
@@ -346,15 +1160,138 @@ impl ToByteArray for CredentialPubkey {
     }
 }
 
+#[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize, LightDiscriminator)]
+pub struct AttributesHash {
+    pub attributes_hash: [u8; 32],
+}
+
+impl AttributesHash {
+    pub fn new(attributes_hash: [u8; 32]) -> Self {
+        Self { attributes_hash }
+    }
+}
+
+// The attributes hash is a Poseidon hash and already a field element.
+impl ToByteArray for AttributesHash {
+    const NUM_FIELDS: usize = 1;
+    fn to_byte_array(&self) -> std::result::Result<[u8; 32], HasherError> {
+        Ok(self.attributes_hash)
+    }
+}
+
+/// Event of a successful verification.
+/// `data` is `ephemeral_pubkey || ciphertext || tag`, encrypted to the verification's `encryption_pubkey`.
 #[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize, LightDiscriminator)]
 pub struct EncryptedEventAccount {
     pub data: Vec<u8>,
 }
 
+/// Credential proof and its public inputs, see `zk_verify_credential`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CredentialProofData {
+    pub input_root_index: u16,
+    /// Event data encrypted to the verification's `encryption_pubkey`.
+    pub public_data: Vec<u8>,
+    pub credential_proof: CompressedProof,
+    /// Issuer of the credential, `None` if the proof hides it in the verification's issuer set.
+    pub issuer: Option<[u8; 32]>,
+    pub nullifier: [u8; 32],
+    pub attribute_min: [u64; NUM_CREDENTIAL_ATTRIBUTES],
+    pub attribute_max: [u64; NUM_CREDENTIAL_ATTRIBUTES],
+    /// Poseidon(campaign_input(verifier, campaign_id), credential private key),
+    /// zero if the verification has no campaign.
+    pub campaign_nullifier: [u8; 32],
+}
+
+/// Created once per credential holder and campaign, see `VerificationAccount::campaign_id`.
+#[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize, LightDiscriminator)]
+pub struct CampaignNullifierAccount {}
+
+/// Verification context created by a verifier.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, LightDiscriminator)]
+pub struct VerificationAccount {
+    pub verifier: Pubkey,
+    /// External nullifier of the proofs, a credential can be used once per verification_id.
+    pub verification_id: [u8; 31],
+    /// X25519 public key the event data of this verification is encrypted to.
+    pub encryption_pubkey: [u8; 32],
+    /// Circuits whose registered verifying keys proofs are verified with,
+    /// e.g. the credential circuits for v1 and v2 state trees.
+    pub circuit_ids: Vec<u32>,
+    /// Stable identities (`IssuerAccount::issuer_pubkey`) of the accepted issuers.
+    pub allowed_issuers: Vec<Pubkey>,
+    /// Anonymity set mode: Merkle root of the hashed issuers a proof may hide its issuer in,
+    /// see `zk_verify_credential_issuer_set`.
+    pub issuer_set_root: Option<[u8; 32]>,
+    /// Slot from which on no more proofs are accepted.
+    pub expiry_slot: u64,
+    /// Sybil mode: verifications of this verifier with the same campaign share a nullifier,
+    /// a credential holder can pass only one of them.
+    pub campaign_id: Option<[u8; 31]>,
+}
+
 #[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize, LightDiscriminator)]
 pub struct IssuerAccount {
+    /// Stable issuer identity, the key that created the issuer.
+    /// Credentials and verifiers reference it across authority rotations.
     pub issuer_pubkey: Pubkey,
     pub num_credentials_issued: u64,
+    /// Keys that act for the issuer.
+    pub authority: IssuerAuthority,
+}
+
+/// M-of-N authority set of an issuer.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct IssuerAuthority {
+    pub authorities: Vec<Pubkey>,
+    /// Number of authorities that must sign.
+    pub threshold: u8,
+}
+
+impl IssuerAuthority {
+    /// Single authority, as created by `create_issuer`.
+    pub fn single(authority: Pubkey) -> Self {
+        Self {
+            authorities: vec![authority],
+            threshold: 1,
+        }
+    }
+
+    /// Checks that the threshold is reachable and the authorities are unique.
+    pub fn validate(&self) -> Result<()> {
+        if self.authorities.is_empty()
+            || self.authorities.len() > MAX_ISSUER_AUTHORITIES
+            || self.threshold == 0
+            || self.threshold as usize > self.authorities.len()
+        {
+            return err!(ErrorCode::InvalidIssuerAuthority);
+        }
+        for (i, authority) in self.authorities.iter().enumerate() {
+            if self.authorities[i + 1..].contains(authority) {
+                return err!(ErrorCode::InvalidIssuerAuthority);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that at least `threshold` authorities signed,
+    /// either as the fee paying signer or as signer in `co_signers`.
+    pub fn check_signers(&self, signer: &AccountInfo, co_signers: &[AccountInfo]) -> Result<()> {
+        let signatures = self
+            .authorities
+            .iter()
+            .filter(|authority| {
+                (signer.is_signer && signer.key == *authority)
+                    || co_signers
+                        .iter()
+                        .any(|account| account.is_signer && account.key == *authority)
+            })
+            .count();
+        if signatures < self.threshold as usize {
+            return err!(ErrorCode::MissingIssuerSignatures);
+        }
+        Ok(())
+    }
 }
 
 #[error_code]
@@ -363,4 +1300,36 @@ pub enum ErrorCode {
     InvalidIssuer,
     #[msg("Not enough keys in remaining accounts")]
     AccountNotEnoughKeys,
+    #[msg("current_slot is in the future or too old")]
+    InvalidCurrentSlot,
+    #[msg("Issuer authority threshold must be between 1 and the number of unique authorities")]
+    InvalidIssuerAuthority,
+    #[msg("Not enough issuer authorities signed")]
+    MissingIssuerSignatures,
+    #[msg("A verification must allow 1 to MAX_ALLOWED_ISSUERS issuers or have an issuer set")]
+    InvalidAllowedIssuers,
+    #[msg("Verification is expired")]
+    VerificationExpired,
+    #[msg("Issuer is not allowed by this verification")]
+    IssuerNotAllowed,
+    #[msg("Verifying key is incomplete or has too many IC points")]
+    InvalidVerifyingKey,
+    #[msg("Verifying key does not belong to a circuit of this verification")]
+    InvalidCircuit,
+    #[msg("A verification must accept between 1 and MAX_VERIFICATION_CIRCUITS circuits")]
+    InvalidCircuitIds,
+    #[msg("Root index is out of the state tree's root history")]
+    InvalidRootIndex,
+    #[msg("Event data is shorter than the encryption overhead")]
+    InvalidEventData,
+    #[msg("A batched verification must have between 1 and MAX_BATCH_CREDENTIAL_PROOFS proofs")]
+    InvalidCredentialProofs,
+    #[msg("Campaign id must not be zero")]
+    InvalidCampaignId,
+    #[msg("Verification has no issuer set")]
+    IssuerSetNotSupported,
+    #[msg("Signer is not the upgrade authority of the program")]
+    InvalidUpgradeAuthority,
+    #[msg("Proof root is more than MAX_ROOT_AGE roots older than the current root")]
+    StaleRoot,
 }
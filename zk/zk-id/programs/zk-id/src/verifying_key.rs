@@ -1,25 +0,0 @@
-use groth16_solana::groth16::Groth16Verifyingkey;
-
-pub const VERIFYINGKEY: Groth16Verifyingkey = Groth16Verifyingkey {
-	nr_pubinputs: 8,
-
-	vk_alpha_g1: [45u8, 77u8, 154u8, 167u8, 227u8, 2u8, 217u8, 223u8, 65u8, 116u8, 157u8, 85u8, 7u8, 148u8, 157u8, 5u8, 219u8, 234u8, 51u8, 251u8, 177u8, 108u8, 100u8, 59u8, 34u8, 245u8, 153u8, 162u8, 190u8, 109u8, 242u8, 226u8, 20u8, 190u8, 221u8, 80u8, 60u8, 55u8, 206u8, 176u8, 97u8, 216u8, 236u8, 96u8, 32u8, 159u8, 227u8, 69u8, 206u8, 137u8, 131u8, 10u8, 25u8, 35u8, 3u8, 1u8, 240u8, 118u8, 202u8, 255u8, 0u8, 77u8, 25u8, 38u8],
-
-	vk_beta_g2: [9u8, 103u8, 3u8, 47u8, 203u8, 247u8, 118u8, 209u8, 175u8, 201u8, 133u8, 248u8, 136u8, 119u8, 241u8, 130u8, 211u8, 132u8, 128u8, 166u8, 83u8, 242u8, 222u8, 202u8, 169u8, 121u8, 76u8, 188u8, 59u8, 243u8, 6u8, 12u8, 14u8, 24u8, 120u8, 71u8, 173u8, 76u8, 121u8, 131u8, 116u8, 208u8, 214u8, 115u8, 43u8, 245u8, 1u8, 132u8, 125u8, 214u8, 139u8, 192u8, 224u8, 113u8, 36u8, 30u8, 2u8, 19u8, 188u8, 127u8, 193u8, 61u8, 183u8, 171u8, 48u8, 76u8, 251u8, 209u8, 224u8, 138u8, 112u8, 74u8, 153u8, 245u8, 232u8, 71u8, 217u8, 63u8, 140u8, 60u8, 170u8, 253u8, 222u8, 196u8, 107u8, 122u8, 13u8, 55u8, 157u8, 166u8, 154u8, 77u8, 17u8, 35u8, 70u8, 167u8, 23u8, 57u8, 193u8, 177u8, 164u8, 87u8, 168u8, 199u8, 49u8, 49u8, 35u8, 210u8, 77u8, 47u8, 145u8, 146u8, 248u8, 150u8, 183u8, 198u8, 62u8, 234u8, 5u8, 169u8, 213u8, 127u8, 6u8, 84u8, 122u8, 208u8, 206u8, 200u8],
-
-	vk_gamma_g2: [25u8, 142u8, 147u8, 147u8, 146u8, 13u8, 72u8, 58u8, 114u8, 96u8, 191u8, 183u8, 49u8, 251u8, 93u8, 37u8, 241u8, 170u8, 73u8, 51u8, 53u8, 169u8, 231u8, 18u8, 151u8, 228u8, 133u8, 183u8, 174u8, 243u8, 18u8, 194u8, 24u8, 0u8, 222u8, 239u8, 18u8, 31u8, 30u8, 118u8, 66u8, 106u8, 0u8, 102u8, 94u8, 92u8, 68u8, 121u8, 103u8, 67u8, 34u8, 212u8, 247u8, 94u8, 218u8, 221u8, 70u8, 222u8, 189u8, 92u8, 217u8, 146u8, 246u8, 237u8, 9u8, 6u8, 137u8, 208u8, 88u8, 95u8, 240u8, 117u8, 236u8, 158u8, 153u8, 173u8, 105u8, 12u8, 51u8, 149u8, 188u8, 75u8, 49u8, 51u8, 112u8, 179u8, 142u8, 243u8, 85u8, 172u8, 218u8, 220u8, 209u8, 34u8, 151u8, 91u8, 18u8, 200u8, 94u8, 165u8, 219u8, 140u8, 109u8, 235u8, 74u8, 171u8, 113u8, 128u8, 141u8, 203u8, 64u8, 143u8, 227u8, 209u8, 231u8, 105u8, 12u8, 67u8, 211u8, 123u8, 76u8, 230u8, 204u8, 1u8, 102u8, 250u8, 125u8, 170u8],
-
-	vk_delta_g2: [22u8, 174u8, 147u8, 208u8, 172u8, 122u8, 235u8, 140u8, 107u8, 118u8, 81u8, 111u8, 106u8, 194u8, 142u8, 249u8, 49u8, 219u8, 251u8, 148u8, 230u8, 183u8, 217u8, 43u8, 254u8, 249u8, 149u8, 220u8, 193u8, 162u8, 70u8, 224u8, 35u8, 200u8, 228u8, 224u8, 29u8, 126u8, 95u8, 148u8, 140u8, 33u8, 169u8, 73u8, 11u8, 41u8, 152u8, 125u8, 85u8, 13u8, 242u8, 87u8, 214u8, 173u8, 202u8, 8u8, 45u8, 5u8, 68u8, 172u8, 38u8, 158u8, 137u8, 206u8, 34u8, 202u8, 165u8, 71u8, 76u8, 215u8, 55u8, 155u8, 9u8, 163u8, 153u8, 106u8, 255u8, 102u8, 135u8, 117u8, 194u8, 142u8, 126u8, 204u8, 46u8, 37u8, 0u8, 202u8, 91u8, 141u8, 174u8, 201u8, 181u8, 49u8, 91u8, 203u8, 9u8, 137u8, 122u8, 49u8, 71u8, 118u8, 0u8, 207u8, 26u8, 15u8, 37u8, 137u8, 232u8, 202u8, 186u8, 81u8, 70u8, 58u8, 123u8, 237u8, 97u8, 196u8, 207u8, 179u8, 215u8, 125u8, 98u8, 153u8, 184u8, 27u8, 176u8, 77u8],
-
-	vk_ic: &[
-		[6u8, 116u8, 25u8, 196u8, 168u8, 139u8, 50u8, 234u8, 24u8, 80u8, 72u8, 23u8, 1u8, 43u8, 230u8, 178u8, 165u8, 176u8, 101u8, 164u8, 240u8, 6u8, 228u8, 49u8, 213u8, 82u8, 106u8, 117u8, 102u8, 23u8, 57u8, 101u8, 6u8, 0u8, 22u8, 209u8, 170u8, 16u8, 2u8, 213u8, 10u8, 138u8, 98u8, 195u8, 189u8, 38u8, 61u8, 204u8, 249u8, 53u8, 98u8, 199u8, 88u8, 168u8, 175u8, 253u8, 183u8, 49u8, 46u8, 214u8, 46u8, 22u8, 20u8, 9u8],
-		[13u8, 221u8, 187u8, 171u8, 240u8, 78u8, 162u8, 23u8, 234u8, 32u8, 105u8, 191u8, 86u8, 204u8, 31u8, 214u8, 84u8, 22u8, 120u8, 70u8, 144u8, 122u8, 215u8, 127u8, 216u8, 149u8, 239u8, 74u8, 185u8, 2u8, 147u8, 164u8, 34u8, 238u8, 33u8, 206u8, 139u8, 82u8, 64u8, 206u8, 243u8, 191u8, 84u8, 63u8, 216u8, 199u8, 125u8, 154u8, 86u8, 80u8, 44u8, 178u8, 22u8, 156u8, 128u8, 249u8, 100u8, 55u8, 165u8, 160u8, 31u8, 78u8, 83u8, 68u8],
-		[43u8, 76u8, 131u8, 233u8, 121u8, 94u8, 117u8, 36u8, 121u8, 55u8, 64u8, 127u8, 145u8, 255u8, 102u8, 135u8, 55u8, 68u8, 113u8, 27u8, 67u8, 241u8, 109u8, 31u8, 175u8, 63u8, 165u8, 56u8, 19u8, 134u8, 234u8, 121u8, 44u8, 171u8, 222u8, 10u8, 13u8, 87u8, 190u8, 105u8, 216u8, 104u8, 36u8, 85u8, 97u8, 66u8, 129u8, 2u8, 137u8, 196u8, 48u8, 167u8, 118u8, 232u8, 5u8, 215u8, 87u8, 13u8, 135u8, 125u8, 159u8, 116u8, 86u8, 67u8],
-		[19u8, 142u8, 242u8, 236u8, 76u8, 140u8, 97u8, 35u8, 86u8, 205u8, 20u8, 168u8, 70u8, 9u8, 250u8, 3u8, 49u8, 94u8, 8u8, 224u8, 96u8, 18u8, 163u8, 69u8, 176u8, 33u8, 254u8, 86u8, 2u8, 209u8, 227u8, 61u8, 15u8, 135u8, 181u8, 253u8, 64u8, 195u8, 56u8, 76u8, 225u8, 192u8, 167u8, 233u8, 108u8, 252u8, 3u8, 177u8, 160u8, 224u8, 183u8, 203u8, 75u8, 131u8, 176u8, 112u8, 125u8, 148u8, 121u8, 119u8, 111u8, 57u8, 125u8, 168u8],
-		[7u8, 152u8, 186u8, 253u8, 239u8, 252u8, 239u8, 44u8, 124u8, 128u8, 115u8, 96u8, 18u8, 122u8, 90u8, 34u8, 220u8, 190u8, 223u8, 151u8, 158u8, 218u8, 50u8, 253u8, 25u8, 229u8, 119u8, 201u8, 161u8, 223u8, 178u8, 130u8, 19u8, 126u8, 235u8, 91u8, 131u8, 138u8, 103u8, 7u8, 26u8, 13u8, 203u8, 168u8, 157u8, 140u8, 119u8, 151u8, 239u8, 214u8, 41u8, 231u8, 229u8, 103u8, 234u8, 198u8, 88u8, 249u8, 131u8, 68u8, 229u8, 163u8, 99u8, 41u8],
-		[37u8, 58u8, 169u8, 29u8, 158u8, 74u8, 77u8, 127u8, 202u8, 187u8, 181u8, 170u8, 44u8, 94u8, 0u8, 214u8, 60u8, 127u8, 104u8, 198u8, 212u8, 0u8, 178u8, 107u8, 109u8, 133u8, 127u8, 196u8, 61u8, 122u8, 122u8, 42u8, 11u8, 1u8, 147u8, 194u8, 208u8, 97u8, 68u8, 193u8, 255u8, 251u8, 141u8, 71u8, 179u8, 93u8, 218u8, 145u8, 239u8, 38u8, 121u8, 23u8, 203u8, 219u8, 186u8, 172u8, 120u8, 223u8, 128u8, 207u8, 171u8, 134u8, 138u8, 162u8],
-		[40u8, 188u8, 141u8, 174u8, 217u8, 229u8, 72u8, 52u8, 232u8, 100u8, 30u8, 241u8, 1u8, 223u8, 99u8, 28u8, 92u8, 174u8, 57u8, 226u8, 1u8, 248u8, 128u8, 113u8, 182u8, 58u8, 158u8, 203u8, 148u8, 254u8, 239u8, 244u8, 18u8, 100u8, 144u8, 201u8, 214u8, 77u8, 169u8, 224u8, 59u8, 121u8, 163u8, 151u8, 16u8, 148u8, 39u8, 2u8, 224u8, 123u8, 35u8, 102u8, 204u8, 233u8, 93u8, 76u8, 101u8, 111u8, 134u8, 229u8, 26u8, 122u8, 215u8, 151u8],
-		[23u8, 165u8, 206u8, 29u8, 47u8, 19u8, 149u8, 232u8, 48u8, 112u8, 50u8, 201u8, 139u8, 236u8, 226u8, 197u8, 196u8, 56u8, 89u8, 146u8, 41u8, 46u8, 89u8, 82u8, 37u8, 150u8, 132u8, 32u8, 149u8, 44u8, 222u8, 131u8, 33u8, 96u8, 209u8, 108u8, 151u8, 147u8, 48u8, 101u8, 230u8, 25u8, 214u8, 140u8, 213u8, 36u8, 230u8, 174u8, 147u8, 71u8, 156u8, 44u8, 80u8, 171u8, 47u8, 45u8, 65u8, 213u8, 224u8, 147u8, 196u8, 223u8, 20u8, 230u8],
-		[14u8, 5u8, 129u8, 121u8, 109u8, 169u8, 0u8, 255u8, 184u8, 100u8, 232u8, 96u8, 112u8, 158u8, 194u8, 213u8, 77u8, 115u8, 130u8, 240u8, 160u8, 240u8, 243u8, 129u8, 8u8, 46u8, 239u8, 65u8, 188u8, 28u8, 236u8, 222u8, 4u8, 104u8, 112u8, 160u8, 235u8, 48u8, 64u8, 159u8, 177u8, 251u8, 42u8, 11u8, 252u8, 133u8, 19u8, 169u8, 77u8, 173u8, 99u8, 145u8, 171u8, 116u8, 203u8, 34u8, 179u8, 218u8, 18u8, 120u8, 163u8, 75u8, 157u8, 15u8],
-	]
-};
@@ -20,7 +20,7 @@ PROJECT_DIR="$(dirname "$SCRIPT_DIR")"
 cd "$PROJECT_DIR"
 
 # Check if node and npm are installed
-echo -e "${BLUE}[1/7]${NC} Checking dependencies..."
+echo -e "${BLUE}[1/5]${NC} Checking dependencies..."
 if ! command -v node &> /dev/null; then
     echo -e "${RED}Error: Node.js is not installed${NC}"
     echo "Please install Node.js from https://nodejs.org/"
@@ -48,20 +48,20 @@ echo -e "${GREEN}✓${NC} All required tools are installed"
 echo ""
 
 # Install npm dependencies
-echo -e "${BLUE}[2/7]${NC} Installing npm dependencies..."
+echo -e "${BLUE}[2/5]${NC} Installing npm dependencies..."
 npm install
 echo -e "${GREEN}✓${NC} Dependencies installed"
 echo ""
 
 # Create necessary directories
-echo -e "${BLUE}[3/7]${NC} Creating build directories..."
+echo -e "${BLUE}[3/5]${NC} Creating build directories..."
 mkdir -p pot
 mkdir -p build
 echo -e "${GREEN}✓${NC} Directories created"
 echo ""
 
 # Download Powers of Tau
-echo -e "${BLUE}[4/7]${NC} Downloading Powers of Tau ceremony file..."
+echo -e "${BLUE}[4/5]${NC} Downloading Powers of Tau ceremony file..."
 PTAU_FILE="pot/powersOfTau28_hez_final_16.ptau"
 
 if [ -f "$PTAU_FILE" ]; then
@@ -130,78 +130,16 @@ else
 fi
 echo ""
 
-# Compile the circuit
-echo -e "${BLUE}[5/7]${NC} Compiling circom circuit..."
+# Compile the circuits, generate the zkeys and export the verification keys.
+# The artifact step checks the circuit sources against circuits/manifest.json,
+# verifies the zkeys and verification keys against the circuits and stamps them in build/artifacts.json.
+echo -e "${BLUE}[5/5]${NC} Generating circuit artifacts..."
 echo "This may take several minutes depending on circuit complexity..."
-circom circuits/compressed_account_merkle_proof.circom \
-    --r1cs \
-    --wasm \
-    --sym \
-    --verbose \
-    -o build
-
-if [ $? -eq 0 ]; then
-    echo -e "${GREEN}✓${NC} Circuit compiled successfully"
-    echo "  - Generated: build/compressed_account_merkle_proof.r1cs"
-    echo "  - Generated: build/compressed_account_merkle_proof.wasm"
-    echo "  - Generated: build/compressed_account_merkle_proof.sym"
-else
-    echo -e "${RED}Error: Circuit compilation failed${NC}"
-    exit 1
-fi
-echo ""
-
-# Generate the initial zkey
-echo -e "${BLUE}[6/7]${NC} Generating proving key (zkey)..."
-echo "Running Groth16 setup (this may take several minutes)..."
-npx snarkjs groth16 setup \
-    build/compressed_account_merkle_proof.r1cs \
-    "$PTAU_FILE" \
-    build/circuit_0000.zkey
-
-if [ $? -eq 0 ]; then
-    echo -e "${GREEN}✓${NC} Initial zkey generated"
-else
-    echo -e "${RED}Error: zkey generation failed${NC}"
-    exit 1
-fi
-
-# Contribute to the ceremony
-echo ""
-echo "Contributing to the ceremony..."
-# Generate real randomness from /dev/urandom combined with system entropy
-# This creates 256 bits of entropy for the contribution
-RANDOM_ENTROPY=$(head -c 32 /dev/urandom | xxd -p -c 256)
-SYSTEM_ENTROPY="${RANDOM}${RANDOM}${RANDOM}$(date +%s%N)$(uname -a | sha256sum | cut -d' ' -f1)"
-COMBINED_ENTROPY="${RANDOM_ENTROPY}${SYSTEM_ENTROPY}"
-
-npx snarkjs zkey contribute \
-    build/circuit_0000.zkey \
-    build/compressed_account_merkle_proof_final.zkey \
-    --name="First contribution" \
-    -v \
-    -e="$COMBINED_ENTROPY"
-
-if [ $? -eq 0 ]; then
-    echo -e "${GREEN}✓${NC} Contribution complete"
-else
-    echo -e "${RED}Error: Contribution failed${NC}"
-    exit 1
-fi
-echo ""
-
-# Export verification key
-echo -e "${BLUE}[7/7]${NC} Exporting verification key..."
-npx snarkjs zkey export verificationkey \
-    build/compressed_account_merkle_proof_final.zkey \
-    build/verification_key.json
-
-if [ $? -eq 0 ]; then
-    echo -e "${GREEN}✓${NC} Verification key exported to build/verification_key.json"
-else
-    echo -e "${RED}Error: Verification key export failed${NC}"
+if ! cargo run --release -p zk-id-artifacts -- generate "$PTAU_FILE"; then
+    echo -e "${RED}Error: Circuit artifact generation failed${NC}"
     exit 1
 fi
+echo -e "${GREEN}✓${NC} Circuit artifacts generated and verified"
 echo ""
 
 # Print summary
@@ -209,12 +147,13 @@ echo -e "${GREEN}======================================${NC}"
 echo -e "${GREEN}Setup Complete!${NC}"
 echo -e "${GREEN}======================================${NC}"
 echo ""
-echo "Generated files:"
-echo "  - build/compressed_account_merkle_proof.r1cs"
-echo "  - build/compressed_account_merkle_proof.wasm"
-echo "  - build/compressed_account_merkle_proof.sym"
-echo "  - build/compressed_account_merkle_proof_final.zkey"
-echo "  - build/verification_key.json"
+echo "Generated files (v1 and v2 state tree circuits):"
+echo "  - build/compressed_account_merkle_proof{,_v2}.r1cs"
+echo "  - build/compressed_account_merkle_proof{,_v2}_js/*.wasm"
+echo "  - build/compressed_account_merkle_proof{,_v2}.sym"
+echo "  - build/compressed_account_merkle_proof{,_v2}_final.zkey"
+echo "  - build/verification_key{,_v2}.json"
+echo "  - build/artifacts.json (artifact hashes checked by build.rs)"
 echo ""
 echo "Next steps:"
 echo "  1. Run Rust tests: ${BLUE}cargo test test_compressed_account_merkle_proof_circuit${NC}"
@@ -9,7 +9,7 @@ use light_hasher::{
 };
 use light_merkle_tree_reference::MerkleTree;
 use num_bigint::BigUint;
-use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::signature::Keypair;
 use std::collections::HashMap;
 
 // Link the generated witness library
@@ -17,62 +17,57 @@ use std::collections::HashMap;
 extern "C" {}
 
 rust_witness::witness!(compressedaccountmerkleproof);
+rust_witness::witness!(compressedaccountmerkleproofv2);
+rust_witness::witness!(compressedaccountmerkleproofissuerset);
 
-// Use the verifying key from the library
+// Use the verifying key and credential keypair from the library
+use zk_id::client::{u64_to_field, CredentialKeypair, IssuerSet};
 use zk_id::verifying_key::VERIFYINGKEY;
+use zk_id::{campaign_input, NUM_CREDENTIAL_ATTRIBUTES, NUM_PUBLIC_INPUTS};
 
-/// Derives a credential keypair from a Solana keypair
-/// The private key is derived by signing "CREDENTIAL" and truncating to 248 bits
-/// The public key is Poseidon(private_key)
+/// Expiry and attributes of a credential and the public predicates proven about them
 #[derive(Debug, Clone)]
-struct CredentialKeypair {
-    pub private_key: [u8; 32], // 248 bits
-    pub public_key: [u8; 32],  // Poseidon hash of private key
+struct AttributeInputs {
+    pub expiry_slot: u64,
+    pub attributes: [u64; NUM_CREDENTIAL_ATTRIBUTES],
+    pub blinding: [u8; 32],
+    pub current_slot: u64,
+    pub attribute_min: [u64; NUM_CREDENTIAL_ATTRIBUTES],
+    pub attribute_max: [u64; NUM_CREDENTIAL_ATTRIBUTES],
 }
 
-impl CredentialKeypair {
-    pub fn new(solana_keypair: &Keypair) -> Self {
-        // Sign the message "CREDENTIAL" with the Solana keypair
-        let message = b"CREDENTIAL";
-        let signature = solana_keypair.sign_message(message);
-
-        // Hash the signature to get entropy
-        let hashed = Sha256::hash(signature.as_ref()).unwrap();
-
-        // Truncate to 248 bits (31 bytes) for BN254 field compatibility
-        let mut private_key = [0u8; 32];
-        private_key[1..32].copy_from_slice(&hashed[0..31]);
-
-        let public_key = Poseidon::hashv(&[&private_key]).unwrap();
-
+impl AttributeInputs {
+    /// Credential with age 25 expiring at slot 1000, proving age >= 18 at slot 100
+    pub fn new() -> Self {
+        let mut blinding = [5u8; 32];
+        blinding[0] = 0;
         Self {
-            private_key,
-            public_key,
+            expiry_slot: 1000,
+            attributes: [25, 276, 0, 0],
+            blinding,
+            current_slot: 100,
+            attribute_min: [18, 0, 0, 0],
+            attribute_max: [u64::MAX; NUM_CREDENTIAL_ATTRIBUTES],
         }
     }
 
-    /// Get the private key as a BigUint for circuit input
-    pub fn private_key_biguint(&self) -> BigUint {
-        BigUint::from_bytes_be(&self.private_key)
+    /// attributes_hash = Poseidon(attributes, blinding)
+    pub fn attributes_hash(&self) -> [u8; 32] {
+        let attributes: Vec<[u8; 32]> = self.attributes.iter().map(|a| u64_to_field(*a)).collect();
+        let mut inputs: Vec<&[u8]> = attributes.iter().map(|a| a.as_slice()).collect();
+        inputs.push(self.blinding.as_slice());
+        Poseidon::hashv(&inputs).unwrap()
     }
 
-    /// Compute nullifier for a given verification_id and issuer
-    /// Nullifier = Poseidon(verification_id, private_key, data_hash)
-    /// where data_hash = Poseidon(issuer_hashed, public_key)
-    pub fn compute_nullifier(
-        &self,
-        verification_id: &[u8; 31],
-        issuer_hashed: &[u8; 32],
-    ) -> [u8; 32] {
-        // First compute data_hash = Poseidon(issuer_hashed, public_key)
-        let data_hash = Poseidon::hashv(&[issuer_hashed, &self.public_key]).unwrap();
-
-        // Then compute nullifier = Poseidon(verification_id, private_key, data_hash)
-        // verification_id needs to be padded to 32 bytes for Poseidon
-        let mut padded_verification = [0u8; 32];
-        padded_verification[1..32].copy_from_slice(verification_id);
-
-        Poseidon::hashv(&[&padded_verification, &self.private_key, &data_hash]).unwrap()
+    /// data_hash = Poseidon(issuer_hashed, credential_pubkey, expiry_slot, attributes_hash)
+    pub fn data_hash(&self, issuer_hashed: &[u8; 32], credential_pubkey: &[u8; 32]) -> [u8; 32] {
+        Poseidon::hashv(&[
+            issuer_hashed,
+            credential_pubkey,
+            &u64_to_field(self.expiry_slot),
+            &self.attributes_hash(),
+        ])
+        .unwrap()
     }
 }
 
@@ -119,8 +114,9 @@ fn add_compressed_account_to_circuit_inputs(
     let mut encrypted_data_hash = Sha256::hash(&hash_input).unwrap();
     encrypted_data_hash[0] = 0;
 
-    // Compute nullifier using credential private key, verification_id, and issuer
-    let nullifier = credential.compute_nullifier(verification_id, &issuer_hashed);
+    // Compute nullifier using credential private key, verification_id, and the credential data hash
+    let data_hash = compressed_account.data.as_ref().unwrap().data_hash;
+    let nullifier = credential.compute_nullifier(verification_id, &data_hash);
 
     // Add all inputs to the HashMap
     inputs.insert(
@@ -160,7 +156,7 @@ fn add_compressed_account_to_circuit_inputs(
     // Add credential private key (private input)
     inputs.insert(
         "credentialPrivateKey".to_string(),
-        vec![credential.private_key_biguint().to_string()],
+        vec![BigUint::from_bytes_be(&credential.private_key).to_string()],
     );
 
     // Add verification_id (public input) - pad to 32 bytes
@@ -185,6 +181,57 @@ fn add_compressed_account_to_circuit_inputs(
         "nullifier".to_string(),
         vec![BigUint::from_bytes_be(&nullifier).to_string()],
     );
+
+    // No campaign, see add_campaign_to_circuit_inputs
+    add_campaign_to_circuit_inputs(inputs, &[0u8; 32], &[0u8; 32]);
+}
+
+/// Helper function to add the campaign inputs of a sybil mode verification to the circuit inputs HashMap
+///
+/// # Arguments
+/// * `inputs` - Mutable reference to the HashMap that will be populated with circuit inputs
+/// * `campaign` - campaign_input(verifier, campaign_id), zero for verifications without a campaign
+/// * `campaign_nullifier` - Poseidon(campaign, credential private key), zero without a campaign
+fn add_campaign_to_circuit_inputs(
+    inputs: &mut HashMap<String, Vec<String>>,
+    campaign: &[u8; 32],
+    campaign_nullifier: &[u8; 32],
+) {
+    inputs.insert(
+        "campaign_id".to_string(),
+        vec![BigUint::from_bytes_be(campaign).to_string()],
+    );
+    inputs.insert(
+        "campaign_nullifier".to_string(),
+        vec![BigUint::from_bytes_be(campaign_nullifier).to_string()],
+    );
+}
+
+/// Helper function to add issuer set inputs to the circuit inputs HashMap
+///
+/// # Arguments
+/// * `inputs` - Mutable reference to the HashMap that will be populated with circuit inputs
+/// * `issuer_set_root` - Root of the issuer set Merkle tree
+/// * `issuer_index` - Position of the issuer in the issuer set
+/// * `issuer_path` - Path elements of the issuer in the issuer set Merkle tree
+fn add_issuer_set_to_circuit_inputs(
+    inputs: &mut HashMap<String, Vec<String>>,
+    issuer_set_root: &[u8; 32],
+    issuer_index: u32,
+    issuer_path: &[[u8; 32]],
+) {
+    inputs.insert(
+        "issuer_set_root".to_string(),
+        vec![BigUint::from_bytes_be(issuer_set_root).to_string()],
+    );
+    inputs.insert("issuer_index".to_string(), vec![issuer_index.to_string()]);
+    inputs.insert(
+        "issuerPathElements".to_string(),
+        issuer_path
+            .iter()
+            .map(|hash| BigUint::from_bytes_be(hash).to_string())
+            .collect(),
+    );
 }
 
 /// Helper function to add Merkle proof inputs to the circuit inputs HashMap
@@ -213,6 +260,39 @@ fn add_merkle_proof_to_circuit_inputs(
     );
 }
 
+/// Helper function to add expiry and attribute inputs to the circuit inputs HashMap
+fn add_attributes_to_circuit_inputs(
+    inputs: &mut HashMap<String, Vec<String>>,
+    attribute_inputs: &AttributeInputs,
+) {
+    let to_strings = |values: &[u64]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+
+    inputs.insert(
+        "expiry_slot".to_string(),
+        vec![attribute_inputs.expiry_slot.to_string()],
+    );
+    inputs.insert(
+        "attributes".to_string(),
+        to_strings(&attribute_inputs.attributes),
+    );
+    inputs.insert(
+        "attributes_blinding".to_string(),
+        vec![BigUint::from_bytes_be(&attribute_inputs.blinding).to_string()],
+    );
+    inputs.insert(
+        "current_slot".to_string(),
+        vec![attribute_inputs.current_slot.to_string()],
+    );
+    inputs.insert(
+        "attribute_min".to_string(),
+        to_strings(&attribute_inputs.attribute_min),
+    );
+    inputs.insert(
+        "attribute_max".to_string(),
+        to_strings(&attribute_inputs.attribute_max),
+    );
+}
+
 #[test]
 fn test_compressed_account_merkle_proof_circuit() {
     let zkey_path = "./build/compressed_account_merkle_proof_final.zkey".to_string();
@@ -237,7 +317,8 @@ fn test_compressed_account_merkle_proof_circuit() {
     // Compute data_hash as hash of issuer and credential commitment
     let issuer_hashed =
         hashv_to_bn254_field_size_be_const_array::<2>(&[issuer_pubkey.as_ref()]).unwrap();
-    let data_hash = Poseidon::hashv(&[issuer_hashed.as_slice(), &credential.public_key]).unwrap();
+    let attribute_inputs = AttributeInputs::new();
+    let data_hash = attribute_inputs.data_hash(&issuer_hashed, &credential.public_key);
 
     let compressed_account = CompressedAccount {
         owner,
@@ -276,6 +357,7 @@ fn test_compressed_account_merkle_proof_circuit() {
         &encrypted_data,
     );
     add_merkle_proof_to_circuit_inputs(&mut proof_inputs, &merkle_proof_hashes, &merkle_root);
+    add_attributes_to_circuit_inputs(&mut proof_inputs, &attribute_inputs);
 
     // Generate and verify proof
     let circuit_inputs = serde_json::to_string(&proof_inputs).unwrap();
@@ -293,6 +375,91 @@ fn test_compressed_account_merkle_proof_circuit() {
     assert!(is_valid, "Proof should be valid");
 }
 
+#[test]
+fn test_compressed_account_merkle_proof_circuit_v2() {
+    let zkey_path = "./build/compressed_account_merkle_proof_v2_final.zkey".to_string();
+
+    // Create test data, a leaf index != 0 to distinguish the batched (BE) leaf index encoding
+    let owner = Pubkey::new_from_array([1u8; 32]);
+    let merkle_tree_pubkey = Pubkey::new_from_array([2u8; 32]);
+    let leaf_index: u32 = 1;
+    let issuer_pubkey = Pubkey::new_from_array([4u8; 32]);
+
+    let user_keypair = Keypair::new();
+    let credential = CredentialKeypair::new(&user_keypair);
+
+    let encrypted_data = vec![6u8; 64];
+    let mut address = [3u8; 32];
+    address[0] = 0;
+    let verification_id = [7u8; 31];
+
+    let issuer_hashed =
+        hashv_to_bn254_field_size_be_const_array::<2>(&[issuer_pubkey.as_ref()]).unwrap();
+    let attribute_inputs = AttributeInputs::new();
+    let data_hash = attribute_inputs.data_hash(&issuer_hashed, &credential.public_key);
+
+    let compressed_account = CompressedAccount {
+        owner,
+        lamports: 0,
+        address: Some(address),
+        data: Some(CompressedAccountData {
+            discriminator: [1u8; 8],
+            data: vec![],
+            data_hash,
+        }),
+    };
+
+    // Account hash of a batched (v2) state tree
+    let compressed_account_hash = compressed_account
+        .hash(&merkle_tree_pubkey, &leaf_index, true)
+        .unwrap();
+
+    // v2 state trees have 32 levels
+    let mut merkle_tree = MerkleTree::<Poseidon>::new(32, 0);
+    merkle_tree.append(&[1u8; 32]).unwrap();
+    merkle_tree.append(&compressed_account_hash).unwrap();
+
+    let merkle_proof_hashes = merkle_tree
+        .get_proof_of_leaf(leaf_index as usize, false)
+        .unwrap();
+    let merkle_root = merkle_tree.root();
+
+    let mut proof_inputs = HashMap::new();
+    add_compressed_account_to_circuit_inputs(
+        &mut proof_inputs,
+        &compressed_account,
+        &merkle_tree_pubkey,
+        leaf_index,
+        &issuer_pubkey,
+        &credential,
+        &verification_id,
+        &encrypted_data,
+    );
+    // Batched trees hash the leaf index big-endian
+    let mut account_leaf_index_bytes = [0u8; 32];
+    account_leaf_index_bytes[28..32].copy_from_slice(&leaf_index.to_be_bytes());
+    proof_inputs.insert(
+        "account_leaf_index".to_string(),
+        vec![BigUint::from_bytes_be(&account_leaf_index_bytes).to_string()],
+    );
+    add_merkle_proof_to_circuit_inputs(&mut proof_inputs, &merkle_proof_hashes, &merkle_root);
+    add_attributes_to_circuit_inputs(&mut proof_inputs, &attribute_inputs);
+
+    let circuit_inputs = serde_json::to_string(&proof_inputs).unwrap();
+    let proof = CircomProver::prove(
+        ProofLib::Arkworks,
+        WitnessFn::RustWitness(compressedaccountmerkleproofv2_witness),
+        circuit_inputs,
+        zkey_path.clone(),
+    )
+    .expect("Proof generation failed");
+
+    let is_valid = CircomProver::verify(ProofLib::Arkworks, proof, zkey_path)
+        .expect("Proof verification failed");
+
+    assert!(is_valid, "Proof should be valid");
+}
+
 #[test]
 fn test_invalid_proof_rejected() {
     let zkey_path = "./build/compressed_account_merkle_proof_final.zkey".to_string();
@@ -316,7 +483,8 @@ fn test_invalid_proof_rejected() {
     // Compute data_hash as hash of issuer and credential commitment
     let issuer_hashed =
         hashv_to_bn254_field_size_be_const_array::<2>(&[issuer_pubkey.as_ref()]).unwrap();
-    let data_hash = Poseidon::hashv(&[issuer_hashed.as_slice(), &credential.public_key]).unwrap();
+    let attribute_inputs = AttributeInputs::new();
+    let data_hash = attribute_inputs.data_hash(&issuer_hashed, &credential.public_key);
 
     let compressed_account = CompressedAccount {
         owner,
@@ -355,6 +523,7 @@ fn test_invalid_proof_rejected() {
 
     let invalid_root = [0u8; 32];
     add_merkle_proof_to_circuit_inputs(&mut proof_inputs, &merkle_proof_hashes, &invalid_root);
+    add_attributes_to_circuit_inputs(&mut proof_inputs, &attribute_inputs);
 
     // Generate proof (succeeds even with wrong root)
     let circuit_inputs = serde_json::to_string(&proof_inputs).unwrap();
@@ -397,7 +566,8 @@ fn test_groth16_solana_verification() {
     // Compute data_hash as hash of issuer and credential commitment
     let issuer_hashed =
         hashv_to_bn254_field_size_be_const_array::<2>(&[issuer_pubkey.as_ref()]).unwrap();
-    let data_hash = Poseidon::hashv(&[issuer_hashed.as_slice(), &credential.public_key]).unwrap();
+    let attribute_inputs = AttributeInputs::new();
+    let data_hash = attribute_inputs.data_hash(&issuer_hashed, &credential.public_key);
 
     let compressed_account = CompressedAccount {
         owner,
@@ -436,6 +606,7 @@ fn test_groth16_solana_verification() {
         &encrypted_data,
     );
     add_merkle_proof_to_circuit_inputs(&mut proof_inputs, &merkle_proof_hashes, &merkle_root);
+    add_attributes_to_circuit_inputs(&mut proof_inputs, &attribute_inputs);
 
     // Generate proof with circom-prover
     let circuit_inputs = serde_json::to_string(&proof_inputs).unwrap();
@@ -455,7 +626,7 @@ fn test_groth16_solana_verification() {
 
     // Convert proof and public inputs to groth16-solana format
     let (proof_a, proof_b, proof_c) = convert_proof(&proof.proof).expect("Failed to convert proof");
-    let public_inputs: [[u8; 32]; 8] = convert_public_inputs(&proof.pub_inputs);
+    let public_inputs: [[u8; 32]; NUM_PUBLIC_INPUTS] = convert_public_inputs(&proof.pub_inputs);
 
     // Verify with groth16-solana
     let mut verifier =
@@ -464,3 +635,275 @@ fn test_groth16_solana_verification() {
 
     verifier.verify().expect("Groth16 verification failed");
 }
+
+/// Proves a valid credential with `attribute_inputs` and returns whether the proof verifies.
+fn prove_credential_attributes(attribute_inputs: &AttributeInputs) -> bool {
+    prove_credential(attribute_inputs, |_, _| {})
+}
+
+/// Proves a valid credential with `attribute_inputs` and the inputs changed by `modify_inputs`,
+/// returns whether the proof verifies.
+fn prove_credential(
+    attribute_inputs: &AttributeInputs,
+    modify_inputs: impl FnOnce(&mut HashMap<String, Vec<String>>, &CredentialKeypair),
+) -> bool {
+    prove_credential_with_circuit(
+        attribute_inputs,
+        WitnessFn::RustWitness(compressedaccountmerkleproof_witness),
+        "./build/compressed_account_merkle_proof_final.zkey",
+        modify_inputs,
+    )
+}
+
+/// Proves a valid credential of issuer [4u8; 32] with the circuit of `witness_fn` and `zkey_path`,
+/// the inputs changed by `modify_inputs`, returns whether the proof verifies.
+fn prove_credential_with_circuit(
+    attribute_inputs: &AttributeInputs,
+    witness_fn: WitnessFn,
+    zkey_path: &str,
+    modify_inputs: impl FnOnce(&mut HashMap<String, Vec<String>>, &CredentialKeypair),
+) -> bool {
+    let zkey_path = zkey_path.to_string();
+
+    let owner = Pubkey::new_from_array([1u8; 32]);
+    let merkle_tree_pubkey = Pubkey::new_from_array([2u8; 32]);
+    let leaf_index: u32 = 0;
+    let issuer_pubkey = Pubkey::new_from_array([4u8; 32]);
+
+    let user_keypair = Keypair::new();
+    let credential = CredentialKeypair::new(&user_keypair);
+
+    let encrypted_data = vec![6u8; 64];
+    let mut address = [3u8; 32];
+    address[0] = 0;
+    let verification_id = [7u8; 31];
+
+    let issuer_hashed =
+        hashv_to_bn254_field_size_be_const_array::<2>(&[issuer_pubkey.as_ref()]).unwrap();
+    let data_hash = attribute_inputs.data_hash(&issuer_hashed, &credential.public_key);
+
+    let compressed_account = CompressedAccount {
+        owner,
+        lamports: 0,
+        address: Some(address),
+        data: Some(CompressedAccountData {
+            discriminator: [1u8; 8],
+            data: vec![],
+            data_hash,
+        }),
+    };
+    let compressed_account_hash = compressed_account
+        .hash(&merkle_tree_pubkey, &leaf_index, false)
+        .unwrap();
+
+    let mut merkle_tree = MerkleTree::<Poseidon>::new(26, 0);
+    merkle_tree.append(&compressed_account_hash).unwrap();
+    let merkle_proof_hashes = merkle_tree
+        .get_proof_of_leaf(leaf_index as usize, false)
+        .unwrap();
+    let merkle_root = merkle_tree.root();
+
+    let mut proof_inputs = HashMap::new();
+    add_compressed_account_to_circuit_inputs(
+        &mut proof_inputs,
+        &compressed_account,
+        &merkle_tree_pubkey,
+        leaf_index,
+        &issuer_pubkey,
+        &credential,
+        &verification_id,
+        &encrypted_data,
+    );
+    add_merkle_proof_to_circuit_inputs(&mut proof_inputs, &merkle_proof_hashes, &merkle_root);
+    add_attributes_to_circuit_inputs(&mut proof_inputs, attribute_inputs);
+    modify_inputs(&mut proof_inputs, &credential);
+
+    let circuit_inputs = serde_json::to_string(&proof_inputs).unwrap();
+    let proof = CircomProver::prove(
+        ProofLib::Arkworks,
+        witness_fn,
+        circuit_inputs,
+        zkey_path.clone(),
+    )
+    .expect("Proof generation should succeed");
+
+    CircomProver::verify(ProofLib::Arkworks, proof, zkey_path)
+        .expect("Verification should return a result")
+}
+
+#[test]
+fn test_attribute_predicates() {
+    // age >= 18 and country == 276
+    let mut attribute_inputs = AttributeInputs::new();
+    attribute_inputs.attribute_min[1] = 276;
+    attribute_inputs.attribute_max[1] = 276;
+    assert!(
+        prove_credential_attributes(&attribute_inputs),
+        "Proof should be valid for satisfied predicates"
+    );
+
+    // age >= 30 does not hold for age 25
+    let mut attribute_inputs = AttributeInputs::new();
+    attribute_inputs.attribute_min[0] = 30;
+    assert!(
+        !prove_credential_attributes(&attribute_inputs),
+        "Proof should be invalid for an unsatisfied lower bound"
+    );
+
+    // country == 250 does not hold for country 276
+    let mut attribute_inputs = AttributeInputs::new();
+    attribute_inputs.attribute_min[1] = 250;
+    attribute_inputs.attribute_max[1] = 250;
+    assert!(
+        !prove_credential_attributes(&attribute_inputs),
+        "Proof should be invalid for an unsatisfied equality"
+    );
+}
+
+#[test]
+fn test_expired_credential_rejected() {
+    // The credential is valid up to the slot before expiry_slot
+    let mut attribute_inputs = AttributeInputs::new();
+    attribute_inputs.current_slot = attribute_inputs.expiry_slot - 1;
+    assert!(
+        prove_credential_attributes(&attribute_inputs),
+        "Proof should be valid before expiry"
+    );
+
+    attribute_inputs.current_slot = attribute_inputs.expiry_slot;
+    assert!(
+        !prove_credential_attributes(&attribute_inputs),
+        "Proof should be invalid at expiry_slot"
+    );
+}
+
+#[test]
+fn test_campaign_nullifier() {
+    let attribute_inputs = AttributeInputs::new();
+    let verifier = solana_sdk::pubkey::Pubkey::new_unique();
+    let campaign = campaign_input(&verifier, &[9u8; 31]);
+
+    assert!(
+        prove_credential(&attribute_inputs, |inputs, credential| {
+            let campaign_nullifier = credential.compute_campaign_nullifier(&campaign);
+            add_campaign_to_circuit_inputs(inputs, &campaign, &campaign_nullifier);
+        }),
+        "Proof should be valid for the campaign nullifier"
+    );
+
+    // The nullifier of another campaign
+    assert!(
+        !prove_credential(&attribute_inputs, |inputs, credential| {
+            let campaign_nullifier =
+                credential.compute_campaign_nullifier(&campaign_input(&verifier, &[8u8; 31]));
+            add_campaign_to_circuit_inputs(inputs, &campaign, &campaign_nullifier);
+        }),
+        "Proof should be invalid for the nullifier of another campaign"
+    );
+
+    // The nullifier of the same campaign id of another verifier
+    assert!(
+        !prove_credential(&attribute_inputs, |inputs, credential| {
+            let other_campaign =
+                campaign_input(&solana_sdk::pubkey::Pubkey::new_unique(), &[9u8; 31]);
+            let campaign_nullifier = credential.compute_campaign_nullifier(&other_campaign);
+            add_campaign_to_circuit_inputs(inputs, &campaign, &campaign_nullifier);
+        }),
+        "Proof should be invalid for the nullifier of another verifier's campaign"
+    );
+
+    // The nullifier of another credential holder
+    assert!(
+        !prove_credential(&attribute_inputs, |inputs, _| {
+            let campaign_nullifier =
+                CredentialKeypair::new(&Keypair::new()).compute_campaign_nullifier(&campaign);
+            add_campaign_to_circuit_inputs(inputs, &campaign, &campaign_nullifier);
+        }),
+        "Proof should be invalid for the nullifier of another credential holder"
+    );
+
+    // Without a campaign the campaign nullifier must be zero, it would link proofs otherwise
+    assert!(
+        !prove_credential(&attribute_inputs, |inputs, credential| {
+            let campaign_nullifier = credential.compute_campaign_nullifier(&[0u8; 32]);
+            add_campaign_to_circuit_inputs(inputs, &[0u8; 32], &campaign_nullifier);
+        }),
+        "Proof should be invalid for a non-zero nullifier without a campaign"
+    );
+}
+
+#[test]
+fn test_issuer_set_membership() {
+    let attribute_inputs = AttributeInputs::new();
+    let prove_issuer_set = |modify_inputs: &dyn Fn(&mut HashMap<String, Vec<String>>)| -> bool {
+        prove_credential_with_circuit(
+            &attribute_inputs,
+            WitnessFn::RustWitness(compressedaccountmerkleproofissuerset_witness),
+            "./build/compressed_account_merkle_proof_issuer_set_final.zkey",
+            |inputs, _| modify_inputs(inputs),
+        )
+    };
+
+    // The issuer of prove_credential_with_circuit is the second of three issuers
+    let issuer = solana_sdk::pubkey::Pubkey::new_from_array([4u8; 32]);
+    let issuer_set = IssuerSet::new(&[
+        solana_sdk::pubkey::Pubkey::new_unique(),
+        issuer,
+        solana_sdk::pubkey::Pubkey::new_unique(),
+    ])
+    .unwrap();
+    let (issuer_index, issuer_path) = issuer_set.merkle_proof(&issuer).unwrap();
+    assert!(
+        prove_issuer_set(&|inputs| {
+            add_issuer_set_to_circuit_inputs(
+                inputs,
+                &issuer_set.root(),
+                issuer_index,
+                &issuer_path,
+            );
+        }),
+        "Proof should be valid for an issuer in the issuer set"
+    );
+
+    // The root of a set without the issuer
+    let other_member = solana_sdk::pubkey::Pubkey::new_unique();
+    let other_issuer_set = IssuerSet::new(&[other_member]).unwrap();
+    assert!(
+        !prove_issuer_set(&|inputs| {
+            add_issuer_set_to_circuit_inputs(
+                inputs,
+                &other_issuer_set.root(),
+                issuer_index,
+                &issuer_path,
+            );
+        }),
+        "Proof should be invalid for the root of another issuer set"
+    );
+
+    // The path of another member of the set without the issuer
+    let (other_index, other_path) = other_issuer_set.merkle_proof(&other_member).unwrap();
+    assert!(
+        !prove_issuer_set(&|inputs| {
+            add_issuer_set_to_circuit_inputs(
+                inputs,
+                &other_issuer_set.root(),
+                other_index,
+                &other_path,
+            );
+        }),
+        "Proof should be invalid for an issuer that is not in the issuer set"
+    );
+
+    // The path of the issuer at another position
+    assert!(
+        !prove_issuer_set(&|inputs| {
+            add_issuer_set_to_circuit_inputs(
+                inputs,
+                &issuer_set.root(),
+                issuer_index + 1,
+                &issuer_path,
+            );
+        }),
+        "Proof should be invalid for a wrong issuer index"
+    );
+}
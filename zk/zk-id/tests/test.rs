@@ -1,87 +1,52 @@
 // #![cfg(feature = "test-sbf")]
 
 use anchor_lang::{InstructionData, ToAccountMetas};
-use circom_prover::{prover::ProofLib, witness::WitnessFn, CircomProver};
-use groth16_solana::proof_parser::circom_prover::convert_proof;
+use groth16_solana::groth16::Groth16Verifyingkey;
+use light_batched_merkle_tree::merkle_tree::BatchedMerkleTreeAccount;
 use light_client::indexer::CompressedAccount;
-use light_hasher::{hash_to_field_size::hash_to_bn254_field_size_be, Hasher, Poseidon, Sha256};
+use light_compressed_account::TreeType;
 use light_program_test::{
-    program_test::LightProgramTest, AddressWithTree, Indexer, ProgramTestConfig, Rpc, RpcError,
+    program_test::{setup_mock_program_data, LightProgramTest, TestRpc},
+    AddressWithTree, Indexer, ProgramTestConfig, Rpc, RpcError,
 };
 use light_sdk::{
     address::v2::derive_address,
-    instruction::{PackedAccounts, SystemAccountMetaConfig},
+    instruction::{
+        account_meta::CompressedAccountMetaBurn, PackedAccounts, SystemAccountMetaConfig,
+    },
+    LightDiscriminator,
 };
-use num_bigint::BigUint;
 use solana_sdk::{
-    instruction::Instruction,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
     signature::{Keypair, Signature, Signer},
 };
-use std::collections::HashMap;
-use zk_id::{CREDENTIAL, ISSUER, ZK_ID_CHECK};
-
-/// Derives a credential keypair from a Solana keypair
-/// The private key is derived by signing "CREDENTIAL" and truncating to 248 bits
-/// The public key is Poseidon(private_key)
-#[derive(Debug, Clone)]
-struct CredentialKeypair {
-    pub private_key: [u8; 32], // 248 bits
-    pub public_key: [u8; 32],  // Poseidon hash of private key
-}
-
-impl CredentialKeypair {
-    pub fn new(solana_keypair: &Keypair) -> Self {
-        // Sign the message "CREDENTIAL" with the Solana keypair
-        let message = b"CREDENTIAL";
-        let signature = solana_keypair.sign_message(message);
-
-        // Hash the signature to get entropy
-        let hashed = Sha256::hash(signature.as_ref()).unwrap();
-
-        // Truncate to 248 bits (31 bytes) for BN254 field compatibility
-        let mut private_key = [0u8; 32];
-        private_key[1..32].copy_from_slice(&hashed[0..31]);
-
-        let public_key = Poseidon::hashv(&[&private_key]).unwrap();
-
-        Self {
-            private_key,
-            public_key,
-        }
-    }
-
-    /// Compute nullifier for a given verification_id and issuer
-    /// Nullifier = Poseidon(verification_id, private_key, data_hash)
-    /// where data_hash = Poseidon(issuer_hashed, public_key)
-    pub fn compute_nullifier(
-        &self,
-        verification_id: &[u8; 31],
-        issuer_hashed: &[u8; 32],
-    ) -> [u8; 32] {
-        // First compute data_hash = Poseidon(issuer_hashed, public_key)
-        let data_hash = Poseidon::hashv(&[issuer_hashed, &self.public_key]).unwrap();
-
-        // Then compute nullifier = Poseidon(verification_id, private_key, data_hash)
-        // verification_id needs to be padded to 32 bytes for Poseidon
-        let mut padded_verification = [0u8; 32];
-        padded_verification[1..32].copy_from_slice(verification_id);
-
-        Poseidon::hashv(&[&padded_verification, &self.private_key, &data_hash]).unwrap()
-    }
-}
+use zk_id::client::encryption::{DisclosedAttributes, EventEncryptionKeypair};
+use zk_id::client::{
+    config_pda, get_credential_merkle_proof, program_data_pda, verify_credential_instruction,
+    verifying_key_pda, AttributePredicates, ClientError, CredentialAttributes, CredentialCircuit,
+    CredentialKeypair, CredentialProofParams, IssuerSet, VerifyCredentialParams,
+    VerifyCredentialsParams, CREDENTIAL_CIRCUIT_ID, CREDENTIAL_CIRCUIT_V2_ID,
+    CREDENTIAL_ISSUER_SET_CIRCUIT_ID, CREDENTIAL_ISSUER_SET_CIRCUIT_V2_ID,
+};
+use zk_id::{
+    campaign_input, CAMPAIGN_NULLIFIER, CREDENTIAL, ISSUER, NUM_CREDENTIAL_ATTRIBUTES, VERIFICATION,
+};
 
-// Link the generated witness library
-#[link(name = "circuit", kind = "static")]
-extern "C" {}
+// Attribute indices used by the tests
+const AGE: usize = 0;
+const COUNTRY: usize = 1;
 
-rust_witness::witness!(compressedaccountmerkleproof);
+/// Directory of the circuit artifacts generated by ./scripts/setup.sh
+const BUILD_DIR: &str = "./build";
 
 #[tokio::test]
 async fn test_create_issuer_and_add_credential() {
     let config = ProgramTestConfig::new(true, Some(vec![("zk_id", zk_id::ID)]));
     let mut rpc = LightProgramTest::new(config).await.unwrap();
     let payer = rpc.get_payer().insecure_clone();
+    setup_verifying_keys(&mut rpc, &payer).await.unwrap();
 
     let address_tree_info = rpc.get_address_tree_v2();
 
@@ -110,6 +75,11 @@ async fn test_create_issuer_and_add_credential() {
     // Create a credential keypair for the user
     let user_keypair = Keypair::new();
     let credential = CredentialKeypair::new(&user_keypair);
+    let mut values = [0u64; NUM_CREDENTIAL_ATTRIBUTES];
+    values[AGE] = 25;
+    values[COUNTRY] = 276;
+    let attributes = CredentialAttributes::new(values);
+    let expiry_slot = rpc.get_slot().await.unwrap() + 1_000_000;
 
     // Use the credential commitment as the "pubkey" for address derivation
     let (credential_address, _) = derive_address(
@@ -125,6 +95,10 @@ async fn test_create_issuer_and_add_credential() {
         address_tree_info.clone(),
         issuer_account,
         credential.public_key,
+        expiry_slot,
+        attributes.hash(),
+        &[],
+        TreeType::StateV1,
     )
     .await
     .unwrap();
@@ -146,7 +120,22 @@ async fn test_create_issuer_and_add_credential() {
         credential.public_key
     );
 
-    // Step 3: Verify the credential with ZK proof
+    // Step 3: The verifier registers a verification that accepts the issuer
+    let verification_expiry_slot = rpc.get_slot().await.unwrap() + 1_000_000;
+    let verification_account = create_verification(
+        &mut rpc,
+        &payer,
+        address_tree_info.clone(),
+        vec![CREDENTIAL_CIRCUIT_ID, CREDENTIAL_CIRCUIT_V2_ID],
+        vec![payer.pubkey()],
+        verification_expiry_slot,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    // Step 4: Verify the credential with ZK proof
     let credential_account = rpc
         .get_compressed_account(credential_address, None)
         .await
@@ -154,36 +143,1448 @@ async fn test_create_issuer_and_add_credential() {
         .value
         .expect("Credential account not found");
     println!("credential_account {:?}", credential_account);
+    // Prove age >= 18 and country == 276 without revealing the attributes
+    let predicates = AttributePredicates::none()
+        .with_range(AGE, 18, u64::MAX)
+        .with_range(COUNTRY, 276, 276);
+
+    // The sha256 of public_data is a public input, a replaced ciphertext fails proof verification.
+    let (mut instruction_data, accounts) = build_verify_credential(
+        &mut rpc,
+        &payer,
+        &credential_account,
+        &verification_account,
+        address_tree_info.clone(),
+        &user_keypair,
+        &attributes,
+        &predicates,
+    )
+    .await
+    .unwrap();
+    let last = instruction_data.public_data.len() - 1;
+    instruction_data.public_data[last] ^= 1;
+    let instruction = verify_credential_instruction(&instruction_data, accounts);
+    let result = rpc
+        .create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer])
+        .await;
+    assert!(result.is_err());
+
+    verify_credential(
+        &mut rpc,
+        &payer,
+        &credential_account,
+        &verification_account,
+        address_tree_info,
+        &user_keypair,
+        &attributes,
+        &predicates,
+    )
+    .await
+    .unwrap();
+
+    println!("Successfully verified credential with ZK proof!");
+
+    // Verify event account was created
+    let final_compressed_accounts = rpc
+        .get_compressed_accounts_by_owner(&zk_id::ID, None, None)
+        .await
+        .unwrap();
+
+    assert_eq!(final_compressed_accounts.value.items.len(), 4);
+
+    // Proofs only read the verification account.
+    assert_eq!(
+        get_verification_account(&mut rpc, &verification_account)
+            .await
+            .hash,
+        verification_account.hash
+    );
+    let verification_account = get_verification_account(&mut rpc, &verification_account).await;
+    let verification: zk_id::VerificationAccount = anchor_lang::AnchorDeserialize::deserialize(
+        &mut verification_account.data.as_ref().unwrap().data.as_slice(),
+    )
+    .unwrap();
+
+    // Only the verifier can decrypt the disclosed attributes of the event
+    let event_data = final_compressed_accounts
+        .value
+        .items
+        .iter()
+        .filter_map(|account| account.data.as_ref())
+        .find(|data| data.discriminator == zk_id::EncryptedEventAccount::LIGHT_DISCRIMINATOR)
+        .expect("Event account not found");
+    let event: zk_id::EncryptedEventAccount =
+        anchor_lang::AnchorDeserialize::deserialize(&mut event_data.data.as_slice()).unwrap();
+    let disclosed = EventEncryptionKeypair::new(&payer)
+        .decrypt(&verification.verification_id, &event.data)
+        .unwrap();
+    assert_eq!(
+        DisclosedAttributes::from_bytes(&disclosed).unwrap(),
+        DisclosedAttributes::new(&attributes, &[AGE, COUNTRY])
+    );
+    assert!(EventEncryptionKeypair::new(&user_keypair)
+        .decrypt(&verification.verification_id, &event.data)
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_verify_credential_v2_state_tree() {
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("zk_id", zk_id::ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+    setup_verifying_keys(&mut rpc, &payer).await.unwrap();
+
+    let address_tree_info = rpc.get_address_tree_v2();
+
+    let (issuer_address, _) = derive_address(
+        &[ISSUER, payer.pubkey().as_ref()],
+        &address_tree_info.tree,
+        &zk_id::ID,
+    );
+    create_issuer(&mut rpc, &payer, &issuer_address, address_tree_info.clone())
+        .await
+        .unwrap();
+    let issuer_account = get_issuer_account(&mut rpc, issuer_address).await;
+
+    let user_keypair = Keypair::new();
+    let credential = CredentialKeypair::new(&user_keypair);
+    let attributes = CredentialAttributes::new([25, 276, 0, 0]);
+    let expiry_slot = rpc.get_slot().await.unwrap() + 1_000_000;
+    let (credential_address, _) = derive_address(
+        &[CREDENTIAL, credential.public_key.as_ref()],
+        &address_tree_info.tree,
+        &zk_id::ID,
+    );
+    add_credential(
+        &mut rpc,
+        &payer,
+        &credential_address,
+        address_tree_info.clone(),
+        &issuer_account,
+        credential.public_key,
+        expiry_slot,
+        attributes.hash(),
+        &[],
+        TreeType::StateV2,
+    )
+    .await
+    .unwrap();
+    let credential_account = rpc
+        .get_compressed_account(credential_address, None)
+        .await
+        .unwrap()
+        .value
+        .unwrap();
+    assert_eq!(credential_account.tree_info.tree_type, TreeType::StateV2);
+    // The credential is in the output queue, prove it once it is appended to the tree.
+    batch_append_state_tree(&mut rpc, credential_account.tree_info.tree).await;
+
+    let verification_account = create_verification(
+        &mut rpc,
+        &payer,
+        address_tree_info.clone(),
+        vec![CREDENTIAL_CIRCUIT_ID, CREDENTIAL_CIRCUIT_V2_ID],
+        vec![payer.pubkey()],
+        expiry_slot,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    // The proof uses the 32 level circuit and the root of the batched tree.
     verify_credential(
         &mut rpc,
         &payer,
         &credential_account,
+        &verification_account,
+        address_tree_info,
+        &user_keypair,
+        &attributes,
+        &AttributePredicates::none(),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_revoke_credential() {
+    let config = ProgramTestConfig::new(true, Some(vec![("zk_id", zk_id::ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+    setup_verifying_keys(&mut rpc, &payer).await.unwrap();
+
+    let address_tree_info = rpc.get_address_tree_v2();
+
+    let (issuer_address, _) = derive_address(
+        &[ISSUER, payer.pubkey().as_ref()],
+        &address_tree_info.tree,
+        &zk_id::ID,
+    );
+    create_issuer(&mut rpc, &payer, &issuer_address, address_tree_info.clone())
+        .await
+        .unwrap();
+    let issuer_account = rpc
+        .get_compressed_account(issuer_address, None)
+        .await
+        .unwrap()
+        .value
+        .unwrap();
+
+    let user_keypair = Keypair::new();
+    let credential = CredentialKeypair::new(&user_keypair);
+    let attributes = CredentialAttributes::new([25, 276, 0, 0]);
+    let expiry_slot = rpc.get_slot().await.unwrap() + 1_000_000;
+    let (credential_address, _) = derive_address(
+        &[CREDENTIAL, credential.public_key.as_ref()],
+        &address_tree_info.tree,
+        &zk_id::ID,
+    );
+    add_credential(
+        &mut rpc,
+        &payer,
+        &credential_address,
+        address_tree_info.clone(),
+        &issuer_account,
+        credential.public_key,
+        expiry_slot,
+        attributes.hash(),
+        &[],
+        TreeType::StateV1,
+    )
+    .await
+    .unwrap();
+
+    let credential_account = rpc
+        .get_compressed_account(credential_address, None)
+        .await
+        .unwrap()
+        .value
+        .unwrap();
+
+    let verification_expiry_slot = rpc.get_slot().await.unwrap() + 1_000_000;
+    let verification_account = create_verification(
+        &mut rpc,
+        &payer,
+        address_tree_info.clone(),
+        vec![CREDENTIAL_CIRCUIT_ID, CREDENTIAL_CIRCUIT_V2_ID],
+        vec![payer.pubkey()],
+        verification_expiry_slot,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    // Only the issuer can revoke the credential.
+    let other_issuer = Keypair::new();
+    rpc.airdrop_lamports(&other_issuer.pubkey(), 1_000_000_000)
+        .await
+        .unwrap();
+    let issuer_account = rpc
+        .get_compressed_account(issuer_address, None)
+        .await
+        .unwrap()
+        .value
+        .unwrap();
+    let result = revoke_credential(
+        &mut rpc,
+        &other_issuer,
+        &issuer_account,
+        &credential_account,
+    )
+    .await;
+    assert!(result.is_err());
+
+    revoke_credential(&mut rpc, &payer, &issuer_account, &credential_account)
+        .await
+        .unwrap();
+
+    let revoked = rpc
+        .get_compressed_account(credential_address, None)
+        .await
+        .unwrap()
+        .value;
+    assert!(revoked.is_none(), "Credential account should be burned");
+
+    // A new proof of the revoked credential is refused, the indexer no longer returns it.
+    let result = build_verify_credential(
+        &mut rpc,
+        &payer,
+        &credential_account,
+        &verification_account,
         address_tree_info,
         &user_keypair,
+        &attributes,
+        &AttributePredicates::none(),
+    )
+    .await;
+    assert!(matches!(result, Err(ClientError::CredentialRevoked)));
+}
+
+#[tokio::test]
+async fn test_verify_credential_recent_root() {
+    let config = ProgramTestConfig::new(true, Some(vec![("zk_id", zk_id::ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+    setup_verifying_keys(&mut rpc, &payer).await.unwrap();
+
+    let address_tree_info = rpc.get_address_tree_v2();
+
+    let (issuer_address, _) = derive_address(
+        &[ISSUER, payer.pubkey().as_ref()],
+        &address_tree_info.tree,
+        &zk_id::ID,
+    );
+    create_issuer(&mut rpc, &payer, &issuer_address, address_tree_info.clone())
+        .await
+        .unwrap();
+    let issuer_account = get_issuer_account(&mut rpc, issuer_address).await;
+
+    let user_keypair = Keypair::new();
+    let credential = CredentialKeypair::new(&user_keypair);
+    let attributes = CredentialAttributes::new([25, 276, 0, 0]);
+    let expiry_slot = rpc.get_slot().await.unwrap() + 1_000_000;
+    let (credential_address, _) = derive_address(
+        &[CREDENTIAL, credential.public_key.as_ref()],
+        &address_tree_info.tree,
+        &zk_id::ID,
+    );
+    add_credential(
+        &mut rpc,
+        &payer,
+        &credential_address,
+        address_tree_info.clone(),
+        &issuer_account,
+        credential.public_key,
+        expiry_slot,
+        attributes.hash(),
+        &[],
+        TreeType::StateV1,
+    )
+    .await
+    .unwrap();
+    let credential_account = rpc
+        .get_compressed_account(credential_address, None)
+        .await
+        .unwrap()
+        .value
+        .unwrap();
+
+    let verification_account = create_verification(
+        &mut rpc,
+        &payer,
+        address_tree_info.clone(),
+        vec![CREDENTIAL_CIRCUIT_ID, CREDENTIAL_CIRCUIT_V2_ID],
+        vec![payer.pubkey()],
+        expiry_slot,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let (instruction_data, accounts) = build_verify_credential(
+        &mut rpc,
+        &payer,
+        &credential_account,
+        &verification_account,
+        address_tree_info.clone(),
+        &user_keypair,
+        &attributes,
+        &AttributePredicates::none(),
+    )
+    .await
+    .unwrap();
+    let root_index = get_credential_merkle_proof(&mut rpc, &credential_account)
+        .await
+        .unwrap()
+        .root_index;
+
+    // An unrelated credential is appended to the same state tree while the proof is in flight.
+    let other_credential = CredentialKeypair::new(&Keypair::new());
+    let (other_credential_address, _) = derive_address(
+        &[CREDENTIAL, other_credential.public_key.as_ref()],
+        &address_tree_info.tree,
+        &zk_id::ID,
+    );
+    let issuer_account = get_issuer_account(&mut rpc, issuer_address).await;
+    add_credential_to_tree(
+        &mut rpc,
+        &payer,
+        &other_credential_address,
+        address_tree_info,
+        &issuer_account,
+        other_credential.public_key,
+        expiry_slot,
+        attributes.hash(),
+        &[],
+        credential_account.tree_info,
     )
     .await
     .unwrap();
+    let current_root_index = get_credential_merkle_proof(&mut rpc, &credential_account)
+        .await
+        .unwrap()
+        .root_index;
+    assert_ne!(current_root_index, root_index);
+
+    // The proof is one append behind the current root, within MAX_ROOT_AGE.
+    let instruction = verify_credential_instruction(&instruction_data, accounts);
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_issuer_authority_rotation() {
+    let config = ProgramTestConfig::new(true, Some(vec![("zk_id", zk_id::ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let address_tree_info = rpc.get_address_tree_v2();
+
+    let (issuer_address, _) = derive_address(
+        &[ISSUER, payer.pubkey().as_ref()],
+        &address_tree_info.tree,
+        &zk_id::ID,
+    );
+    create_issuer(&mut rpc, &payer, &issuer_address, address_tree_info.clone())
+        .await
+        .unwrap();
+
+    // Rotate the single issuer key to a 2 of 3 authority set.
+    let authorities = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let new_authority = zk_id::IssuerAuthority {
+        authorities: authorities.iter().map(|a| a.pubkey()).collect(),
+        threshold: 2,
+    };
+    let issuer_account = get_issuer_account(&mut rpc, issuer_address).await;
+    update_issuer_authority(
+        &mut rpc,
+        &payer,
+        &issuer_account,
+        new_authority.clone(),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // The stable issuer identity does not change with the rotation.
+    let issuer_account = get_issuer_account(&mut rpc, issuer_address).await;
+    let issuer_account_parsed: zk_id::IssuerAccount = anchor_lang::AnchorDeserialize::deserialize(
+        &mut issuer_account.data.as_ref().unwrap().data.as_slice(),
+    )
+    .unwrap();
+    assert_eq!(issuer_account_parsed.issuer_pubkey, payer.pubkey());
+    assert_eq!(issuer_account_parsed.authority, new_authority);
+
+    let credential = CredentialKeypair::new(&Keypair::new());
+    let attributes = CredentialAttributes::new([25, 276, 0, 0]);
+    let expiry_slot = rpc.get_slot().await.unwrap() + 1_000_000;
+    let (credential_address, _) = derive_address(
+        &[CREDENTIAL, credential.public_key.as_ref()],
+        &address_tree_info.tree,
+        &zk_id::ID,
+    );
+
+    // The rotated out key can no longer issue credentials.
+    let result = add_credential(
+        &mut rpc,
+        &payer,
+        &credential_address,
+        address_tree_info.clone(),
+        &issuer_account,
+        credential.public_key,
+        expiry_slot,
+        attributes.hash(),
+        &[],
+        TreeType::StateV1,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // One of the new authorities is below the threshold.
+    let result = add_credential(
+        &mut rpc,
+        &payer,
+        &credential_address,
+        address_tree_info.clone(),
+        &issuer_account,
+        credential.public_key,
+        expiry_slot,
+        attributes.hash(),
+        &[&authorities[0]],
+        TreeType::StateV1,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // A threshold of authorities issues the credential under the stable issuer identity.
+    add_credential(
+        &mut rpc,
+        &payer,
+        &credential_address,
+        address_tree_info.clone(),
+        &issuer_account,
+        credential.public_key,
+        expiry_slot,
+        attributes.hash(),
+        &[&authorities[0], &authorities[2]],
+        TreeType::StateV1,
+    )
+    .await
+    .unwrap();
+
+    let credential_account = rpc
+        .get_compressed_account(credential_address, None)
+        .await
+        .unwrap()
+        .value
+        .unwrap();
+    let credential_account_parsed: zk_id::CredentialAccount =
+        anchor_lang::AnchorDeserialize::deserialize(
+            &mut credential_account.data.as_ref().unwrap().data.as_slice(),
+        )
+        .unwrap();
+    assert_eq!(credential_account_parsed.issuer, payer.pubkey());
+
+    // Authority sets with an unreachable threshold are rejected.
+    let issuer_account = get_issuer_account(&mut rpc, issuer_address).await;
+    let invalid_authority = zk_id::IssuerAuthority {
+        authorities: vec![authorities[0].pubkey()],
+        threshold: 2,
+    };
+    let result = update_issuer_authority(
+        &mut rpc,
+        &payer,
+        &issuer_account,
+        invalid_authority,
+        &[&authorities[0], &authorities[1]],
+    )
+    .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_verification_allowed_issuers() {
+    let config = ProgramTestConfig::new(true, Some(vec![("zk_id", zk_id::ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+    setup_verifying_keys(&mut rpc, &payer).await.unwrap();
+
+    let address_tree_info = rpc.get_address_tree_v2();
+
+    // A verification must accept at least one issuer.
+    let expiry_slot = rpc.get_slot().await.unwrap() + 1_000_000;
+    let result = create_verification(
+        &mut rpc,
+        &payer,
+        address_tree_info.clone(),
+        vec![CREDENTIAL_CIRCUIT_ID, CREDENTIAL_CIRCUIT_V2_ID],
+        vec![],
+        expiry_slot,
+        None,
+        None,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Allowed issuers must be unique.
+    let result = create_verification(
+        &mut rpc,
+        &payer,
+        address_tree_info.clone(),
+        vec![CREDENTIAL_CIRCUIT_ID, CREDENTIAL_CIRCUIT_V2_ID],
+        vec![payer.pubkey(), payer.pubkey()],
+        expiry_slot,
+        None,
+        None,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Two issuers, the verification only accepts the first one.
+    let other_issuer = Keypair::new();
+    rpc.airdrop_lamports(&other_issuer.pubkey(), 1_000_000_000)
+        .await
+        .unwrap();
+    let mut credentials = Vec::new();
+    for issuer in [&payer, &other_issuer] {
+        let (issuer_address, _) = derive_address(
+            &[ISSUER, issuer.pubkey().as_ref()],
+            &address_tree_info.tree,
+            &zk_id::ID,
+        );
+        create_issuer(&mut rpc, issuer, &issuer_address, address_tree_info.clone())
+            .await
+            .unwrap();
+        let issuer_account = get_issuer_account(&mut rpc, issuer_address).await;
+
+        let user_keypair = Keypair::new();
+        let credential = CredentialKeypair::new(&user_keypair);
+        let attributes = CredentialAttributes::new([25, 276, 0, 0]);
+        let (credential_address, _) = derive_address(
+            &[CREDENTIAL, credential.public_key.as_ref()],
+            &address_tree_info.tree,
+            &zk_id::ID,
+        );
+        add_credential(
+            &mut rpc,
+            issuer,
+            &credential_address,
+            address_tree_info.clone(),
+            &issuer_account,
+            credential.public_key,
+            expiry_slot,
+            attributes.hash(),
+            &[],
+            TreeType::StateV1,
+        )
+        .await
+        .unwrap();
+        let credential_account = rpc
+            .get_compressed_account(credential_address, None)
+            .await
+            .unwrap()
+            .value
+            .unwrap();
+        credentials.push((credential_account, user_keypair, attributes));
+    }
+
+    let verification_account = create_verification(
+        &mut rpc,
+        &payer,
+        address_tree_info.clone(),
+        vec![CREDENTIAL_CIRCUIT_ID, CREDENTIAL_CIRCUIT_V2_ID],
+        vec![payer.pubkey()],
+        expiry_slot,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    // A credential of an issuer that is not on the allowed list is rejected.
+    let (credential_account, user_keypair, attributes) = &credentials[1];
+    let result = verify_credential(
+        &mut rpc,
+        &payer,
+        credential_account,
+        &verification_account,
+        address_tree_info.clone(),
+        user_keypair,
+        attributes,
+        &AttributePredicates::none(),
+    )
+    .await;
+    assert!(result.is_err());
+
+    let (credential_account, user_keypair, attributes) = &credentials[0];
+    verify_credential(
+        &mut rpc,
+        &payer,
+        credential_account,
+        &verification_account,
+        address_tree_info,
+        user_keypair,
+        attributes,
+        &AttributePredicates::none(),
+    )
+    .await
+    .unwrap();
+
+    let verification_account = get_verification_account(&mut rpc, &verification_account).await;
+    let verification: zk_id::VerificationAccount = anchor_lang::AnchorDeserialize::deserialize(
+        &mut verification_account.data.as_ref().unwrap().data.as_slice(),
+    )
+    .unwrap();
+    assert_eq!(verification.verifier, payer.pubkey());
+    assert_eq!(verification.allowed_issuers, vec![payer.pubkey()]);
+}
+
+#[tokio::test]
+async fn test_verify_credentials_batch() {
+    let config = ProgramTestConfig::new(true, Some(vec![("zk_id", zk_id::ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+    setup_verifying_keys(&mut rpc, &payer).await.unwrap();
+
+    let address_tree_info = rpc.get_address_tree_v2();
+    let expiry_slot = rpc.get_slot().await.unwrap() + 1_000_000;
+
+    // Credentials of two issuers, e.g. KYC and accreditation.
+    let accreditation_issuer = Keypair::new();
+    rpc.airdrop_lamports(&accreditation_issuer.pubkey(), 1_000_000_000)
+        .await
+        .unwrap();
+    let mut credentials = Vec::new();
+    for issuer in [&payer, &accreditation_issuer] {
+        let (issuer_address, _) = derive_address(
+            &[ISSUER, issuer.pubkey().as_ref()],
+            &address_tree_info.tree,
+            &zk_id::ID,
+        );
+        create_issuer(&mut rpc, issuer, &issuer_address, address_tree_info.clone())
+            .await
+            .unwrap();
+        let issuer_account = get_issuer_account(&mut rpc, issuer_address).await;
+
+        let credential = CredentialKeypair::new(&Keypair::new());
+        let attributes = CredentialAttributes::new([25, 276, 0, 0]);
+        let (credential_address, _) = derive_address(
+            &[CREDENTIAL, credential.public_key.as_ref()],
+            &address_tree_info.tree,
+            &zk_id::ID,
+        );
+        add_credential(
+            &mut rpc,
+            issuer,
+            &credential_address,
+            address_tree_info.clone(),
+            &issuer_account,
+            credential.public_key,
+            expiry_slot,
+            attributes.hash(),
+            &[],
+            TreeType::StateV1,
+        )
+        .await
+        .unwrap();
+        let credential_account = rpc
+            .get_compressed_account(credential_address, None)
+            .await
+            .unwrap()
+            .value
+            .unwrap();
+        credentials.push((credential_account, credential, attributes));
+    }
+
+    let verification_account = create_verification(
+        &mut rpc,
+        &payer,
+        address_tree_info.clone(),
+        vec![CREDENTIAL_CIRCUIT_ID, CREDENTIAL_CIRCUIT_V2_ID],
+        vec![payer.pubkey(), accreditation_issuer.pubkey()],
+        expiry_slot,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let predicates = AttributePredicates::none().with_range(AGE, 18, u64::MAX);
+    let (instruction_data, accounts) = zk_id::client::build_verify_credentials(
+        &mut rpc,
+        BUILD_DIR,
+        VerifyCredentialsParams {
+            payer: payer.pubkey(),
+            verification_account: &verification_account,
+            address_tree_info,
+            credentials: credentials
+                .iter()
+                .map(
+                    |(credential_account, credential, attributes)| CredentialProofParams {
+                        credential_account,
+                        credential,
+                        attributes,
+                        predicates: &predicates,
+                        disclosed_data: DisclosedAttributes::new(attributes, &[AGE]).to_bytes(),
+                        issuer_set: None,
+                    },
+                )
+                .collect(),
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(instruction_data.credential_proofs.len(), 2);
+    // Two Groth16 verifications exceed the default compute budget.
+    let instructions = [
+        ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+        verify_credential_instruction(&instruction_data, accounts),
+    ];
+    rpc.create_and_send_transaction(&instructions, &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    // The event addresses exist, the credentials can't be used again for this verification.
+    let result = rpc
+        .create_and_send_transaction(&instructions, &payer.pubkey(), &[&payer])
+        .await;
+    assert!(result.is_err());
+
+    // Proofs only read the verification account.
+    assert_eq!(
+        get_verification_account(&mut rpc, &verification_account)
+            .await
+            .hash,
+        verification_account.hash
+    );
+
+    let compressed_accounts = rpc
+        .get_compressed_accounts_by_owner(&zk_id::ID, None, None)
+        .await
+        .unwrap();
+    let num_events = compressed_accounts
+        .value
+        .items
+        .iter()
+        .filter_map(|account| account.data.as_ref())
+        .filter(|data| data.discriminator == zk_id::EncryptedEventAccount::LIGHT_DISCRIMINATOR)
+        .count();
+    assert_eq!(num_events, 2);
+}
+
+#[tokio::test]
+async fn test_campaign_nullifier() {
+    let config = ProgramTestConfig::new(true, Some(vec![("zk_id", zk_id::ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+    setup_verifying_keys(&mut rpc, &payer).await.unwrap();
+
+    let address_tree_info = rpc.get_address_tree_v2();
+    let expiry_slot = rpc.get_slot().await.unwrap() + 1_000_000;
+
+    let (issuer_address, _) = derive_address(
+        &[ISSUER, payer.pubkey().as_ref()],
+        &address_tree_info.tree,
+        &zk_id::ID,
+    );
+    create_issuer(&mut rpc, &payer, &issuer_address, address_tree_info.clone())
+        .await
+        .unwrap();
+
+    // Credentials of two holders.
+    let mut credentials = Vec::new();
+    for _ in 0..2 {
+        let issuer_account = get_issuer_account(&mut rpc, issuer_address).await;
+        let user_keypair = Keypair::new();
+        let credential = CredentialKeypair::new(&user_keypair);
+        let attributes = CredentialAttributes::new([25, 276, 0, 0]);
+        let (credential_address, _) = derive_address(
+            &[CREDENTIAL, credential.public_key.as_ref()],
+            &address_tree_info.tree,
+            &zk_id::ID,
+        );
+        add_credential(
+            &mut rpc,
+            &payer,
+            &credential_address,
+            address_tree_info.clone(),
+            &issuer_account,
+            credential.public_key,
+            expiry_slot,
+            attributes.hash(),
+            &[],
+            TreeType::StateV1,
+        )
+        .await
+        .unwrap();
+        let credential_account = rpc
+            .get_compressed_account(credential_address, None)
+            .await
+            .unwrap()
+            .value
+            .unwrap();
+        credentials.push((credential_account, user_keypair, attributes));
+    }
+
+    // Zero is the circuit's no campaign value.
+    let result = create_verification(
+        &mut rpc,
+        &payer,
+        address_tree_info.clone(),
+        vec![CREDENTIAL_CIRCUIT_ID, CREDENTIAL_CIRCUIT_V2_ID],
+        vec![payer.pubkey()],
+        expiry_slot,
+        Some([0u8; 31]),
+        None,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Two verifications of the same campaign, e.g. two rounds of an airdrop.
+    let campaign_id = [9u8; 31];
+    let mut verification_accounts = Vec::new();
+    for _ in 0..2 {
+        let verification_account = create_verification(
+            &mut rpc,
+            &payer,
+            address_tree_info.clone(),
+            vec![CREDENTIAL_CIRCUIT_ID, CREDENTIAL_CIRCUIT_V2_ID],
+            vec![payer.pubkey()],
+            expiry_slot,
+            Some(campaign_id),
+            None,
+        )
+        .await
+        .unwrap();
+        verification_accounts.push(verification_account);
+    }
+
+    let (credential_account, user_keypair, attributes) = &credentials[0];
+    verify_credential(
+        &mut rpc,
+        &payer,
+        credential_account,
+        &verification_accounts[0],
+        address_tree_info.clone(),
+        user_keypair,
+        attributes,
+        &AttributePredicates::none(),
+    )
+    .await
+    .unwrap();
+
+    // The campaign nullifier account of the first holder exists.
+    let campaign_nullifier = CredentialKeypair::new(user_keypair)
+        .compute_campaign_nullifier(&campaign_input(&payer.pubkey(), &campaign_id));
+    let (campaign_nullifier_address, _) = derive_address(
+        &[
+            CAMPAIGN_NULLIFIER,
+            campaign_nullifier.as_slice(),
+            payer.pubkey().as_ref(),
+            campaign_id.as_slice(),
+        ],
+        &address_tree_info.tree,
+        &zk_id::ID,
+    );
+    let campaign_nullifier_account = rpc
+        .get_compressed_account(campaign_nullifier_address, None)
+        .await
+        .unwrap()
+        .value
+        .unwrap();
+    assert_eq!(
+        campaign_nullifier_account.data.unwrap().discriminator,
+        zk_id::CampaignNullifierAccount::LIGHT_DISCRIMINATOR
+    );
+
+    // The event address of the second verification is new, but the campaign nullifier exists.
+    let result = verify_credential(
+        &mut rpc,
+        &payer,
+        credential_account,
+        &verification_accounts[1],
+        address_tree_info.clone(),
+        user_keypair,
+        attributes,
+        &AttributePredicates::none(),
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Another verifier reusing the campaign id does not share the campaign nullifier.
+    let other_verifier = Keypair::new();
+    rpc.airdrop_lamports(&other_verifier.pubkey(), 1_000_000_000)
+        .await
+        .unwrap();
+    let other_verification_account = create_verification(
+        &mut rpc,
+        &other_verifier,
+        address_tree_info.clone(),
+        vec![CREDENTIAL_CIRCUIT_ID, CREDENTIAL_CIRCUIT_V2_ID],
+        vec![payer.pubkey()],
+        expiry_slot,
+        Some(campaign_id),
+        None,
+    )
+    .await
+    .unwrap();
+    verify_credential(
+        &mut rpc,
+        &payer,
+        credential_account,
+        &other_verification_account,
+        address_tree_info.clone(),
+        user_keypair,
+        attributes,
+        &AttributePredicates::none(),
+    )
+    .await
+    .unwrap();
+
+    // Another holder can still pass the second verification.
+    let (credential_account, user_keypair, attributes) = &credentials[1];
+    verify_credential(
+        &mut rpc,
+        &payer,
+        credential_account,
+        &verification_accounts[1],
+        address_tree_info,
+        user_keypair,
+        attributes,
+        &AttributePredicates::none(),
+    )
+    .await
+    .unwrap();
+
+    let verification_account = get_verification_account(&mut rpc, &verification_accounts[1]).await;
+    let verification: zk_id::VerificationAccount = anchor_lang::AnchorDeserialize::deserialize(
+        &mut verification_account.data.as_ref().unwrap().data.as_slice(),
+    )
+    .unwrap();
+    assert_eq!(verification.campaign_id, Some(campaign_id));
+}
+
+#[tokio::test]
+async fn test_issuer_set_verification() {
+    let config = ProgramTestConfig::new(true, Some(vec![("zk_id", zk_id::ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+    setup_verifying_keys(&mut rpc, &payer).await.unwrap();
+
+    let address_tree_info = rpc.get_address_tree_v2();
+    let expiry_slot = rpc.get_slot().await.unwrap() + 1_000_000;
+
+    let (issuer_address, _) = derive_address(
+        &[ISSUER, payer.pubkey().as_ref()],
+        &address_tree_info.tree,
+        &zk_id::ID,
+    );
+    create_issuer(&mut rpc, &payer, &issuer_address, address_tree_info.clone())
+        .await
+        .unwrap();
+    let issuer_account = get_issuer_account(&mut rpc, issuer_address).await;
+
+    let user_keypair = Keypair::new();
+    let credential = CredentialKeypair::new(&user_keypair);
+    let attributes = CredentialAttributes::new([25, 276, 0, 0]);
+    let (credential_address, _) = derive_address(
+        &[CREDENTIAL, credential.public_key.as_ref()],
+        &address_tree_info.tree,
+        &zk_id::ID,
+    );
+    add_credential(
+        &mut rpc,
+        &payer,
+        &credential_address,
+        address_tree_info.clone(),
+        &issuer_account,
+        credential.public_key,
+        expiry_slot,
+        attributes.hash(),
+        &[],
+        TreeType::StateV1,
+    )
+    .await
+    .unwrap();
+    let credential_account = rpc
+        .get_compressed_account(credential_address, None)
+        .await
+        .unwrap()
+        .value
+        .unwrap();
+
+    // The verifier accepts three issuers without learning which one issued the credential.
+    let issuer_set =
+        IssuerSet::new(&[Pubkey::new_unique(), payer.pubkey(), Pubkey::new_unique()]).unwrap();
+    let issuer_set_circuit_ids = vec![
+        CREDENTIAL_ISSUER_SET_CIRCUIT_ID,
+        CREDENTIAL_ISSUER_SET_CIRCUIT_V2_ID,
+    ];
+    let verification_account = create_verification(
+        &mut rpc,
+        &payer,
+        address_tree_info.clone(),
+        issuer_set_circuit_ids.clone(),
+        vec![],
+        expiry_slot,
+        None,
+        Some(issuer_set.root()),
+    )
+    .await
+    .unwrap();
+
+    // The issuer isn't on the allowed list, proofs that reveal it are rejected.
+    let result = verify_credential(
+        &mut rpc,
+        &payer,
+        &credential_account,
+        &verification_account,
+        address_tree_info.clone(),
+        &user_keypair,
+        &attributes,
+        &AttributePredicates::none(),
+    )
+    .await;
+    assert!(result.is_err());
+
+    verify_credential_issuer_set(
+        &mut rpc,
+        &payer,
+        &credential_account,
+        &verification_account,
+        address_tree_info.clone(),
+        &user_keypair,
+        &attributes,
+        &issuer_set,
+    )
+    .await
+    .unwrap();
+
+    let verification_account = get_verification_account(&mut rpc, &verification_account).await;
+    let verification: zk_id::VerificationAccount = anchor_lang::AnchorDeserialize::deserialize(
+        &mut verification_account.data.as_ref().unwrap().data.as_slice(),
+    )
+    .unwrap();
+    assert!(verification.allowed_issuers.is_empty());
+    assert_eq!(verification.issuer_set_root, Some(issuer_set.root()));
+
+    // The issuer is not in the set of another verification.
+    let other_issuer_set = IssuerSet::new(&[Pubkey::new_unique()]).unwrap();
+    let other_verification_account = create_verification(
+        &mut rpc,
+        &payer,
+        address_tree_info.clone(),
+        issuer_set_circuit_ids,
+        vec![],
+        expiry_slot,
+        None,
+        Some(other_issuer_set.root()),
+    )
+    .await
+    .unwrap();
+    let result = verify_credential_issuer_set(
+        &mut rpc,
+        &payer,
+        &credential_account,
+        &other_verification_account,
+        address_tree_info,
+        &user_keypair,
+        &attributes,
+        &other_issuer_set,
+    )
+    .await;
+    assert!(matches!(result, Err(ClientError::IssuerNotInSet(_))));
+}
+
+#[tokio::test]
+async fn test_verifying_key_registry() {
+    let config = ProgramTestConfig::new(true, Some(vec![("zk_id", zk_id::ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let address_tree_info = rpc.get_address_tree_v2();
+
+    let admin = Keypair::new();
+    rpc.airdrop_lamports(&admin.pubkey(), 1_000_000_000)
+        .await
+        .unwrap();
+    // The payer is the upgrade authority of the program.
+    setup_mock_program_data(&mut rpc, &payer, &zk_id::ID);
+    // Only the upgrade authority can initialize the config.
+    let result = initialize_config(&mut rpc, &admin, admin.pubkey()).await;
+    assert!(result.is_err());
+    initialize_config(&mut rpc, &payer, admin.pubkey())
+        .await
+        .unwrap();
+    // The config can only be initialized once.
+    let result = initialize_config(&mut rpc, &payer, payer.pubkey()).await;
+    assert!(result.is_err());
+
+    // Only the admin can register verifying keys.
+    let result = register_verifying_key(
+        &mut rpc,
+        &payer,
+        CREDENTIAL_CIRCUIT_ID,
+        &zk_id::verifying_key::VERIFYINGKEY,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Register the same circuit under two ids, a verification only accepts the key of its circuit.
+    let other_circuit_id = 2;
+    for circuit_id in [CREDENTIAL_CIRCUIT_ID, other_circuit_id] {
+        register_verifying_key(
+            &mut rpc,
+            &admin,
+            circuit_id,
+            &zk_id::verifying_key::VERIFYINGKEY,
+        )
+        .await
+        .unwrap();
+    }
+    let verifying_key_account = rpc
+        .get_account(verifying_key_pda(CREDENTIAL_CIRCUIT_ID))
+        .await
+        .unwrap()
+        .unwrap();
+    let verifying_key: zk_id::VerifyingKeyAccount =
+        anchor_lang::AccountDeserialize::try_deserialize(
+            &mut verifying_key_account.data.as_slice(),
+        )
+        .unwrap();
+    assert_eq!(verifying_key.circuit_id, CREDENTIAL_CIRCUIT_ID);
+    assert_eq!(
+        verifying_key.vk_ic.as_slice(),
+        zk_id::verifying_key::VERIFYINGKEY.vk_ic
+    );
+
+    let (issuer_address, _) = derive_address(
+        &[ISSUER, payer.pubkey().as_ref()],
+        &address_tree_info.tree,
+        &zk_id::ID,
+    );
+    create_issuer(&mut rpc, &payer, &issuer_address, address_tree_info.clone())
+        .await
+        .unwrap();
+    let issuer_account = get_issuer_account(&mut rpc, issuer_address).await;
+
+    let user_keypair = Keypair::new();
+    let credential = CredentialKeypair::new(&user_keypair);
+    let attributes = CredentialAttributes::new([25, 276, 0, 0]);
+    let expiry_slot = rpc.get_slot().await.unwrap() + 1_000_000;
+    let (credential_address, _) = derive_address(
+        &[CREDENTIAL, credential.public_key.as_ref()],
+        &address_tree_info.tree,
+        &zk_id::ID,
+    );
+    add_credential(
+        &mut rpc,
+        &payer,
+        &credential_address,
+        address_tree_info.clone(),
+        &issuer_account,
+        credential.public_key,
+        expiry_slot,
+        attributes.hash(),
+        &[],
+        TreeType::StateV1,
+    )
+    .await
+    .unwrap();
+    let credential_account = rpc
+        .get_compressed_account(credential_address, None)
+        .await
+        .unwrap()
+        .value
+        .unwrap();
+
+    let verification_account = create_verification(
+        &mut rpc,
+        &payer,
+        address_tree_info.clone(),
+        vec![other_circuit_id],
+        vec![payer.pubkey()],
+        expiry_slot,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    // The verifying key of a circuit the verification does not accept is rejected.
+    let (instruction_data, mut accounts) = build_verify_credential(
+        &mut rpc,
+        &payer,
+        &credential_account,
+        &verification_account,
+        address_tree_info.clone(),
+        &user_keypair,
+        &attributes,
+        &AttributePredicates::none(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(accounts[2].pubkey, verifying_key_pda(CREDENTIAL_CIRCUIT_ID));
+    let instruction = verify_credential_instruction(&instruction_data, accounts.clone());
+    let result = rpc
+        .create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer])
+        .await;
+    assert!(result.is_err());
+
+    accounts[2].pubkey = verifying_key_pda(other_circuit_id);
+    let instruction = verify_credential_instruction(&instruction_data, accounts);
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+}
+
+/// Creates the config with `admin` as upgrade authority and admin and registers the verifying keys
+/// of the credential and issuer set circuits for v1 and v2 state trees.
+async fn setup_verifying_keys<R>(rpc: &mut R, admin: &Keypair) -> Result<(), RpcError>
+where
+    R: Rpc + Indexer + TestRpc,
+{
+    setup_mock_program_data(rpc, admin, &zk_id::ID);
+    initialize_config(rpc, admin, admin.pubkey()).await?;
+    for tree_type in [TreeType::StateV1, TreeType::StateV2] {
+        for circuit in [
+            CredentialCircuit::new(tree_type, BUILD_DIR).unwrap(),
+            CredentialCircuit::new_issuer_set(tree_type, BUILD_DIR).unwrap(),
+        ] {
+            register_verifying_key(rpc, admin, circuit.circuit_id, circuit.verifying_key).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Initializes the config, `payer` must be the upgrade authority of the program.
+async fn initialize_config<R>(
+    rpc: &mut R,
+    payer: &Keypair,
+    admin: Pubkey,
+) -> Result<Signature, RpcError>
+where
+    R: Rpc + Indexer,
+{
+    let instruction = Instruction {
+        program_id: zk_id::ID,
+        accounts: zk_id::accounts::InitializeConfig {
+            signer: payer.pubkey(),
+            config: config_pda(),
+            program_data: program_data_pda(),
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_id::instruction::InitializeConfig { admin }.data(),
+    };
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[payer])
+        .await
+}
+
+/// Registers a verifying key, the IC points are appended in chunks to fit the transaction size.
+async fn register_verifying_key<R>(
+    rpc: &mut R,
+    admin: &Keypair,
+    circuit_id: u32,
+    verifying_key: &Groth16Verifyingkey<'_>,
+) -> Result<(), RpcError>
+where
+    R: Rpc + Indexer,
+{
+    let instruction = Instruction {
+        program_id: zk_id::ID,
+        accounts: zk_id::accounts::RegisterVerifyingKey {
+            admin: admin.pubkey(),
+            config: config_pda(),
+            verifying_key: verifying_key_pda(circuit_id),
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_id::instruction::RegisterVerifyingKey {
+            circuit_id,
+            vk_alpha_g1: verifying_key.vk_alpha_g1,
+            vk_beta_g2: verifying_key.vk_beta_g2,
+            vk_gamma_g2: verifying_key.vk_gamma_g2,
+            vk_delta_g2: verifying_key.vk_delta_g2,
+        }
+        .data(),
+    };
+    rpc.create_and_send_transaction(&[instruction], &admin.pubkey(), &[admin])
+        .await?;
+
+    for vk_ic in verifying_key.vk_ic.chunks(8) {
+        let instruction = Instruction {
+            program_id: zk_id::ID,
+            accounts: zk_id::accounts::AppendVerifyingKeyIc {
+                admin: admin.pubkey(),
+                config: config_pda(),
+                verifying_key: verifying_key_pda(circuit_id),
+            }
+            .to_account_metas(None),
+            data: zk_id::instruction::AppendVerifyingKeyIc {
+                vk_ic: vk_ic.to_vec(),
+            }
+            .data(),
+        };
+        rpc.create_and_send_transaction(&[instruction], &admin.pubkey(), &[admin])
+            .await?;
+    }
+    Ok(())
+}
+
+async fn get_issuer_account<R>(rpc: &mut R, issuer_address: [u8; 32]) -> CompressedAccount
+where
+    R: Rpc + Indexer,
+{
+    rpc.get_compressed_account(issuer_address, None)
+        .await
+        .unwrap()
+        .value
+        .expect("Issuer account not found")
+}
+
+async fn create_issuer<R>(
+    rpc: &mut R,
+    payer: &Keypair,
+    address: &[u8; 32],
+    address_tree_info: light_client::indexer::TreeInfo,
+) -> Result<Signature, RpcError>
+where
+    R: Rpc + Indexer,
+{
+    let mut remaining_accounts = PackedAccounts::default();
+    remaining_accounts.add_pre_accounts_signer(payer.pubkey());
+    let config = SystemAccountMetaConfig::new(zk_id::ID);
+    remaining_accounts.add_system_accounts_v2(config)?;
+
+    let rpc_result = rpc
+        .get_validity_proof(
+            vec![],
+            vec![AddressWithTree {
+                address: *address,
+                tree: address_tree_info.tree,
+            }],
+            None,
+        )
+        .await?
+        .value;
+    let packed_address_tree_accounts = rpc_result
+        .pack_tree_infos(&mut remaining_accounts)
+        .address_trees;
+    let output_state_tree_index = rpc
+        .get_random_state_tree_info()?
+        .pack_output_tree_index(&mut remaining_accounts)?;
+
+    let (remaining_accounts_metas, system_accounts_offset, _) =
+        remaining_accounts.to_account_metas();
+
+    let instruction_data = zk_id::instruction::CreateIssuer {
+        proof: rpc_result.proof,
+        address_tree_info: packed_address_tree_accounts[0],
+        output_state_tree_index,
+        system_accounts_offset: system_accounts_offset as u8,
+    };
+
+    let accounts = zk_id::accounts::GenericAnchorAccounts {
+        signer: payer.pubkey(),
+    };
 
-    println!("Successfully verified credential with ZK proof!");
+    let instruction = Instruction {
+        program_id: zk_id::ID,
+        accounts: [accounts.to_account_metas(None), remaining_accounts_metas].concat(),
+        data: instruction_data.data(),
+    };
 
-    // Verify event account was created
-    let final_compressed_accounts = rpc
-        .get_compressed_accounts_by_owner(&zk_id::ID, None, None)
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[payer])
         .await
-        .unwrap();
-
-    assert_eq!(final_compressed_accounts.value.items.len(), 3);
 }
 
-async fn create_issuer<R>(
+/// Registers a verification with a random verification_id and returns its compressed account.
+async fn create_verification<R>(
     rpc: &mut R,
     payer: &Keypair,
-    address: &[u8; 32],
     address_tree_info: light_client::indexer::TreeInfo,
-) -> Result<Signature, RpcError>
+    circuit_ids: Vec<u32>,
+    allowed_issuers: Vec<Pubkey>,
+    expiry_slot: u64,
+    campaign_id: Option<[u8; 31]>,
+    issuer_set_root: Option<[u8; 32]>,
+) -> Result<CompressedAccount, RpcError>
 where
     R: Rpc + Indexer,
 {
+    let mut verification_id = [0u8; 31];
+    verification_id.copy_from_slice(&Pubkey::new_unique().to_bytes()[0..31]);
+    let (address, _) = derive_address(
+        &[VERIFICATION, verification_id.as_slice()],
+        &address_tree_info.tree,
+        &zk_id::ID,
+    );
+
     let mut remaining_accounts = PackedAccounts::default();
     remaining_accounts.add_pre_accounts_signer(payer.pubkey());
     let config = SystemAccountMetaConfig::new(zk_id::ID);
@@ -193,7 +1594,7 @@ where
         .get_validity_proof(
             vec![],
             vec![AddressWithTree {
-                address: *address,
+                address,
                 tree: address_tree_info.tree,
             }],
             None,
@@ -203,6 +1604,7 @@ where
     let packed_address_tree_accounts = rpc_result
         .pack_tree_infos(&mut remaining_accounts)
         .address_trees;
+    // Proofs read the verification account, read only accounts must be in a v2 state tree.
     let output_state_tree_index = rpc
         .get_random_state_tree_info()?
         .pack_output_tree_index(&mut remaining_accounts)?;
@@ -210,11 +1612,18 @@ where
     let (remaining_accounts_metas, system_accounts_offset, _) =
         remaining_accounts.to_account_metas();
 
-    let instruction_data = zk_id::instruction::CreateIssuer {
+    let instruction_data = zk_id::instruction::CreateVerification {
         proof: rpc_result.proof,
         address_tree_info: packed_address_tree_accounts[0],
         output_state_tree_index,
         system_accounts_offset: system_accounts_offset as u8,
+        verification_id,
+        encryption_pubkey: EventEncryptionKeypair::new(payer).public_key,
+        circuit_ids,
+        allowed_issuers,
+        expiry_slot,
+        campaign_id,
+        issuer_set_root,
     };
 
     let accounts = zk_id::accounts::GenericAnchorAccounts {
@@ -228,7 +1637,27 @@ where
     };
 
     rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[payer])
+        .await?;
+
+    Ok(rpc
+        .get_compressed_account(address, None)
+        .await?
+        .value
+        .expect("Verification account not found"))
+}
+
+async fn get_verification_account<R>(
+    rpc: &mut R,
+    verification_account: &CompressedAccount,
+) -> CompressedAccount
+where
+    R: Rpc + Indexer,
+{
+    rpc.get_compressed_account(verification_account.address.unwrap(), None)
         .await
+        .unwrap()
+        .value
+        .expect("Verification account not found")
 }
 
 async fn add_credential<R>(
@@ -238,12 +1667,55 @@ async fn add_credential<R>(
     address_tree_info: light_client::indexer::TreeInfo,
     issuer_account: &CompressedAccount,
     credential_commitment: [u8; 32],
+    expiry_slot: u64,
+    attributes_hash: [u8; 32],
+    co_signers: &[&Keypair],
+    tree_type: TreeType,
+) -> Result<Signature, RpcError>
+where
+    R: Rpc + Indexer,
+{
+    // Credentials in v2 trees can only be proven once a forester appended them from the output queue.
+    let output_state_tree_info = match tree_type {
+        TreeType::StateV2 => rpc.get_random_state_tree_info()?,
+        _ => rpc.get_random_state_tree_info_v1()?,
+    };
+    add_credential_to_tree(
+        rpc,
+        payer,
+        address,
+        address_tree_info,
+        issuer_account,
+        credential_commitment,
+        expiry_slot,
+        attributes_hash,
+        co_signers,
+        output_state_tree_info,
+    )
+    .await
+}
+
+/// Issues a credential into the state tree `output_state_tree_info`.
+async fn add_credential_to_tree<R>(
+    rpc: &mut R,
+    payer: &Keypair,
+    address: &[u8; 32],
+    address_tree_info: light_client::indexer::TreeInfo,
+    issuer_account: &CompressedAccount,
+    credential_commitment: [u8; 32],
+    expiry_slot: u64,
+    attributes_hash: [u8; 32],
+    co_signers: &[&Keypair],
+    output_state_tree_info: light_client::indexer::TreeInfo,
 ) -> Result<Signature, RpcError>
 where
     R: Rpc + Indexer,
 {
     let mut remaining_accounts = PackedAccounts::default();
     remaining_accounts.add_pre_accounts_signer(payer.pubkey());
+    for co_signer in co_signers {
+        remaining_accounts.add_pre_accounts_signer(co_signer.pubkey());
+    }
     let config = SystemAccountMetaConfig::new(zk_id::ID);
     remaining_accounts.add_system_accounts_v2(config)?;
 
@@ -270,11 +1742,10 @@ where
         output_state_tree_index: packed_state_tree_accounts.output_tree_index,
     };
 
-    let output_state_tree_index = rpc
-        .get_random_state_tree_info_v1()?
-        .pack_output_tree_index(&mut remaining_accounts)?;
+    let output_state_tree_index =
+        output_state_tree_info.pack_output_tree_index(&mut remaining_accounts)?;
 
-    // Parse the issuer account data to get num_credentials_issued
+    // Parse the issuer account data to get num_credentials_issued and the authority
     let issuer_data = issuer_account.data.as_ref().unwrap();
     let issuer_account_parsed: zk_id::IssuerAccount =
         anchor_lang::AnchorDeserialize::deserialize(&mut issuer_data.data.as_slice()).unwrap();
@@ -289,7 +1760,11 @@ where
         system_accounts_offset: system_accounts_offset as u8,
         issuer_account_meta,
         credential_pubkey: Pubkey::new_from_array(credential_commitment),
+        issuer_pubkey: issuer_account_parsed.issuer_pubkey,
         num_credentials_issued: issuer_account_parsed.num_credentials_issued,
+        authority: issuer_account_parsed.authority,
+        expiry_slot,
+        attributes_hash,
     };
 
     let accounts = zk_id::accounts::GenericAnchorAccounts {
@@ -302,119 +1777,101 @@ where
         data: instruction_data.data(),
     };
 
-    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[payer])
+    let signers: Vec<&Keypair> = std::iter::once(payer)
+        .chain(co_signers.iter().copied())
+        .collect();
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &signers)
         .await
 }
 
-async fn verify_credential<R>(
+/// Appends the output queue of a batched state tree to the tree, as a forester does.
+/// The new root is computed with the indexer's reference tree, the batch append proof is skipped.
+async fn batch_append_state_tree(rpc: &mut LightProgramTest, merkle_tree: Pubkey) {
+    let bundle = rpc
+        .indexer_mut()
+        .unwrap()
+        .state_merkle_trees
+        .iter_mut()
+        .find(|bundle| bundle.accounts.merkle_tree == merkle_tree)
+        .expect("State tree not found");
+    let mut queued = bundle.output_queue_elements.clone();
+    queued.sort_by_key(|(_, leaf_index)| *leaf_index);
+    for (hash, leaf_index) in queued {
+        if leaf_index as usize == bundle.merkle_tree.layers[0].len() {
+            bundle.merkle_tree.append(&hash).unwrap();
+        }
+    }
+    let new_root = bundle.merkle_tree.root();
+    let next_index = bundle.merkle_tree.layers[0].len() as u64;
+
+    let mut tree_account = rpc.get_account(merkle_tree).await.unwrap().unwrap();
+    {
+        let mut batched_tree = BatchedMerkleTreeAccount::state_from_bytes(
+            &mut tree_account.data,
+            &merkle_tree.to_bytes().into(),
+        )
+        .unwrap();
+        let metadata = batched_tree.get_metadata_mut();
+        metadata.next_index = next_index;
+        metadata.sequence_number += 1;
+        batched_tree.root_history.push(new_root);
+    }
+    rpc.set_account(merkle_tree, tree_account);
+}
+
+async fn update_issuer_authority<R>(
     rpc: &mut R,
     payer: &Keypair,
-    credential_account: &CompressedAccount,
-    address_tree_info: light_client::indexer::TreeInfo,
-    user_keypair: &Keypair,
+    issuer_account: &CompressedAccount,
+    new_authority: zk_id::IssuerAuthority,
+    co_signers: &[&Keypair],
 ) -> Result<Signature, RpcError>
 where
     R: Rpc + Indexer,
 {
-    // Get the merkle proof for the credential account
-    let proofs = rpc
-        .get_multiple_compressed_account_proofs(vec![credential_account.hash], None)
-        .await?
-        .value
-        .items;
-
-    let merkle_proof = &proofs[0];
-    let leaf_index = merkle_proof.leaf_index as u32;
-    let merkle_proof_hashes = &merkle_proof.proof;
-    let merkle_root = merkle_proof.root;
-    let root_index = (merkle_proof.root_seq % 2400) as u16;
-
-    let state_tree = merkle_proof.merkle_tree;
-
-    // Parse the credential account data
-    let credential_data = credential_account.data.as_ref().unwrap();
-    let credential_account_parsed: zk_id::CredentialAccount =
-        anchor_lang::AnchorDeserialize::deserialize(&mut credential_data.data.as_slice()).unwrap();
-
-    // Generate encrypted data (in a real scenario, this would be user-provided)
-    let encrypted_data = vec![42u8; 64];
-
-    // Create the credential keypair from the user keypair
-    let credential = CredentialKeypair::new(user_keypair);
-
-    // Generate a verification_id (31 bytes)
-    let mut verification_id = [0u8; 31];
-    let random_pubkey = Pubkey::new_unique();
-    verification_id.copy_from_slice(&random_pubkey.to_bytes()[0..31]);
-
-    // Generate the ZK proof using the actual merkle root
-    let (credential_proof, nullifier) = generate_credential_proof(
-        credential_account,
-        &state_tree,
-        leaf_index,
-        &merkle_proof_hashes,
-        &merkle_root,
-        &credential_account_parsed.issuer,
-        &credential,
-        &encrypted_data,
-        &verification_id,
-    );
-
-    // Create the verification transaction
     let mut remaining_accounts = PackedAccounts::default();
     remaining_accounts.add_pre_accounts_signer(payer.pubkey());
+    for co_signer in co_signers {
+        remaining_accounts.add_pre_accounts_signer(co_signer.pubkey());
+    }
     let config = SystemAccountMetaConfig::new(zk_id::ID);
     remaining_accounts.add_system_accounts_v2(config)?;
 
-    let (event_address, _) = derive_address(
-        &[
-            ZK_ID_CHECK,
-            nullifier.as_slice(),
-            verification_id.as_slice(),
-        ],
-        &address_tree_info.tree,
-        &zk_id::ID,
-    );
-
     let rpc_result = rpc
-        .get_validity_proof(
-            vec![],
-            vec![AddressWithTree {
-                address: event_address,
-                tree: address_tree_info.tree,
-            }],
-            None,
-        )
+        .get_validity_proof(vec![issuer_account.hash], vec![], None)
         .await?
         .value;
 
-    let packed_address_tree_accounts = rpc_result
+    let packed_state_tree_accounts = rpc_result
         .pack_tree_infos(&mut remaining_accounts)
-        .address_trees;
+        .state_trees
+        .unwrap();
 
-    let output_state_tree_index = rpc
-        .get_random_state_tree_info_v1()?
-        .pack_output_tree_index(&mut remaining_accounts)?;
+    let issuer_account_meta = light_sdk::instruction::account_meta::CompressedAccountMeta {
+        tree_info: packed_state_tree_accounts.packed_tree_infos[0],
+        address: issuer_account.address.unwrap(),
+        output_state_tree_index: packed_state_tree_accounts.output_tree_index,
+    };
+
+    let issuer_data = issuer_account.data.as_ref().unwrap();
+    let issuer_account_parsed: zk_id::IssuerAccount =
+        anchor_lang::AnchorDeserialize::deserialize(&mut issuer_data.data.as_slice()).unwrap();
 
     let (remaining_accounts_metas, system_accounts_offset, _) =
         remaining_accounts.to_account_metas();
 
-    let instruction_data = zk_id::instruction::ZkVerifyCredential {
+    let instruction_data = zk_id::instruction::UpdateIssuerAuthority {
         proof: rpc_result.proof,
-        address_tree_info: packed_address_tree_accounts[0],
-        output_state_tree_index,
         system_accounts_offset: system_accounts_offset as u8,
-        input_root_index: root_index,
-        public_data: encrypted_data,
-        credential_proof,
-        issuer: credential_account_parsed.issuer.to_bytes(),
-        nullifier,
-        verification_id,
+        issuer_account_meta,
+        issuer_pubkey: issuer_account_parsed.issuer_pubkey,
+        num_credentials_issued: issuer_account_parsed.num_credentials_issued,
+        authority: issuer_account_parsed.authority,
+        new_authority,
     };
 
-    let accounts = zk_id::accounts::VerifyAccounts {
+    let accounts = zk_id::accounts::GenericAnchorAccounts {
         signer: payer.pubkey(),
-        input_merkle_tree: state_tree,
     };
 
     let instruction = Instruction {
@@ -423,222 +1880,230 @@ where
         data: instruction_data.data(),
     };
 
-    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[payer])
+    let signers: Vec<&Keypair> = std::iter::once(payer)
+        .chain(co_signers.iter().copied())
+        .collect();
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &signers)
         .await
 }
 
-fn generate_credential_proof(
+async fn verify_credential<R>(
+    rpc: &mut R,
+    payer: &Keypair,
     credential_account: &CompressedAccount,
-    merkle_tree_pubkey: &Pubkey,
-    leaf_index: u32,
-    merkle_proof_hashes: &[[u8; 32]],
-    merkle_root: &[u8; 32],
-    issuer_pubkey: &Pubkey,
-    credential: &CredentialKeypair,
-    encrypted_data: &[u8],
-    verification_id: &[u8; 31],
-) -> (
-    light_compressed_account::instruction_data::compressed_proof::CompressedProof,
-    [u8; 32], // nullifier
-) {
-    let zkey_path = "./build/compressed_account_merkle_proof_final.zkey".to_string();
-
-    // Build circuit inputs
-    let mut proof_inputs = HashMap::new();
-
-    // Add compressed account inputs
-    let discriminator = if let Some(ref data) = credential_account.data {
-        data.discriminator
-    } else {
-        [0u8; 8]
-    };
-
-    let owner_hashed = hash_to_bn254_field_size_be(zk_id::ID.as_ref());
-    let merkle_tree_hashed = hash_to_bn254_field_size_be(merkle_tree_pubkey.as_ref());
-
-    // Use the same hashing as on-chain: hashv_to_bn254_field_size_be_const_array::<2>
-    use light_hasher::hash_to_field_size::hashv_to_bn254_field_size_be_const_array;
-    let issuer_hashed =
-        hashv_to_bn254_field_size_be_const_array::<2>(&[issuer_pubkey.as_ref()]).unwrap();
-
-    // Compute data_hash as hash of issuer and credential commitment (public key is already a Poseidon hash)
-    let mut hash_input = Vec::new();
-    hash_input.extend_from_slice((encrypted_data.len() as u32).to_le_bytes().as_ref());
-    hash_input.extend_from_slice(encrypted_data);
-    let mut encrypted_data_hash = Sha256::hash(hash_input.as_slice()).unwrap();
-    encrypted_data_hash[0] = 0;
-
-    let public_data_hash =
-        Poseidon::hashv(&[issuer_hashed.as_slice(), &credential.public_key]).unwrap();
-
-    // Verify the data_hash matches
-    let expected_data_hash = credential_account.data.as_ref().unwrap().data_hash;
-    assert_eq!(public_data_hash, expected_data_hash, "Data hash mismatch");
-
-    // Compute what the circuit will compute for the leaf hash
-    // The circuit adds 36893488147419103232 (0x2000000000000000) to discriminator
-    // This effectively puts a 2 prefix at byte 23 (counting from right in BE)
-
-    // SDK format: 32-byte array with leaf_index in LE at [28..32]
-    let mut leaf_index_bytes = [0u8; 32];
-    leaf_index_bytes[28..32].copy_from_slice(&(leaf_index as u32).to_le_bytes());
-
-    // SDK format: 32-byte array with discriminator at [24..32] and prefix 2 at [23]
-    let mut discriminator_bytes = [0u8; 32];
-    discriminator_bytes[24..32].copy_from_slice(&discriminator);
-    discriminator_bytes[23] = 2;
-
-    let computed_leaf_hash = Poseidon::hashv(&[
-        owner_hashed.as_slice(),
-        leaf_index_bytes.as_slice(),
-        merkle_tree_hashed.as_slice(),
-        credential_account.address.as_ref().unwrap().as_ref(),
-        discriminator_bytes.as_slice(),
-        public_data_hash.as_slice(),
-    ])
-    .unwrap();
-
-    assert_eq!(
-        computed_leaf_hash, credential_account.hash,
-        "Leaf hash mismatch - circuit cannot recreate account hash"
-    );
-
-    proof_inputs.insert(
-        "owner_hashed".to_string(),
-        vec![BigUint::from_bytes_be(&owner_hashed).to_string()],
-    );
-    proof_inputs.insert("leaf_index".to_string(), vec![leaf_index.to_string()]);
-
-    // account_leaf_index needs to be in the same format as SDK: 32-byte array with value at [28..32] in LE
-    let mut account_leaf_index_bytes = [0u8; 32];
-    account_leaf_index_bytes[28..32]
-        .copy_from_slice(&(credential_account.leaf_index as u32).to_le_bytes());
-    proof_inputs.insert(
-        "account_leaf_index".to_string(),
-        vec![BigUint::from_bytes_be(&account_leaf_index_bytes).to_string()],
-    );
+    verification_account: &CompressedAccount,
+    address_tree_info: light_client::indexer::TreeInfo,
+    user_keypair: &Keypair,
+    attributes: &CredentialAttributes,
+    predicates: &AttributePredicates,
+) -> Result<Signature, ClientError>
+where
+    R: Rpc + Indexer,
+{
+    let (instruction_data, accounts) = build_verify_credential(
+        rpc,
+        payer,
+        credential_account,
+        verification_account,
+        address_tree_info,
+        user_keypair,
+        attributes,
+        predicates,
+    )
+    .await?;
+    let instruction = verify_credential_instruction(&instruction_data, accounts);
 
-    // Add address field - credential account has an address
-    let address = credential_account.address.unwrap_or([0u8; 32]);
-    proof_inputs.insert(
-        "address".to_string(),
-        vec![BigUint::from_bytes_be(&address).to_string()],
-    );
+    Ok(rpc
+        .create_and_send_transaction(&[instruction], &payer.pubkey(), &[payer])
+        .await?)
+}
 
-    proof_inputs.insert(
-        "merkle_tree_hashed".to_string(),
-        vec![BigUint::from_bytes_be(&merkle_tree_hashed).to_string()],
-    );
-    proof_inputs.insert(
-        "discriminator".to_string(),
-        vec![BigUint::from_bytes_be(&discriminator).to_string()],
-    );
-    proof_inputs.insert(
-        "issuer_hashed".to_string(),
-        vec![BigUint::from_bytes_be(&issuer_hashed).to_string()],
-    );
+/// Generates the credential proof against the current root of the credential's tree
+/// and returns the instruction data and accounts of `zk_verify_credential`.
+async fn build_verify_credential<R>(
+    rpc: &mut R,
+    payer: &Keypair,
+    credential_account: &CompressedAccount,
+    verification_account: &CompressedAccount,
+    address_tree_info: light_client::indexer::TreeInfo,
+    user_keypair: &Keypair,
+    attributes: &CredentialAttributes,
+    predicates: &AttributePredicates,
+) -> Result<(zk_id::instruction::ZkVerifyCredential, Vec<AccountMeta>), ClientError>
+where
+    R: Rpc + Indexer,
+{
+    // Disclose age and country to the verifier, encrypted to its encryption key
+    let disclosed_data = DisclosedAttributes::new(attributes, &[AGE, COUNTRY]).to_bytes();
+
+    zk_id::client::build_verify_credential(
+        rpc,
+        BUILD_DIR,
+        VerifyCredentialParams {
+            payer: payer.pubkey(),
+            credential_account,
+            verification_account,
+            address_tree_info,
+            credential: &CredentialKeypair::new(user_keypair),
+            attributes,
+            predicates,
+            disclosed_data,
+        },
+    )
+    .await
+}
 
-    // Add credential private key (private input) - already padded to 32 bytes
-    proof_inputs.insert(
-        "credentialPrivateKey".to_string(),
-        vec![BigUint::from_bytes_be(&credential.private_key).to_string()],
-    );
+async fn verify_credential_issuer_set<R>(
+    rpc: &mut R,
+    payer: &Keypair,
+    credential_account: &CompressedAccount,
+    verification_account: &CompressedAccount,
+    address_tree_info: light_client::indexer::TreeInfo,
+    user_keypair: &Keypair,
+    attributes: &CredentialAttributes,
+    issuer_set: &IssuerSet,
+) -> Result<Signature, ClientError>
+where
+    R: Rpc + Indexer,
+{
+    let (instruction_data, accounts) = build_verify_credential_issuer_set(
+        rpc,
+        payer,
+        credential_account,
+        verification_account,
+        address_tree_info,
+        user_keypair,
+        attributes,
+        issuer_set,
+    )
+    .await?;
+    let instruction = verify_credential_instruction(&instruction_data, accounts);
 
-    proof_inputs.insert(
-        "encrypted_data_hash".to_string(),
-        vec![BigUint::from_bytes_be(&encrypted_data_hash).to_string()],
-    );
-    proof_inputs.insert(
-        "public_encrypted_data_hash".to_string(),
-        vec![BigUint::from_bytes_be(&encrypted_data_hash).to_string()],
-    );
+    Ok(rpc
+        .create_and_send_transaction(&[instruction], &payer.pubkey(), &[payer])
+        .await?)
+}
 
-    // Add verification_id (public input) - pad to 32 bytes
-    let mut padded_verification = [0u8; 32];
-    padded_verification[1..32].copy_from_slice(verification_id);
-    proof_inputs.insert(
-        "verification_id".to_string(),
-        vec![BigUint::from_bytes_be(&padded_verification).to_string()],
-    );
+/// Generates a credential proof that hides the issuer in `issuer_set`
+/// and returns the instruction data and accounts of `zk_verify_credential_issuer_set`.
+async fn build_verify_credential_issuer_set<R>(
+    rpc: &mut R,
+    payer: &Keypair,
+    credential_account: &CompressedAccount,
+    verification_account: &CompressedAccount,
+    address_tree_info: light_client::indexer::TreeInfo,
+    user_keypair: &Keypair,
+    attributes: &CredentialAttributes,
+    issuer_set: &IssuerSet,
+) -> Result<
+    (
+        zk_id::instruction::ZkVerifyCredentialIssuerSet,
+        Vec<AccountMeta>,
+    ),
+    ClientError,
+>
+where
+    R: Rpc + Indexer,
+{
+    let disclosed_data = DisclosedAttributes::new(attributes, &[AGE]).to_bytes();
+
+    zk_id::client::build_verify_credential_issuer_set(
+        rpc,
+        BUILD_DIR,
+        VerifyCredentialParams {
+            payer: payer.pubkey(),
+            credential_account,
+            verification_account,
+            address_tree_info,
+            credential: &CredentialKeypair::new(user_keypair),
+            attributes,
+            predicates: &AttributePredicates::none(),
+            disclosed_data,
+        },
+        issuer_set,
+    )
+    .await
+}
 
-    // Compute nullifier
-    let nullifier = credential.compute_nullifier(verification_id, &issuer_hashed);
-    proof_inputs.insert(
-        "nullifier".to_string(),
-        vec![BigUint::from_bytes_be(&nullifier).to_string()],
-    );
+async fn revoke_credential<R>(
+    rpc: &mut R,
+    payer: &Keypair,
+    issuer_account: &CompressedAccount,
+    credential_account: &CompressedAccount,
+) -> Result<Signature, RpcError>
+where
+    R: Rpc + Indexer,
+{
+    let mut remaining_accounts = PackedAccounts::default();
+    remaining_accounts.add_pre_accounts_signer(payer.pubkey());
+    let config = SystemAccountMetaConfig::new(zk_id::ID);
+    remaining_accounts.add_system_accounts_v2(config)?;
 
-    // Add merkle proof inputs
-    let path_elements: Vec<String> = merkle_proof_hashes
-        .iter()
-        .map(|hash| BigUint::from_bytes_be(hash).to_string())
-        .collect();
-    proof_inputs.insert("pathElements".to_string(), path_elements);
+    let rpc_result = rpc
+        .get_validity_proof(
+            vec![issuer_account.hash, credential_account.hash],
+            vec![],
+            None,
+        )
+        .await?
+        .value;
 
-    // Use the actual merkle root from the indexer
-    let expected_root_bigint = BigUint::from_bytes_be(merkle_root);
-    proof_inputs.insert(
-        "expectedRoot".to_string(),
-        vec![expected_root_bigint.to_string()],
-    );
+    let packed_state_tree_accounts = rpc_result
+        .pack_tree_infos(&mut remaining_accounts)
+        .state_trees
+        .unwrap();
 
-    // Generate proof
-    let circuit_inputs = serde_json::to_string(&proof_inputs).unwrap();
-    let proof = CircomProver::prove(
-        ProofLib::Arkworks,
-        WitnessFn::RustWitness(compressedaccountmerkleproof_witness),
-        circuit_inputs,
-        zkey_path.clone(),
-    )
-    .expect("Proof generation failed");
+    // Write the updated issuer account to the credential's tree,
+    // so the revocation advances the root the credential was proven against.
+    let output_state_tree_index = credential_account
+        .tree_info
+        .pack_output_tree_index(&mut remaining_accounts)?;
 
-    // Verify proof locally
-    let is_valid = CircomProver::verify(ProofLib::Arkworks, proof.clone(), zkey_path.clone())
-        .expect("Proof verification failed");
-    assert!(is_valid, "Local circom proof verification should pass");
+    let issuer_account_meta = light_sdk::instruction::account_meta::CompressedAccountMeta {
+        tree_info: packed_state_tree_accounts.packed_tree_infos[0],
+        address: issuer_account.address.unwrap(),
+        output_state_tree_index,
+    };
+    let credential_account_meta = CompressedAccountMetaBurn {
+        tree_info: packed_state_tree_accounts.packed_tree_infos[1],
+        address: credential_account.address.unwrap(),
+    };
 
-    // Convert to groth16-solana format and compress
-    let (proof_a_uncompressed, proof_b_uncompressed, proof_c_uncompressed) =
-        convert_proof(&proof.proof).expect("Failed to convert proof");
+    let issuer_data = issuer_account.data.as_ref().unwrap();
+    let issuer_account_parsed: zk_id::IssuerAccount =
+        anchor_lang::AnchorDeserialize::deserialize(&mut issuer_data.data.as_slice()).unwrap();
+    let credential_data = credential_account.data.as_ref().unwrap();
+    let credential_account_parsed: zk_id::CredentialAccount =
+        anchor_lang::AnchorDeserialize::deserialize(&mut credential_data.data.as_slice()).unwrap();
 
-    use groth16_solana::proof_parser::circom_prover::convert_proof_to_compressed;
-    let (proof_a, proof_b, proof_c) = convert_proof_to_compressed(
-        &proof_a_uncompressed,
-        &proof_b_uncompressed,
-        &proof_c_uncompressed,
-    )
-    .expect("Failed to compress proof");
+    let (remaining_accounts_metas, system_accounts_offset, _) =
+        remaining_accounts.to_account_metas();
 
-    // Verify with groth16-solana locally (same as on-chain)
-    {
-        use groth16_solana::groth16::Groth16Verifier;
-        use groth16_solana::proof_parser::circom_prover::convert_public_inputs;
-
-        // Convert public inputs from the circom proof (8 public inputs in circuit)
-        let public_inputs_converted: [[u8; 32]; 8] = convert_public_inputs(&proof.pub_inputs);
-        println!("public_inputs_converted {:?}", public_inputs_converted);
-        // Create verifier using the uncompressed proofs (which have proof_a negated)
-        let mut verifier = Groth16Verifier::new(
-            &proof_a_uncompressed,
-            &proof_b_uncompressed,
-            &proof_c_uncompressed,
-            &public_inputs_converted,
-            &zk_id::verifying_key::VERIFYINGKEY,
-        )
-        .expect("Failed to create verifier");
+    let instruction_data = zk_id::instruction::RevokeCredential {
+        proof: rpc_result.proof,
+        system_accounts_offset: system_accounts_offset as u8,
+        issuer_account_meta,
+        issuer_pubkey: issuer_account_parsed.issuer_pubkey,
+        num_credentials_issued: issuer_account_parsed.num_credentials_issued,
+        authority: issuer_account_parsed.authority,
+        credential_account_meta,
+        credential_pubkey: credential_account_parsed
+            .credential_pubkey
+            .credential_pubkey,
+        expiry_slot: credential_account_parsed.expiry_slot,
+        attributes_hash: credential_account_parsed.attributes_hash.attributes_hash,
+    };
 
-        // Verify
-        verifier
-            .verify()
-            .expect("Local groth16-solana verification failed");
-    }
+    let accounts = zk_id::accounts::GenericAnchorAccounts {
+        signer: payer.pubkey(),
+    };
 
-    let compressed_proof =
-        light_compressed_account::instruction_data::compressed_proof::CompressedProof {
-            a: proof_a,
-            b: proof_b,
-            c: proof_c,
-        };
+    let instruction = Instruction {
+        program_id: zk_id::ID,
+        accounts: [accounts.to_account_metas(None), remaining_accounts_metas].concat(),
+        data: instruction_data.data(),
+    };
 
-    (compressed_proof, nullifier)
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[payer])
+        .await
 }
@@ -48,6 +48,8 @@ const WASM_PATH = path.join(BUILD_DIR, "compressed_account_merkle_proof_js/compr
 const ZKEY_PATH = path.join(BUILD_DIR, "compressed_account_merkle_proof_final.zkey");
 
 const MERKLE_TREE_DEPTH = 26;
+const NUM_CREDENTIAL_ATTRIBUTES = 4;
+const U64_MAX = (1n << 64n) - 1n;
 
 /** Hash to BN254 field (matching Light Protocol's hashv_to_bn254_field_size_be) */
 function hashToBn254Field(data: Uint8Array): Uint8Array {
@@ -112,34 +114,37 @@ describe("zk-id", () => {
   }
 
   /** Compute nullifier = Poseidon(verification_id, credentialPrivateKey, data_hash) */
-  /** where data_hash = Poseidon(issuer_hashed, credential_pubkey) */
   function computeNullifier(
     verificationId: Uint8Array,
     credentialPrivateKey: Uint8Array,
-    issuerHashed: Uint8Array,
-    credentialPubkey: Uint8Array
+    credentialDataHash: Uint8Array
   ): Uint8Array {
-    // First compute data_hash = Poseidon(issuer_hashed, credential_pubkey)
-    const dataHash = poseidon([
-      BigInt("0x" + Buffer.from(issuerHashed).toString("hex")),
-      BigInt("0x" + Buffer.from(credentialPubkey).toString("hex")),
-    ]);
-    const dataHashBigInt = poseidon.F.toObject(dataHash);
-
-    // Then compute nullifier = Poseidon(verification_id, credentialPrivateKey, data_hash)
     const hash = poseidon([
       BigInt("0x" + Buffer.from(verificationId).toString("hex")),
       BigInt("0x" + Buffer.from(credentialPrivateKey).toString("hex")),
-      dataHashBigInt,
+      BigInt("0x" + Buffer.from(credentialDataHash).toString("hex")),
     ]);
     return bigintToBytes32(poseidon.F.toObject(hash));
   }
 
-  /** Compute credential data hash = Poseidon(issuer_hashed, credential_pubkey) */
-  function computeCredentialDataHash(issuerHashed: Uint8Array, credentialPubkey: Uint8Array): Uint8Array {
+  /** Compute attributes hash = Poseidon(attributes, blinding) */
+  function computeAttributesHash(attributes: bigint[], blinding: Uint8Array): Uint8Array {
+    const hash = poseidon([...attributes, BigInt("0x" + Buffer.from(blinding).toString("hex"))]);
+    return bigintToBytes32(poseidon.F.toObject(hash));
+  }
+
+  /** Compute credential data hash = Poseidon(issuer_hashed, credential_pubkey, expiry_slot, attributes_hash) */
+  function computeCredentialDataHash(
+    issuerHashed: Uint8Array,
+    credentialPubkey: Uint8Array,
+    expirySlot: bigint,
+    attributesHash: Uint8Array
+  ): Uint8Array {
     const hash = poseidon([
       BigInt("0x" + Buffer.from(issuerHashed).toString("hex")),
       BigInt("0x" + Buffer.from(credentialPubkey).toString("hex")),
+      expirySlot,
+      BigInt("0x" + Buffer.from(attributesHash).toString("hex")),
     ]);
     return bigintToBytes32(poseidon.F.toObject(hash));
   }
@@ -212,6 +217,12 @@ describe("zk-id", () => {
     address: Uint8Array;
     pathElements: Uint8Array[];
     encryptedDataHash: Uint8Array;
+    currentSlot: bigint;
+    attributeMin: bigint[];
+    attributeMax: bigint[];
+    expirySlot: bigint;
+    attributes: bigint[];
+    attributesBlinding: Uint8Array;
   }): Promise<{ a: number[]; b: number[]; c: number[] }> {
     const inputs = {
       owner_hashed: toFieldString(params.ownerHashed),
@@ -228,6 +239,12 @@ describe("zk-id", () => {
       address: toFieldString(params.address),
       pathElements: params.pathElements.map(toFieldString),
       encrypted_data_hash: toFieldString(params.encryptedDataHash),
+      current_slot: params.currentSlot.toString(),
+      attribute_min: params.attributeMin.map((value) => value.toString()),
+      attribute_max: params.attributeMax.map((value) => value.toString()),
+      expiry_slot: params.expirySlot.toString(),
+      attributes: params.attributes.map((value) => value.toString()),
+      attributes_blinding: toFieldString(params.attributesBlinding),
     };
 
     const { proof } = await snarkjs.groth16.fullProve(inputs, WASM_PATH, ZKEY_PATH);
@@ -277,12 +294,16 @@ describe("zk-id", () => {
       const { privateKey, publicKey } = generateCredentialKeypair();
       const verificationId = generateFieldElement();
       const issuerHashed = hashToBn254Field(issuer.publicKey.toBytes());
-      const nullifier = computeNullifier(verificationId, privateKey, issuerHashed, publicKey);
+      const attributesHash = computeAttributesHash([25n, 276n, 0n, 0n], generateFieldElement());
+      const credentialDataHash = computeCredentialDataHash(issuerHashed, publicKey, 1000n, attributesHash);
+      const nullifier = computeNullifier(verificationId, privateKey, credentialDataHash);
 
-      // Compute data_hash = Poseidon(issuer_hashed, credential_pubkey)
+      // Compute data_hash = Poseidon(issuer_hashed, credential_pubkey, expiry_slot, attributes_hash)
       const dataHash = poseidon([
         BigInt("0x" + Buffer.from(issuerHashed).toString("hex")),
         BigInt("0x" + Buffer.from(publicKey).toString("hex")),
+        1000n,
+        BigInt("0x" + Buffer.from(attributesHash).toString("hex")),
       ]);
       const dataHashBigInt = poseidon.F.toObject(dataHash);
 
@@ -315,15 +336,29 @@ describe("zk-id", () => {
       const discriminator = new Uint8Array(32);
       discriminator.set(Buffer.from([0x2e, 0x9c, 0x4a, 0x87, 0x12, 0x34, 0x56, 0x78]), 24);
 
+      // Credential attributes [age, country, 0, 0], proving age >= 18 before expiry
+      const attributes = [25n, 276n, 0n, 0n];
+      const attributesBlinding = generateFieldElement();
+      const expirySlot = 1_000_000n;
+      const currentSlot = BigInt(await rpc.getSlot());
+      const attributeMin = [18n, 0n, 0n, 0n];
+      const attributeMax = Array.from({ length: NUM_CREDENTIAL_ATTRIBUTES }, () => U64_MAX);
+
+      const attributesHash = computeAttributesHash(attributes, attributesBlinding);
+      const credentialDataHash = computeCredentialDataHash(
+        issuerHashed,
+        credentialPubkey,
+        expirySlot,
+        attributesHash
+      );
+
       const verificationId = generateFieldElement();
-      const nullifier = computeNullifier(verificationId, credentialPrivateKey, issuerHashed, credentialPubkey);
+      const nullifier = computeNullifier(verificationId, credentialPrivateKey, credentialDataHash);
 
       const encryptedDataHash = generateFieldElement();
       const address = generateFieldElement();
       const pathElements = Array.from({ length: MERKLE_TREE_DEPTH }, () => new Uint8Array(32));
 
-      const credentialDataHash = computeCredentialDataHash(issuerHashed, credentialPubkey);
-
       const LAMPORTS_OFFSET = 36893488147419103232n;
       const accountHash = poseidon([
         BigInt("0x" + Buffer.from(ownerHashed).toString("hex")),
@@ -356,6 +391,12 @@ describe("zk-id", () => {
         address,
         pathElements,
         encryptedDataHash,
+        currentSlot,
+        attributeMin,
+        attributeMax,
+        expirySlot,
+        attributes,
+        attributesBlinding,
       });
 
       assert.ok(zkProof.a.length === 32, "Proof A should be 32 bytes");
@@ -366,12 +407,14 @@ describe("zk-id", () => {
     it("should verify nullifier uniqueness property", () => {
       const { privateKey, publicKey } = generateCredentialKeypair();
       const issuerHashed = hashToBn254Field(issuer.publicKey.toBytes());
+      const attributesHash = computeAttributesHash([25n, 276n, 0n, 0n], generateFieldElement());
+      const credentialDataHash = computeCredentialDataHash(issuerHashed, publicKey, 1000n, attributesHash);
 
       const verificationId1 = generateFieldElement();
       const verificationId2 = generateFieldElement();
 
-      const nullifier1 = computeNullifier(verificationId1, privateKey, issuerHashed, publicKey);
-      const nullifier2 = computeNullifier(verificationId2, privateKey, issuerHashed, publicKey);
+      const nullifier1 = computeNullifier(verificationId1, privateKey, credentialDataHash);
+      const nullifier2 = computeNullifier(verificationId2, privateKey, credentialDataHash);
 
       assert.notDeepStrictEqual(
         Array.from(nullifier1),